@@ -1,10 +1,41 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use rubikscube::{Cube, MetricKind};
 
 fn criterion_benchmark(c: &mut Criterion) {
     let mut cube = Cube::new(MetricKind::HalfTurnMetric);
     c.bench_function("cube turn", |b| b.iter(|| cube.turn(black_box(0))));
+
+    // random turns, as in scrambles and rollouts, defeat branch prediction on the turn kind
+    let mut rng = StdRng::seed_from_u64(0);
+    let twists: Vec<u8> = (0..1000)
+        .map(|_| rng.gen_range(0..MetricKind::HalfTurnMetric as u8))
+        .collect();
+
+    c.bench_function("cube 1000 random turns", |b| {
+        b.iter(|| {
+            for &twist in &twists {
+                cube.turn(black_box(twist)).unwrap();
+            }
+        })
+    });
+
+    let mut scrambled_cube = Cube::cube_htm();
+    scrambled_cube.scramble(100);
+
+    c.bench_function("cube clone", |b| b.iter(|| *black_box(&scrambled_cube)));
+
+    c.bench_function("cube hash", |b| {
+        b.iter(|| {
+            let mut hasher = DefaultHasher::new();
+            black_box(&scrambled_cube).hash(&mut hasher);
+            hasher.finish()
+        })
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
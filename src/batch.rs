@@ -0,0 +1,352 @@
+use rand::distributions::{Distribution, Uniform};
+
+use crate::{
+    cube::Cube,
+    cubies::*,
+    errors::CubeError,
+    moves::MetricKind,
+    turn_table::{CubieTurn, TURN_TABLE},
+};
+
+const EDGES: usize = NUM_EDGES as usize;
+const CORNERS: usize = NUM_CORNERS as usize;
+
+/// A batch of cubes stored in structure of arrays layout.
+///
+/// The state of all cubes is kept in four flat arrays, one per cubie kind and property,
+/// so that a whole batch can be turned and encoded without allocating per cube. Intended
+/// for running many reinforcement learning rollouts in lockstep.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CubeBatch {
+    edge_orientation: Vec<u8>,
+    corner_orientation: Vec<u8>,
+    edge_permutation: Vec<u8>,
+    corner_permutation: Vec<u8>,
+    turn_metric: MetricKind,
+}
+
+impl CubeBatch {
+    /// Creates a batch of `size` solved cubes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{CubeBatch, MetricKind};
+    ///
+    /// let batch = CubeBatch::new(64, MetricKind::HalfTurnMetric);
+    /// assert_eq!(batch.len(), 64);
+    /// assert!(batch.solved().iter().all(|&solved| solved));
+    /// ```
+    pub fn new(size: usize, turn_metric: MetricKind) -> CubeBatch {
+        CubeBatch {
+            edge_orientation: vec![0; size * EDGES],
+            corner_orientation: vec![0; size * CORNERS],
+            edge_permutation: (0..size).flat_map(|_| 0..NUM_EDGES).collect(),
+            corner_permutation: (0..size).flat_map(|_| 0..NUM_CORNERS).collect(),
+            turn_metric,
+        }
+    }
+
+    /// Creates a batch holding copies of `cubes`, using the turn metric they share.
+    ///
+    /// Returns `CubeError::MixedTurnMetrics` with the index of the first cube whose turn
+    /// metric differs from that of the first cube.
+    pub fn from_cubes(cubes: &[Cube]) -> Result<CubeBatch, CubeError> {
+        let turn_metric = cubes
+            .first()
+            .map_or(MetricKind::HalfTurnMetric, Cube::turn_metric);
+        if let Some(idx) = cubes
+            .iter()
+            .position(|cube| cube.turn_metric() != turn_metric)
+        {
+            return Err(CubeError::MixedTurnMetrics(idx));
+        }
+        let mut batch = CubeBatch::new(cubes.len(), turn_metric);
+        for (idx, cube) in cubes.iter().enumerate() {
+            batch.set(idx, cube);
+        }
+        Ok(batch)
+    }
+
+    /// Returns the number of cubes in the batch.
+    pub fn len(&self) -> usize {
+        self.corner_permutation.len() / CORNERS
+    }
+
+    /// Returns true if the batch holds no cubes.
+    pub fn is_empty(&self) -> bool {
+        self.corner_permutation.is_empty()
+    }
+
+    /// Returns the turn metric of the batch.
+    pub fn turn_metric(&self) -> MetricKind {
+        self.turn_metric
+    }
+
+    /// Returns a copy of the cube at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn get(&self, idx: usize) -> Cube {
+        Cube::from_cubicles(
+            &self.edge_orientation[idx * EDGES..][..EDGES],
+            &self.corner_orientation[idx * CORNERS..][..CORNERS],
+            &self.edge_permutation[idx * EDGES..][..EDGES],
+            &self.corner_permutation[idx * CORNERS..][..CORNERS],
+            self.turn_metric,
+        )
+    }
+
+    /// Replaces the cube at `idx` with a copy of `cube`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn set(&mut self, idx: usize, cube: &Cube) {
+        let [eo, co, ep, cp] = cube.cubicles();
+        self.edge_orientation[idx * EDGES..][..EDGES].copy_from_slice(eo);
+        self.corner_orientation[idx * CORNERS..][..CORNERS].copy_from_slice(co);
+        self.edge_permutation[idx * EDGES..][..EDGES].copy_from_slice(ep);
+        self.corner_permutation[idx * CORNERS..][..CORNERS].copy_from_slice(cp);
+    }
+
+    /// Performs `twists[i]` on the `i`th cube, for every cube in the batch.
+    ///
+    /// All twists are checked before any cube is turned.
+    ///
+    /// # Arguments
+    ///
+    /// * `twists` - index of turn enum variant for every cube
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{CubeBatch, MetricKind, Turn};
+    ///
+    /// let mut batch = CubeBatch::new(2, MetricKind::HalfTurnMetric);
+    /// batch.turn(&[Turn::L as u8, Turn::R as u8]).unwrap();
+    /// batch.turn(&[Turn::L_ as u8, Turn::R2 as u8]).unwrap();
+    ///
+    /// assert_eq!(batch.solved(), vec![true, false]);
+    /// ```
+    pub fn turn(&mut self, twists: &[u8]) -> Result<(), CubeError> {
+        if twists.len() != self.len() {
+            return Err(CubeError::InvalidBufferLength(self.len(), twists.len()));
+        }
+        if let Some(&twist) = twists
+            .iter()
+            .find(|&&twist| twist >= self.turn_metric as u8)
+        {
+            return Err(CubeError::InvalidTurn(twist, self.turn_metric as u8));
+        }
+
+        for (idx, &twist) in twists.iter().enumerate() {
+            self.turn_cube(idx, twist);
+        }
+        Ok(())
+    }
+
+    /// Performs an already checked turn on the cube at `idx`.
+    #[inline]
+    fn turn_cube(&mut self, idx: usize, twist: u8) {
+        let CubieTurn { edges, corners } = &TURN_TABLE[twist as usize];
+
+        edges.apply::<NUM_EDGE_ORIENTATION>(
+            &mut self.edge_permutation[idx * EDGES..][..EDGES],
+            &mut self.edge_orientation[idx * EDGES..][..EDGES],
+        );
+        corners.apply::<NUM_CORNER_ORIENTATION>(
+            &mut self.corner_permutation[idx * CORNERS..][..CORNERS],
+            &mut self.corner_orientation[idx * CORNERS..][..CORNERS],
+        );
+    }
+
+    /// Returns true if the cube at `idx` is solved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn is_solved(&self, idx: usize) -> bool {
+        let edges_solved = self.edge_orientation[idx * EDGES..][..EDGES]
+            .iter()
+            .zip(&self.edge_permutation[idx * EDGES..][..EDGES])
+            .enumerate()
+            .all(|(cubicle, (&orientation, &cubie))| orientation == 0 && cubie as usize == cubicle);
+        let corners_solved = self.corner_orientation[idx * CORNERS..][..CORNERS]
+            .iter()
+            .zip(&self.corner_permutation[idx * CORNERS..][..CORNERS])
+            .enumerate()
+            .all(|(cubicle, (&orientation, &cubie))| orientation == 0 && cubie as usize == cubicle);
+
+        edges_solved && corners_solved
+    }
+
+    /// Returns whether every cube in the batch is solved.
+    pub fn solved(&self) -> Vec<bool> {
+        (0..self.len()).map(|idx| self.is_solved(idx)).collect()
+    }
+
+    /// Scrambles every cube in the batch with `num_turns` random turns.
+    pub fn scramble(&mut self, num_turns: u32) {
+        for idx in 0..self.len() {
+            self.scramble_cube(idx, num_turns);
+        }
+    }
+
+    /// Resets every solved cube to the solved state scrambled with `num_turns` random turns.
+    ///
+    /// Returns the number of cubes that were reset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{CubeBatch, MetricKind};
+    ///
+    /// let mut batch = CubeBatch::new(8, MetricKind::HalfTurnMetric);
+    /// assert_eq!(batch.reset_solved(0), 8);
+    /// ```
+    pub fn reset_solved(&mut self, num_turns: u32) -> usize {
+        let mut num_reset = 0;
+        for idx in 0..self.len() {
+            if self.is_solved(idx) {
+                self.set(idx, &Cube::new(self.turn_metric));
+                self.scramble_cube(idx, num_turns);
+                num_reset += 1;
+            }
+        }
+        num_reset
+    }
+
+    fn scramble_cube(&mut self, idx: usize, num_turns: u32) {
+        let between = Uniform::from(0..self.turn_metric as u8);
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..num_turns {
+            self.turn_cube(idx, between.sample(&mut rng));
+        }
+    }
+
+    /// Writes the one-hot representation of every cube into `buffer`.
+    ///
+    /// The representation of the `i`th cube, as returned by `Cube::representation`, is
+    /// written to `buffer[i * REPRESENTATION_SIZE..(i + 1) * REPRESENTATION_SIZE]`. Works
+    /// with any element type convertible from `bool`, such as `f32` and `u8`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{CubeBatch, MetricKind, REPRESENTATION_SIZE};
+    ///
+    /// let mut batch = CubeBatch::new(4, MetricKind::HalfTurnMetric);
+    /// batch.scramble(20);
+    ///
+    /// let mut buffer = vec![0f32; 4 * REPRESENTATION_SIZE];
+    /// batch.write_representation(&mut buffer).unwrap();
+    ///
+    /// assert_eq!(buffer.iter().sum::<f32>(), 4.0 * 20.0);
+    /// ```
+    pub fn write_representation<T: Copy + From<bool>>(
+        &self,
+        buffer: &mut [T],
+    ) -> Result<(), CubeError> {
+        let expected_len = self.len() * REPRESENTATION_SIZE;
+        if buffer.len() != expected_len {
+            return Err(CubeError::InvalidBufferLength(expected_len, buffer.len()));
+        }
+
+        buffer.fill(T::from(false));
+        for (idx, repr) in buffer.chunks_exact_mut(REPRESENTATION_SIZE).enumerate() {
+            let corner_orientation = &self.corner_orientation[idx * CORNERS..][..CORNERS];
+            let corner_permutation = &self.corner_permutation[idx * CORNERS..][..CORNERS];
+            for cubicle in 0..NUM_CORNERS {
+                let cubie = corner_permutation[cubicle as usize];
+                let orientation = corner_orientation[cubicle as usize];
+                repr[corner_state_index(cubicle, cubie, orientation)] = T::from(true);
+            }
+
+            let edge_orientation = &self.edge_orientation[idx * EDGES..][..EDGES];
+            let edge_permutation = &self.edge_permutation[idx * EDGES..][..EDGES];
+            for cubicle in 0..NUM_EDGES {
+                let cubie = edge_permutation[cubicle as usize];
+                let orientation = edge_orientation[cubicle as usize];
+                repr[edge_state_index(cubicle, cubie, orientation)] = T::from(true);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CubeBatch;
+    use crate::cubies::REPRESENTATION_SIZE;
+    use crate::errors::CubeError;
+    use crate::{Cube, MetricKind};
+
+    #[test]
+    fn batch_matches_cubes_test() {
+        let mut cubes = vec![Cube::cube_htm(); 16];
+        for cube in cubes.iter_mut() {
+            cube.scramble(30);
+        }
+        let mut batch = CubeBatch::from_cubes(&cubes).unwrap();
+
+        for step in 0..50 {
+            let twists: Vec<u8> = (0..16)
+                .map(|idx| ((idx * 5 + step * 7) % 18) as u8)
+                .collect();
+            batch.turn(&twists).unwrap();
+            for (cube, &twist) in cubes.iter_mut().zip(twists.iter()) {
+                cube.turn(twist).unwrap();
+            }
+        }
+
+        for (idx, cube) in cubes.iter().enumerate() {
+            assert_eq!(batch.get(idx), *cube);
+            assert_eq!(batch.is_solved(idx), cube.solved());
+        }
+
+        let mut buffer = vec![0u8; 16 * REPRESENTATION_SIZE];
+        batch.write_representation(&mut buffer).unwrap();
+        for (repr, cube) in buffer.chunks_exact(REPRESENTATION_SIZE).zip(cubes.iter()) {
+            let expected: Vec<u8> = cube.representation().iter().map(|&b| b as u8).collect();
+            assert_eq!(repr, &expected[..]);
+        }
+    }
+
+    #[test]
+    fn reset_solved_test() {
+        let mut batch = CubeBatch::new(4, MetricKind::QuarterTurnMetric);
+        batch.turn(&[0, 1, 2, 3]).unwrap();
+        batch.turn(&[6, 1, 8, 3]).unwrap();
+
+        assert_eq!(batch.solved(), vec![true, false, true, false]);
+        assert_eq!(batch.reset_solved(0), 2);
+        assert_eq!(batch.reset_solved(0), 2);
+        assert!(batch.get(1).is_solvable());
+    }
+
+    #[test]
+    fn batch_err_test() {
+        let mut batch = CubeBatch::new(2, MetricKind::QuarterTurnMetric);
+
+        assert_eq!(batch.turn(&[0, 12]), Err(CubeError::InvalidTurn(12, 12)));
+        assert_eq!(batch.turn(&[0]), Err(CubeError::InvalidBufferLength(2, 1)));
+
+        let mut buffer = vec![0f32; REPRESENTATION_SIZE];
+        assert_eq!(
+            batch.write_representation(&mut buffer),
+            Err(CubeError::InvalidBufferLength(
+                2 * REPRESENTATION_SIZE,
+                REPRESENTATION_SIZE
+            ))
+        );
+        assert!(batch.solved().iter().all(|&solved| solved));
+
+        assert_eq!(
+            CubeBatch::from_cubes(&[Cube::cube_qtm(), Cube::cube_qtm(), Cube::cube_htm()]),
+            Err(CubeError::MixedTurnMetrics(2))
+        );
+    }
+}
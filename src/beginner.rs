@@ -0,0 +1,476 @@
+//! A layer by layer solver following the beginner method, for solutions people can follow.
+//!
+//! The white cross is solved first on the D face, then the white corners, the edges of the
+//! middle layer and finally the yellow last layer on the U face: its cross, the orientation of
+//! its corners, the position of its corners and the position of its edges. Apart from the
+//! cross, which is solved in as few turns as possible, every stage only uses a few well known
+//! algorithms and turns of the U face, picking the shortest combination of them. Solutions are
+//! long but every step is one a person can learn.
+//!
+//! Each stage only needs a bounded number of algorithms from any solvable cube, so the search
+//! always ends, and it tries algorithms in a fixed order, so the same cube always gets the
+//! same solution.
+
+use std::sync::OnceLock;
+
+use crate::{
+    bfs::distance_table,
+    cubies::{Faces, NUM_EDGES, NUM_EDGE_ORIENTATION},
+    errors::CubeError,
+    moves::{in_metric, MetricKind, Turn},
+    turn_table::TURN_TABLE,
+    Cube,
+};
+
+/// Most algorithms a stage of a solvable cube needs, to bound the search.
+const MAX_ALGORITHMS: usize = 8;
+
+/// Edge cubies of the white cross, also their solved cubicles.
+const CROSS_EDGES: [u8; 4] = [8, 9, 10, 11];
+
+/// Number of positions of a single edge, `2 * cubicle + orientation`.
+const EDGE_STATES: usize = 2 * NUM_EDGES as usize;
+
+/// White corners and middle layer edges in the order they are solved, starting with the
+/// front right slot and going around the cube like `rotate`.
+pub(crate) const FIRST_LAYER_CORNERS: [usize; 4] = [6, 5, 4, 7];
+pub(crate) const MIDDLE_LAYER_EDGES: [usize; 4] = [6, 5, 4, 7];
+
+/// Face each face goes to when the cube is rotated a quarter turn around the U face, in the
+/// order L, R, F, B, U, D.
+const ROTATION: [u8; 6] = [2, 3, 1, 0, 4, 5];
+
+/// Algorithms of the first two layers, written for the front right slot.
+const CORNER_INSERTION: &str = "R U R' U'";
+const RIGHT_EDGE_INSERTION: &str = "U R U' R' U' F' U F";
+const LEFT_EDGE_INSERTION: &str = "U' F' U F U R U' R'";
+
+/// Adjustments of the U face, tried before any algorithm.
+const U_TURNS: [Turn; 3] = [Turn::U, Turn::U_, Turn::U2];
+
+/// The turns solving one stage and the cube once they are applied.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Stage {
+    pub name: &'static str,
+    pub turns: Vec<Turn>,
+    pub cube: Cube,
+}
+
+fn parse(algorithm: &str) -> Vec<Turn> {
+    Turn::parse_sequence(algorithm).unwrap()
+}
+
+/// Returns `turns` as seen after rotating the cube a quarter turn around the U face.
+fn rotate(turns: &[Turn]) -> Vec<Turn> {
+    turns
+        .iter()
+        .map(|&turn| {
+            let twist = turn as u8;
+            Turn::from_u8(twist - twist % 6 + ROTATION[(twist % 6) as usize]).unwrap()
+        })
+        .collect()
+}
+
+/// Returns the algorithm for each of the four slots of the middle layer.
+fn in_every_slot(algorithm: &str) -> Vec<Vec<Turn>> {
+    let mut algorithms = vec![parse(algorithm)];
+    for _ in 1..4 {
+        let rotated = rotate(algorithms.last().unwrap());
+        algorithms.push(rotated);
+    }
+    algorithms
+}
+
+/// The U turns followed by the given algorithms.
+fn with_u_turns(algorithms: Vec<Vec<Turn>>) -> Vec<Vec<Turn>> {
+    U_TURNS
+        .iter()
+        .map(|&turn| vec![turn])
+        .chain(algorithms)
+        .collect()
+}
+
+pub(crate) fn edge_solved(cube: &Cube, cubicle: usize) -> bool {
+    let [eo, _, ep, _] = cube.cubicles();
+    ep[cubicle] as usize == cubicle && eo[cubicle] == 0
+}
+
+pub(crate) fn corner_solved(cube: &Cube, cubicle: usize) -> bool {
+    let [_, co, _, cp] = cube.cubicles();
+    cp[cubicle] as usize == cubicle && co[cubicle] == 0
+}
+
+pub(crate) fn cross_solved(cube: &Cube) -> bool {
+    CROSS_EDGES
+        .iter()
+        .all(|&cubicle| edge_solved(cube, cubicle as usize))
+}
+
+/// Returns true if the cross and the first `corners` white corners are solved.
+fn first_layer_solved(cube: &Cube, corners: usize) -> bool {
+    cross_solved(cube)
+        && FIRST_LAYER_CORNERS[..corners]
+            .iter()
+            .all(|&cubicle| corner_solved(cube, cubicle))
+}
+
+/// Returns true if the first layer and the first `edges` middle layer edges are solved.
+fn middle_layer_solved(cube: &Cube, edges: usize) -> bool {
+    first_layer_solved(cube, FIRST_LAYER_CORNERS.len())
+        && MIDDLE_LAYER_EDGES[..edges]
+            .iter()
+            .all(|&cubicle| edge_solved(cube, cubicle))
+}
+
+pub(crate) fn first_two_layers_solved(cube: &Cube) -> bool {
+    middle_layer_solved(cube, MIDDLE_LAYER_EDGES.len())
+}
+
+/// Returns true if the facelets of the U face at `positions` are yellow.
+fn yellow(cube: &Cube, positions: &[(usize, usize)]) -> bool {
+    let face = cube.faces()[Faces::Yellow as usize];
+    positions
+        .iter()
+        .all(|&(row, column)| face[row][column] == Faces::Yellow)
+}
+
+fn last_layer_cross_solved(cube: &Cube) -> bool {
+    first_two_layers_solved(cube) && yellow(cube, &[(0, 1), (1, 0), (1, 2), (2, 1)])
+}
+
+pub(crate) fn last_layer_oriented(cube: &Cube) -> bool {
+    last_layer_cross_solved(cube) && yellow(cube, &[(0, 0), (0, 2), (2, 0), (2, 2)])
+}
+
+fn last_layer_corners_solved(cube: &Cube) -> bool {
+    last_layer_oriented(cube) && (0..4).all(|cubicle| corner_solved(cube, cubicle))
+}
+
+/// A stage of the last layer, solved with U turns and a few algorithms.
+struct LastLayerStage {
+    name: &'static str,
+    algorithms: &'static [&'static str],
+    goal: fn(&Cube) -> bool,
+}
+
+/// The last layer stages: a cross, the orientation with the Sune, then the corners and the
+/// edges with 3-cycles in both directions.
+const LAST_LAYER: [LastLayerStage; 4] = [
+    LastLayerStage {
+        name: "last layer cross",
+        algorithms: &["F R U R' U' F'"],
+        goal: last_layer_cross_solved,
+    },
+    LastLayerStage {
+        name: "last layer orientation",
+        algorithms: &["R U R' U R U2 R'"],
+        goal: last_layer_oriented,
+    },
+    LastLayerStage {
+        name: "last layer corners",
+        algorithms: &["R' F R' B2 R F' R' B2 R2", "R2 B2 R F R' B2 R F' R"],
+        goal: last_layer_corners_solved,
+    },
+    LastLayerStage {
+        name: "last layer edges",
+        algorithms: &["R U' R U R U R U' R' U' R2", "R2 U R U R' U' R' U' R' U R'"],
+        goal: Cube::solved,
+    },
+];
+
+/// Distance to a solved cross of every position of the cross edges, indexed by the edge
+/// positions as the digits of a number in base `EDGE_STATES`.
+struct CrossTable {
+    /// position of a single edge after each turn
+    moves: [[u8; MetricKind::HalfTurnMetric as usize]; EDGE_STATES],
+    distances: Vec<u8>,
+}
+
+impl CrossTable {
+    fn new() -> CrossTable {
+        let mut moves = [[0; MetricKind::HalfTurnMetric as usize]; EDGE_STATES];
+        for (state, moved) in moves.iter_mut().enumerate() {
+            let (cubicle, orientation) = ((state / 2) as u8, state as u8 % 2);
+            for (turn, moved) in moved.iter_mut().enumerate() {
+                let edges = &TURN_TABLE[turn].edges;
+                *moved = match edges.sources.iter().position(|&c| c == cubicle) {
+                    Some(k) => {
+                        let orientation = (orientation + edges.twists[k]) % NUM_EDGE_ORIENTATION;
+                        2 * edges.targets[k] + orientation
+                    }
+                    None => state as u8,
+                };
+            }
+        }
+
+        let mut table = CrossTable {
+            moves,
+            distances: Vec::new(),
+        };
+        let solved = table.index(CROSS_EDGES.map(|cubicle| 2 * cubicle));
+        table.distances = distance_table(
+            EDGE_STATES.pow(CROSS_EDGES.len() as u32),
+            [solved],
+            MetricKind::HalfTurnMetric as usize,
+            |index, turn| table.turn(index, turn),
+        );
+        table
+    }
+
+    fn get() -> &'static CrossTable {
+        static TABLE: OnceLock<CrossTable> = OnceLock::new();
+        TABLE.get_or_init(CrossTable::new)
+    }
+
+    fn index(&self, states: [u8; 4]) -> usize {
+        states
+            .iter()
+            .fold(0, |index, &state| index * EDGE_STATES + state as usize)
+    }
+
+    fn turn(&self, index: usize, turn: usize) -> usize {
+        let mut states = [0; 4];
+        let mut rest = index;
+        for state in states.iter_mut().rev() {
+            *state = self.moves[rest % EDGE_STATES][turn];
+            rest /= EDGE_STATES;
+        }
+        self.index(states)
+    }
+
+    /// Returns a shortest sequence of turns solving the cross of `cube`.
+    fn solve(&self, cube: &Cube) -> Vec<Turn> {
+        let [eo, _, ep, _] = cube.cubicles();
+        let mut index = self.index(CROSS_EDGES.map(|edge| {
+            let cubicle = ep.iter().position(|&cubie| cubie == edge).unwrap();
+            2 * cubicle as u8 + eo[cubicle]
+        }));
+        let mut turns = Vec::new();
+        while self.distances[index] > 0 {
+            let turn = (0..MetricKind::HalfTurnMetric as usize)
+                .find(|&turn| self.distances[self.turn(index, turn)] < self.distances[index])
+                .unwrap();
+            turns.push(Turn::from_u8(turn as u8).unwrap());
+            index = self.turn(index, turn);
+        }
+        turns
+    }
+}
+
+fn apply(cube: &mut Cube, turns: &[Turn]) {
+    for &turn in turns {
+        cube.turn(turn as u8).unwrap();
+    }
+}
+
+/// Returns the shortest combination of `algorithms` after which `goal` holds, preferring
+/// algorithms listed first. The U turns listed first are never used twice in a row.
+fn search(cube: &Cube, algorithms: &[Vec<Turn>], goal: &dyn Fn(&Cube) -> bool) -> Vec<Turn> {
+    fn search_depth(
+        cube: &Cube,
+        algorithms: &[Vec<Turn>],
+        goal: &dyn Fn(&Cube) -> bool,
+        depth: usize,
+        after_u_turn: bool,
+        path: &mut Vec<usize>,
+    ) -> bool {
+        if depth == 0 {
+            return goal(cube);
+        }
+        for (index, algorithm) in algorithms.iter().enumerate() {
+            let u_turn = index < U_TURNS.len();
+            if u_turn && after_u_turn {
+                continue;
+            }
+            let mut next = *cube;
+            apply(&mut next, algorithm);
+            path.push(index);
+            if search_depth(&next, algorithms, goal, depth - 1, u_turn, path) {
+                return true;
+            }
+            path.pop();
+        }
+        false
+    }
+
+    for depth in 0..=MAX_ALGORITHMS {
+        let mut path = Vec::new();
+        if search_depth(cube, algorithms, goal, depth, false, &mut path) {
+            return path
+                .into_iter()
+                .flat_map(|index| algorithms[index].iter().copied())
+                .collect();
+        }
+    }
+    unreachable!(
+        "every stage of a solvable cube needs at most {} algorithms",
+        MAX_ALGORITHMS
+    )
+}
+
+/// Solves `pieces` pieces one after the other, the `n`th once `goal(n, cube)` holds, and
+/// returns the turns of all of them.
+fn solve_pieces(
+    cube: &mut Cube,
+    algorithms: &[Vec<Turn>],
+    pieces: usize,
+    goal: fn(&Cube, usize) -> bool,
+) -> Vec<Turn> {
+    let mut turns = Vec::new();
+    for solved in 1..=pieces {
+        let found = search(cube, algorithms, &|cube| goal(cube, solved));
+        apply(cube, &found);
+        turns.extend(found);
+    }
+    turns
+}
+
+/// Solves a cube with the beginner method, returning the turns of every stage and the cube
+/// after it.
+///
+/// Half turns are written as two quarter turns for cubes in the quarter turn metric. Returns
+/// `CubeError::InvalidState` if the cube is not solvable.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{beginner, Cube};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.scramble(30);
+///
+/// let stages = beginner::solve(&cube).unwrap();
+/// assert_eq!(stages[0].name, "cross");
+/// assert!(stages.last().unwrap().cube.solved());
+/// for stage in stages {
+///     for turn in stage.turns {
+///         cube.turn(turn as u8).unwrap();
+///     }
+///     assert_eq!(cube, stage.cube);
+/// }
+/// ```
+pub fn solve(cube: &Cube) -> Result<Vec<Stage>, CubeError> {
+    if !cube.is_solvable() {
+        return Err(CubeError::InvalidState);
+    }
+    let [eo, co, ep, cp] = cube.cubicles();
+    let mut current = Cube::from_cubicles(eo, co, ep, cp, MetricKind::HalfTurnMetric);
+    let mut stages = Vec::new();
+    let mut finish = |name, turns: Vec<Turn>, current: &Cube| {
+        let [eo, co, ep, cp] = current.cubicles();
+        stages.push(Stage {
+            name,
+            turns: in_metric(turns, cube.turn_metric()),
+            cube: Cube::from_cubicles(eo, co, ep, cp, cube.turn_metric()),
+        });
+    };
+
+    let turns = CrossTable::get().solve(&current);
+    apply(&mut current, &turns);
+    finish("cross", turns, &current);
+
+    let algorithms = with_u_turns(in_every_slot(CORNER_INSERTION));
+    let turns = solve_pieces(
+        &mut current,
+        &algorithms,
+        FIRST_LAYER_CORNERS.len(),
+        first_layer_solved,
+    );
+    finish("first layer corners", turns, &current);
+
+    let mut insertions = in_every_slot(RIGHT_EDGE_INSERTION);
+    insertions.extend(in_every_slot(LEFT_EDGE_INSERTION));
+    let turns = solve_pieces(
+        &mut current,
+        &with_u_turns(insertions),
+        MIDDLE_LAYER_EDGES.len(),
+        middle_layer_solved,
+    );
+    finish("second layer", turns, &current);
+
+    for stage in LAST_LAYER {
+        let algorithms = stage.algorithms.iter().map(|&a| parse(a)).collect();
+        let turns = search(&current, &with_u_turns(algorithms), &stage.goal);
+        apply(&mut current, &turns);
+        finish(stage.name, turns, &current);
+    }
+    Ok(stages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, CrossTable};
+    use crate::{errors::CubeError, Cube, MetricKind, Turn};
+
+    const STAGES: [&str; 7] = [
+        "cross",
+        "first layer corners",
+        "second layer",
+        "last layer cross",
+        "last layer orientation",
+        "last layer corners",
+        "last layer edges",
+    ];
+
+    #[test]
+    fn solve_test() {
+        for depth in 0..15 {
+            let mut cube = Cube::cube_htm();
+            cube.scramble(2 * depth);
+            let stages = solve(&cube).unwrap();
+            assert_eq!(stages, solve(&cube).unwrap());
+
+            let names: Vec<_> = stages.iter().map(|stage| stage.name).collect();
+            assert_eq!(names, STAGES);
+            for stage in stages {
+                for turn in stage.turns {
+                    cube.turn(turn as u8).unwrap();
+                }
+                assert_eq!(cube, stage.cube);
+            }
+            assert!(cube.solved());
+        }
+    }
+
+    #[test]
+    fn cross_table_test() {
+        let table = CrossTable::get();
+        // every placement of the 4 cross edges is reached, in at most 8 turns
+        let reached = table.distances.iter().filter(|&&d| d != u8::MAX).count();
+        assert_eq!(reached, 24 * 22 * 20 * 18);
+        assert_eq!(
+            table.distances.iter().filter(|&&d| d != u8::MAX).max(),
+            Some(&8)
+        );
+
+        let mut cube = Cube::cube_htm();
+        for turn in Turn::parse_sequence("F R' D2").unwrap() {
+            cube.turn(turn as u8).unwrap();
+        }
+        assert_eq!(table.solve(&cube).len(), 3);
+    }
+
+    #[test]
+    fn quarter_turn_metric_test() {
+        let mut cube = Cube::cube_qtm();
+        cube.scramble(25);
+        for stage in solve(&cube).unwrap() {
+            assert_eq!(stage.cube.turn_metric(), MetricKind::QuarterTurnMetric);
+            for turn in stage.turns {
+                cube.turn(turn as u8).unwrap();
+            }
+        }
+        assert!(cube.solved());
+    }
+
+    #[test]
+    fn unsolvable_test() {
+        let [eo, co, ep, _] = Cube::cube_htm().cubicles().map(<[u8]>::to_vec);
+        let swapped = [1, 0, 2, 3, 4, 5, 6, 7];
+        let cube = Cube::from_cubicles(&eo, &co, &ep, &swapped, MetricKind::HalfTurnMetric);
+        assert_eq!(solve(&cube), Err(CubeError::InvalidState));
+
+        let stages = solve(&Cube::cube_htm()).unwrap();
+        assert!(stages.iter().all(|stage| stage.turns.is_empty()));
+    }
+}
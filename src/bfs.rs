@@ -0,0 +1,445 @@
+//! Breadth-first enumeration of the states reachable with a subset of turns.
+//!
+//! An [`Explorer`] tracks only the chosen [`Components`] of the cube state, so it can count
+//! the states of subgroups such as the corners of a 2x2 cube or the edge orientations. Every
+//! projected state has a coordinate, its rank among all arrangements of the components, and
+//! visited states are kept in a bitmap indexed by coordinate.
+
+use crate::{
+    cubies::{NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES, NUM_EDGE_ORIENTATION},
+    errors::CubeError,
+    moves::{MetricKind, Turn},
+    Cube,
+};
+
+/// Largest number of coordinates of an [`Explorer`], so the visited bitmap fits in 512 MiB.
+pub const MAX_COORDINATES: u64 = 1 << 32;
+
+/// Components of the cube state tracked by an [`Explorer`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Components {
+    pub corner_permutation: bool,
+    pub corner_orientation: bool,
+    pub edge_permutation: bool,
+    pub edge_orientation: bool,
+}
+
+impl Components {
+    /// Permutation and orientation of the corners.
+    pub const CORNERS: Components = Components {
+        corner_permutation: true,
+        corner_orientation: true,
+        edge_permutation: false,
+        edge_orientation: false,
+    };
+
+    /// Permutation and orientation of the edges.
+    pub const EDGES: Components = Components {
+        corner_permutation: false,
+        corner_orientation: false,
+        edge_permutation: true,
+        edge_orientation: true,
+    };
+}
+
+pub(crate) const fn factorial(n: u8) -> u64 {
+    if n == 0 {
+        1
+    } else {
+        n as u64 * factorial(n - 1)
+    }
+}
+
+/// Rank of a permutation among the permutations of its length, in lexicographic order.
+pub(crate) fn rank_permutation(permutation: &[u8]) -> u64 {
+    let mut rank = 0;
+    for (i, &cubie) in permutation.iter().enumerate() {
+        let smaller = permutation[i + 1..].iter().filter(|&&c| c < cubie).count();
+        rank = rank * (permutation.len() - i) as u64 + smaller as u64;
+    }
+    rank
+}
+
+/// Inverse of `rank_permutation`.
+pub(crate) fn unrank_permutation(mut rank: u64, permutation: &mut [u8]) {
+    let length = permutation.len();
+    let mut unused = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+    for (i, cubie) in permutation.iter_mut().enumerate() {
+        let place = factorial((length - 1 - i) as u8);
+        let index = (rank / place) as usize;
+        rank %= place;
+        *cubie = unused[index];
+        unused.copy_within(index + 1..length - i, index);
+    }
+}
+
+/// Rank of orientations read as the digits of a number in base `M`.
+pub(crate) fn rank_orientation<const M: u8>(orientations: &[u8]) -> u64 {
+    orientations
+        .iter()
+        .fold(0, |rank, &orientation| rank * M as u64 + orientation as u64)
+}
+
+/// Inverse of `rank_orientation`.
+pub(crate) fn unrank_orientation<const M: u8>(mut rank: u64, orientations: &mut [u8]) {
+    for orientation in orientations.iter_mut().rev() {
+        *orientation = (rank % M as u64) as u8;
+        rank /= M as u64;
+    }
+}
+
+/// Distance of a coordinate not reached by [`distance_table`].
+pub(crate) const UNVISITED: u8 = u8::MAX;
+
+/// Distance of every coordinate below `size` from the nearest of `starts`, found by
+/// breadth-first search, `UNVISITED` for coordinates that are never reached.
+///
+/// `turn(coordinate, twist)` is the coordinate reached from `coordinate` by the turn
+/// numbered `twist`, below `num_twists`.
+pub(crate) fn distance_table(
+    size: usize,
+    starts: impl IntoIterator<Item = usize>,
+    num_twists: usize,
+    turn: impl Fn(usize, usize) -> usize,
+) -> Vec<u8> {
+    let mut distances = vec![UNVISITED; size];
+    let mut frontier: Vec<usize> = starts.into_iter().collect();
+    for &coordinate in &frontier {
+        distances[coordinate] = 0;
+    }
+    let mut distance = 0;
+    while !frontier.is_empty() {
+        distance += 1;
+        let mut next = Vec::new();
+        for coordinate in frontier {
+            for twist in 0..num_twists {
+                let neighbour = turn(coordinate, twist);
+                if distances[neighbour] == UNVISITED {
+                    distances[neighbour] = distance;
+                    next.push(neighbour);
+                }
+            }
+        }
+        frontier = next;
+    }
+    distances
+}
+
+/// Set of coordinates, one bit per coordinate.
+struct Bitmap(Vec<u64>);
+
+impl Bitmap {
+    fn new(size: u64) -> Bitmap {
+        Bitmap(vec![0; size.div_ceil(64) as usize])
+    }
+
+    /// Adds `index` to the set, returning false if it was already present.
+    fn insert(&mut self, index: u64) -> bool {
+        let (word, bit) = ((index / 64) as usize, 1 << (index % 64));
+        let absent = self.0[word] & bit == 0;
+        self.0[word] |= bit;
+        absent
+    }
+}
+
+/// Number of arrangements of each component, 1 for components not tracked, in the order
+/// corner permutation, corner orientation, edge permutation and edge orientation.
+fn component_sizes(components: Components) -> [u64; 4] {
+    let Components {
+        corner_permutation,
+        corner_orientation,
+        edge_permutation,
+        edge_orientation,
+    } = components;
+    let size = |tracked: bool, size: u64| if tracked { size } else { 1 };
+    [
+        size(corner_permutation, factorial(NUM_CORNERS)),
+        size(
+            corner_orientation,
+            (NUM_CORNER_ORIENTATION as u64).pow(NUM_CORNERS as u32),
+        ),
+        size(edge_permutation, factorial(NUM_EDGES)),
+        size(
+            edge_orientation,
+            (NUM_EDGE_ORIENTATION as u64).pow(NUM_EDGES as u32),
+        ),
+    ]
+}
+
+/// Breadth-first explorer of the states reachable from solved with a set of turns.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::bfs::{Components, Explorer};
+/// use rubikscube::Turn;
+///
+/// // the edge orientations reachable with all 18 turns
+/// let turns: Vec<Turn> = (0..18).map(|twist| Turn::from_u8(twist).unwrap()).collect();
+/// let components = Components {
+///     edge_orientation: true,
+///     ..Components::default()
+/// };
+/// let explorer = Explorer::new(&turns, components).unwrap();
+///
+/// assert_eq!(explorer.explore().iter().sum::<u64>(), 2048);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Explorer {
+    turns: Vec<Turn>,
+    /// number of arrangements of each component, see `component_sizes`
+    sizes: [u64; 4],
+    /// rank of each tracked component after each turn, indexed by `rank * turns.len() + turn`,
+    /// for components with at most `MAX_TABLE_SIZE` arrangements
+    tables: [Option<Vec<u32>>; 4],
+}
+
+/// Largest number of arrangements of a component for which turns are looked up in a table,
+/// larger components are turned through a [`Cube`].
+const MAX_TABLE_SIZE: u64 = 1 << 20;
+
+/// Rank of component `component` of `cube`, the components in the order of
+/// `component_sizes`.
+fn rank_component(component: usize, cube: &Cube) -> u64 {
+    let [eo, co, ep, cp] = cube.cubicles();
+    match component {
+        0 => rank_permutation(cp),
+        1 => rank_orientation::<NUM_CORNER_ORIENTATION>(co),
+        2 => rank_permutation(ep),
+        _ => rank_orientation::<NUM_EDGE_ORIENTATION>(eo),
+    }
+}
+
+/// Returns a cube with the components given by `ranks`, in the order of `component_sizes`.
+fn unrank_components(ranks: [u64; 4]) -> Cube {
+    let mut cp = [0; NUM_CORNERS as usize];
+    let mut co = [0; NUM_CORNERS as usize];
+    let mut ep = [0; NUM_EDGES as usize];
+    let mut eo = [0; NUM_EDGES as usize];
+    unrank_permutation(ranks[0], &mut cp);
+    unrank_orientation::<NUM_CORNER_ORIENTATION>(ranks[1], &mut co);
+    unrank_permutation(ranks[2], &mut ep);
+    unrank_orientation::<NUM_EDGE_ORIENTATION>(ranks[3], &mut eo);
+    Cube::from_cubicles(&eo, &co, &ep, &cp, MetricKind::HalfTurnMetric)
+}
+
+impl Explorer {
+    /// Creates an explorer applying `turns` and tracking `components`.
+    ///
+    /// Returns `CubeError::TooManyCoordinates` if the components have more than
+    /// `MAX_COORDINATES` arrangements, such as the permutation and orientation of the edges.
+    pub fn new(turns: &[Turn], components: Components) -> Result<Explorer, CubeError> {
+        let sizes = component_sizes(components);
+        let num_coordinates = sizes
+            .iter()
+            .try_fold(1u64, |product, &size| product.checked_mul(size));
+        match num_coordinates {
+            Some(num_coordinates) if num_coordinates <= MAX_COORDINATES => {}
+            num_coordinates => {
+                return Err(CubeError::TooManyCoordinates(
+                    MAX_COORDINATES,
+                    num_coordinates.unwrap_or(u64::MAX),
+                ))
+            }
+        }
+
+        let tables = std::array::from_fn(|component| {
+            let size = sizes[component];
+            if size == 1 || size > MAX_TABLE_SIZE {
+                return None;
+            }
+            let mut table = Vec::with_capacity(size as usize * turns.len());
+            for rank in 0..size {
+                let mut ranks = [0; 4];
+                ranks[component] = rank;
+                let cube = unrank_components(ranks);
+                for &turn in turns {
+                    let mut turned = cube;
+                    turned.turn(turn as u8).unwrap();
+                    table.push(rank_component(component, &turned) as u32);
+                }
+            }
+            Some(table)
+        });
+
+        Ok(Explorer {
+            turns: turns.to_vec(),
+            sizes,
+            tables,
+        })
+    }
+
+    /// Number of coordinates, including those of unreachable states.
+    pub fn num_coordinates(&self) -> u64 {
+        self.sizes.iter().product()
+    }
+
+    /// Returns the coordinate of the tracked components of `cube`.
+    pub fn rank(&self, cube: &Cube) -> u64 {
+        self.sizes
+            .iter()
+            .enumerate()
+            .filter(|&(_, &size)| size > 1)
+            .fold(0, |coordinate, (component, &size)| {
+                coordinate * size + rank_component(component, cube)
+            })
+    }
+
+    fn component_ranks(&self, mut coordinate: u64) -> [u64; 4] {
+        let mut ranks = [0; 4];
+        for (rank, &size) in ranks.iter_mut().zip(self.sizes.iter()).rev() {
+            *rank = coordinate % size;
+            coordinate /= size;
+        }
+        ranks
+    }
+
+    /// Returns a cube with the tracked components given by `coordinate` and the other
+    /// components solved, the inverse of `rank`.
+    pub fn unrank(&self, coordinate: u64) -> Cube {
+        unrank_components(self.component_ranks(coordinate))
+    }
+
+    /// Visits every state reachable from solved and returns the number of states at each
+    /// distance from solved, starting with the solved state at distance 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::bfs::{Components, Explorer};
+    /// use rubikscube::Turn;
+    ///
+    /// // the corner permutations of the <U, D> group
+    /// let components = Components {
+    ///     corner_permutation: true,
+    ///     ..Components::default()
+    /// };
+    /// let explorer = Explorer::new(&[Turn::U, Turn::D], components).unwrap();
+    ///
+    /// assert_eq!(explorer.explore(), vec![1, 2, 3, 4, 3, 2, 1]);
+    /// ```
+    pub fn explore(&self) -> Vec<u64> {
+        let mut visited = Bitmap::new(self.num_coordinates());
+        let solved = self.rank(&Cube::cube_htm());
+        visited.insert(solved);
+
+        let mut counts = Vec::new();
+        let mut frontier = vec![solved];
+        while !frontier.is_empty() {
+            counts.push(frontier.len() as u64);
+            let mut next = Vec::new();
+            for &coordinate in &frontier {
+                let ranks = self.component_ranks(coordinate);
+                // components without a table are turned through a cube
+                let cube = unrank_components(ranks);
+                for (turn_idx, &turn) in self.turns.iter().enumerate() {
+                    let mut turned = None;
+                    let mut neighbour = 0;
+                    for (component, &size) in self.sizes.iter().enumerate() {
+                        if size == 1 {
+                            continue;
+                        }
+                        let rank = match &self.tables[component] {
+                            Some(table) => {
+                                table[ranks[component] as usize * self.turns.len() + turn_idx]
+                                    as u64
+                            }
+                            None => {
+                                let turned = turned.get_or_insert_with(|| {
+                                    let mut turned = cube;
+                                    turned.turn(turn as u8).unwrap();
+                                    turned
+                                });
+                                rank_component(component, turned)
+                            }
+                        };
+                        neighbour = neighbour * size + rank;
+                    }
+                    if visited.insert(neighbour) {
+                        next.push(neighbour);
+                    }
+                }
+            }
+            frontier = next;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rank_permutation, unrank_permutation, Components, Explorer};
+    use crate::errors::CubeError;
+    use crate::{Cube, Turn};
+
+    fn turns(faces: &[Turn]) -> Vec<Turn> {
+        faces
+            .iter()
+            .flat_map(|&face| (0..3).map(move |kind| Turn::from_u8(face as u8 + 6 * kind).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn rank_round_trip_test() {
+        let mut permutation = [0; 8];
+        for rank in [0, 1, 719, 40319] {
+            unrank_permutation(rank, &mut permutation);
+            assert_eq!(rank_permutation(&permutation), rank);
+        }
+
+        let explorer = Explorer::new(&[], Components::CORNERS).unwrap();
+        let mut cube = Cube::cube_htm();
+        cube.scramble(20);
+        let coordinate = explorer.rank(&cube);
+        let projected = explorer.unrank(coordinate);
+        assert_eq!(explorer.rank(&projected), coordinate);
+        assert_eq!(projected.cubicles()[1], cube.cubicles()[1]);
+        assert_eq!(projected.cubicles()[3], cube.cubicles()[3]);
+        assert_eq!(explorer.rank(&Cube::cube_htm()), 0);
+    }
+
+    #[test]
+    fn pocket_cube_test() {
+        // R, U and F turns keep the back left bottom corner in place, like a 2x2 cube
+        let explorer =
+            Explorer::new(&turns(&[Turn::R, Turn::U, Turn::F]), Components::CORNERS).unwrap();
+        let counts = explorer.explore();
+
+        assert_eq!(counts.iter().sum::<u64>(), 3_674_160);
+        assert_eq!(
+            counts,
+            vec![1, 9, 54, 321, 1847, 9992, 50136, 227536, 870072, 1887748, 623800, 2644]
+        );
+    }
+
+    #[test]
+    fn orientation_test() {
+        let all = turns(&[Turn::L, Turn::R, Turn::F, Turn::B, Turn::U, Turn::D]);
+        let corner_orientation = Components {
+            corner_orientation: true,
+            ..Components::default()
+        };
+        let corner_permutation = Components {
+            corner_permutation: true,
+            ..Components::default()
+        };
+        let count = |components| {
+            Explorer::new(&all, components)
+                .unwrap()
+                .explore()
+                .iter()
+                .sum::<u64>()
+        };
+
+        assert_eq!(count(corner_orientation), 2187);
+        assert_eq!(count(corner_permutation), 40320);
+    }
+
+    #[test]
+    fn too_many_coordinates_test() {
+        assert_eq!(
+            Explorer::new(&[Turn::R], Components::EDGES),
+            Err(CubeError::TooManyCoordinates(1 << 32, 479001600 * 4096))
+        );
+    }
+}
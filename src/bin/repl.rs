@@ -0,0 +1,253 @@
+//! Interactive terminal REPL for manipulating a cube.
+//!
+//! Reads one command per line from stdin, so it can be scripted by piping commands in:
+//!
+//! ```text
+//! echo "R U R' U'
+//! cycles
+//! undo 4" | rubikscube-repl
+//! ```
+//!
+//! The cube is redrawn after every command changing it, with ANSI colors when stdout is a
+//! terminal and as plain letters otherwise. Type `help` for the list of commands.
+
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use clap::Parser;
+
+use rubikscube::{render, Cube, CubeError, CubeHistory, MetricKind, Turn};
+
+const HELP: &str = "\
+<moves>          apply moves in standard notation, such as R U R' U'
+undo [n]         undo the last n moves, 1 by default
+redo [n]         redo the last n undone moves, 1 by default
+set <facelets>   set the cube from 54 facelet colors and clear the history
+reset            set the cube to solved and clear the history
+show             redraw the cube
+facelets         print the 54 facelet colors
+solved           print whether the cube is solved
+solvable         print whether the cube can be solved
+cycles           print the cycles of misplaced or twisted edges and corners
+history          print the moves made, marking the current position
+help             print this message
+quit             exit";
+
+#[derive(Parser)]
+#[command(name = "rubikscube-repl", about = "Interactive Rubik's cube REPL")]
+struct Args {
+    /// Use the quarter turn metric, which disallows half turns
+    #[arg(long)]
+    qtm: bool,
+}
+
+/// Whether the REPL should keep reading commands.
+#[derive(Debug, PartialEq)]
+enum Flow {
+    Continue,
+    Quit,
+}
+
+struct Repl {
+    history: CubeHistory,
+    color: bool,
+}
+
+fn join(turns: &[Turn]) -> String {
+    turns
+        .iter()
+        .map(|turn| turn.to_string().trim().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Errors reported for a command, before reading the next one.
+#[derive(Debug, PartialEq)]
+enum ReplError {
+    Cube(CubeError),
+    /// the argument of `undo` or `redo` is not a number of moves
+    InvalidCount(String),
+}
+
+impl From<CubeError> for ReplError {
+    fn from(err: CubeError) -> ReplError {
+        ReplError::Cube(err)
+    }
+}
+
+impl fmt::Display for ReplError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplError::Cube(err) => write!(f, "{}", err),
+            ReplError::InvalidCount(argument) => write!(
+                f,
+                "Invalid count, Expected a number of moves got {:?}",
+                argument
+            ),
+        }
+    }
+}
+
+fn count(argument: Option<&str>) -> Result<usize, ReplError> {
+    match argument {
+        None => Ok(1),
+        Some(argument) => argument
+            .parse()
+            .map_err(|_| ReplError::InvalidCount(argument.to_string())),
+    }
+}
+
+impl Repl {
+    fn new(turn_metric: MetricKind, color: bool) -> Repl {
+        Repl {
+            history: CubeHistory::new(Cube::new(turn_metric)),
+            color,
+        }
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        if self.color {
+            write!(out, "{}", render::ansi(self.history.cube()))
+        } else {
+            writeln!(out, "{}", self.history.cube())
+        }
+    }
+
+    /// Applies every move or none of them, so a typo does not leave half a sequence applied.
+    fn apply(&mut self, moves: &str) -> Result<(), CubeError> {
+        let turns = Turn::parse_sequence(moves)?;
+        let mut cube = *self.history.cube();
+        for &turn in &turns {
+            cube.turn(turn as u8)?;
+        }
+        for turn in turns {
+            self.history.turn(turn as u8)?;
+        }
+        Ok(())
+    }
+
+    /// Runs a single command, writing its output to `out`.
+    fn handle(&mut self, line: &str, out: &mut impl Write) -> Result<Flow, ReplError> {
+        let line = line.trim();
+        let (command, argument) = match line.split_once(char::is_whitespace) {
+            Some((command, argument)) => (command, Some(argument.trim())),
+            None => (line, None),
+        };
+
+        let redraw = match command {
+            "" => false,
+            "quit" | "exit" => return Ok(Flow::Quit),
+            "help" => {
+                writeln!(out, "{}", HELP).unwrap();
+                false
+            }
+            "undo" => {
+                let position = self.history.history().len();
+                self.history
+                    .reset_to(position.saturating_sub(count(argument)?))?;
+                true
+            }
+            "redo" => {
+                for _ in 0..count(argument)? {
+                    self.history.redo();
+                }
+                true
+            }
+            "set" => {
+                let turn_metric = self.history.cube().turn_metric();
+                let cube = Cube::from_facelets(argument.unwrap_or(""), turn_metric)?;
+                self.history = CubeHistory::new(cube);
+                true
+            }
+            "reset" => {
+                let turn_metric = self.history.cube().turn_metric();
+                self.history = CubeHistory::new(Cube::new(turn_metric));
+                true
+            }
+            "show" => true,
+            "facelets" => {
+                writeln!(out, "{}", self.history.cube().facelets()).unwrap();
+                false
+            }
+            "solved" => {
+                writeln!(out, "{}", self.history.cube().solved()).unwrap();
+                false
+            }
+            "solvable" => {
+                let solvability = self.history.cube().solvability();
+                writeln!(out, "{}", solvability.is_solvable()).unwrap();
+                if !solvability.parity_matches {
+                    writeln!(out, "edge and corner permutation parities differ").unwrap();
+                }
+                if solvability.edge_flip != 0 {
+                    writeln!(out, "an odd number of edges is flipped").unwrap();
+                }
+                if solvability.corner_twist != 0 {
+                    writeln!(
+                        out,
+                        "corner orientations sum to {} modulo 3",
+                        solvability.corner_twist
+                    )
+                    .unwrap();
+                }
+                false
+            }
+            "cycles" => {
+                for (name, cycles) in [
+                    ("edges", self.history.cube().edge_cycles()),
+                    ("corners", self.history.cube().corner_cycles()),
+                ] {
+                    let cycles: Vec<String> = cycles.iter().map(ToString::to_string).collect();
+                    writeln!(out, "{:<8} {}", name, cycles.join(" ")).unwrap();
+                }
+                false
+            }
+            "history" => {
+                let done = join(self.history.history());
+                let undone = join(self.history.undone());
+                writeln!(out, "{} | {}", done, undone).unwrap();
+                false
+            }
+            _ => {
+                self.apply(line)?;
+                true
+            }
+        };
+
+        if redraw {
+            self.draw(out).unwrap();
+        }
+        Ok(Flow::Continue)
+    }
+}
+
+fn main() {
+    let args = Args::parse();
+    let turn_metric = if args.qtm {
+        MetricKind::QuarterTurnMetric
+    } else {
+        MetricKind::HalfTurnMetric
+    };
+
+    let interactive = io::stdin().is_terminal();
+    let mut out = io::stdout();
+    let mut repl = Repl::new(turn_metric, out.is_terminal());
+    repl.draw(&mut out).unwrap();
+
+    let mut lines = io::stdin().lock().lines();
+    loop {
+        if interactive {
+            write!(out, "> ").unwrap();
+            out.flush().unwrap();
+        }
+        let line = match lines.next() {
+            Some(line) => line.unwrap(),
+            None => break,
+        };
+        match repl.handle(&line, &mut out) {
+            Ok(Flow::Continue) => {}
+            Ok(Flow::Quit) => break,
+            Err(err) => writeln!(out, "error: {}", err).unwrap(),
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Splits a CFOP solve into its stages: cross, four F2L pairs, OLL and PLL.
+//!
+//! The turns of a solve are replayed one at a time and, after each of them, the cube is
+//! checked for the stages it has completed. The cross may be solved on any face and the last
+//! layer is the opposite face. The cross face is the first face on which a turn of the solve
+//! completes an F2L pair, or OLL, so a cross the scramble happens to leave on another face
+//! does not count. Without such a turn, it is the face of the last cross seen before the
+//! cube is solved. A stage is completed once
+//!
+//! * cross: the four edges around the cross face are in position and oriented,
+//! * F2L pair: one more slot between the cross and the last layer holds its corner and edge,
+//! * OLL: the first two layers are solved and the last layer face has a single color,
+//! * PLL: the cube is solved.
+//!
+//! Every turn up to a stage's completion counts for that stage. Stages completed by the same
+//! turn as the stage before them, or before the first turn, are skipped.
+
+use std::cmp::Reverse;
+use std::fmt;
+
+use crate::{
+    beginner::{corner_solved, cross_solved, edge_solved, FIRST_LAYER_CORNERS, MIDDLE_LAYER_EDGES},
+    cubies::Faces,
+    errors::CubeError,
+    moves::Turn,
+    symmetry::Symmetry,
+    Cube,
+};
+
+/// A stage of the CFOP method.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CfopStage {
+    Cross,
+    /// The `n`th F2L pair, from 1 to 4, in the order the slots are filled.
+    F2l(u8),
+    Oll,
+    Pll,
+}
+
+impl fmt::Display for CfopStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CfopStage::Cross => write!(f, "cross"),
+            CfopStage::F2l(pair) => write!(f, "F2L {}", pair),
+            CfopStage::Oll => write!(f, "OLL"),
+            CfopStage::Pll => write!(f, "PLL"),
+        }
+    }
+}
+
+/// The stages in the order they are completed.
+const STAGES: [CfopStage; 7] = [
+    CfopStage::Cross,
+    CfopStage::F2l(1),
+    CfopStage::F2l(2),
+    CfopStage::F2l(3),
+    CfopStage::F2l(4),
+    CfopStage::Oll,
+    CfopStage::Pll,
+];
+
+/// A completed stage and the turns spent on it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StageReport {
+    pub stage: CfopStage,
+    pub turns: Vec<Turn>,
+}
+
+impl StageReport {
+    /// Returns true if the stage was completed without any turn of its own.
+    pub fn is_skipped(&self) -> bool {
+        self.turns.is_empty()
+    }
+}
+
+/// The stages of a solve.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Analysis {
+    /// Face the cross was solved on, `None` if no cross was ever solved.
+    pub cross_face: Option<Faces>,
+    /// Completed stages, in order.
+    pub stages: Vec<StageReport>,
+    /// Turns after the last completed stage, empty if the solve solves the cube.
+    pub remaining: Vec<Turn>,
+}
+
+impl Analysis {
+    /// Returns true if the solve ends with a solved cube.
+    pub fn solved(&self) -> bool {
+        self.stages.len() == STAGES.len()
+    }
+}
+
+/// Returns a rotation bringing the center of `face` to the D face.
+fn rotation_to_bottom(face: Faces) -> &'static Symmetry {
+    Symmetry::all()
+        .iter()
+        .find(|symmetry| !symmetry.is_reflection() && symmetry.apply_color(face) == Faces::White)
+        .unwrap()
+}
+
+/// Number of stages completed by a cube with its cross face on D.
+fn progress(cube: &Cube) -> usize {
+    if cube.solved() {
+        return STAGES.len();
+    }
+    if !cross_solved(cube) {
+        return 0;
+    }
+    let pairs = FIRST_LAYER_CORNERS
+        .iter()
+        .zip(&MIDDLE_LAYER_EDGES)
+        .filter(|&(&corner, &edge)| corner_solved(cube, corner) && edge_solved(cube, edge))
+        .count();
+    let last_layer = cube.faces()[Faces::Yellow as usize];
+    if pairs < FIRST_LAYER_CORNERS.len() {
+        1 + pairs
+    } else if last_layer
+        .iter()
+        .flatten()
+        .all(|&color| color == Faces::Yellow)
+    {
+        STAGES.len() - 1
+    } else {
+        STAGES.len() - 2
+    }
+}
+
+/// Replays `turns` on `cube` and splits them into the CFOP stages they complete.
+///
+/// Returns `CubeError::InvalidTurn` if a turn is not in the cube's turn metric.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::cfop::{analyse, CfopStage};
+/// use rubikscube::{Cube, Faces, Turn};
+///
+/// let mut cube = Cube::cube_htm();
+/// for turn in Turn::parse_sequence("U R U' R'").unwrap() {
+///     cube.turn(turn as u8).unwrap();
+/// }
+///
+/// let analysis = analyse(&cube, &Turn::parse_sequence("R U R' U'").unwrap()).unwrap();
+/// assert_eq!(analysis.cross_face, Some(Faces::White));
+/// assert!(analysis.solved());
+///
+/// // the cross and three pairs are solved from the start, R U R' solves the last pair and
+/// // orients the last layer, and U' only has to align it
+/// let counts: Vec<_> = analysis.stages.iter().map(|s| (s.stage, s.turns.len())).collect();
+/// assert_eq!(counts[4..], [(CfopStage::F2l(4), 3), (CfopStage::Oll, 0), (CfopStage::Pll, 1)]);
+/// assert!(analysis.stages[0].is_skipped() && analysis.stages[5].is_skipped());
+/// ```
+pub fn analyse(cube: &Cube, turns: &[Turn]) -> Result<Analysis, CubeError> {
+    let mut current = *cube;
+    let mut states = Vec::with_capacity(turns.len() + 1);
+    states.push(current);
+    for &turn in turns {
+        current.turn(turn as u8)?;
+        states.push(current);
+    }
+    // the progress of every state with each face as the cross face
+    let face_progress: Vec<[usize; 6]> = states
+        .iter()
+        .map(|state| {
+            std::array::from_fn(|face| {
+                let face = Faces::from_repr(face).unwrap();
+                progress(&rotation_to_bottom(face).apply(state))
+            })
+        })
+        .collect();
+    let cross_face = find_cross_face(&face_progress);
+
+    let mut stages = Vec::new();
+    let mut pending = Vec::new();
+    if let Some(face) = cross_face {
+        for (index, row) in face_progress.iter().enumerate() {
+            if index > 0 {
+                pending.push(turns[index - 1]);
+            }
+            for &stage in STAGES.iter().take(row[face as usize]).skip(stages.len()) {
+                stages.push(StageReport {
+                    stage,
+                    turns: std::mem::take(&mut pending),
+                });
+            }
+        }
+    } else {
+        pending = turns.to_vec();
+    }
+    Ok(Analysis {
+        cross_face,
+        stages,
+        remaining: pending,
+    })
+}
+
+/// Returns the cross face of a solve given the progress of every state on each face, see
+/// the module documentation.
+fn find_cross_face(face_progress: &[[usize; 6]]) -> Option<Faces> {
+    let mut highest = face_progress[0];
+    for row in &face_progress[1..] {
+        // solving the cube completes every stage on every face at once
+        let face = (0..6)
+            .filter(|&face| row[face] > highest[face].max(1) && row[face] < STAGES.len())
+            .max_by_key(|&face| (row[face], Reverse(face)));
+        if let Some(face) = face {
+            return Faces::from_repr(face);
+        }
+        for (highest, &progress) in highest.iter_mut().zip(row) {
+            *highest = (*highest).max(progress);
+        }
+    }
+    let row = face_progress
+        .iter()
+        .rev()
+        .find(|row| {
+            row.iter()
+                .any(|&progress| progress > 0 && progress < STAGES.len())
+        })
+        .or_else(|| face_progress.first().filter(|row| row[0] == STAGES.len()))?;
+    let face = (0..6).max_by_key(|&face| (row[face], Reverse(face)))?;
+    Faces::from_repr(face)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{analyse, rotation_to_bottom, CfopStage, STAGES};
+    use crate::{beginner, beginner::cross_solved, errors::CubeError, Cube, Faces, Turn};
+
+    fn parse(turns: &str) -> Vec<Turn> {
+        Turn::parse_sequence(turns).unwrap()
+    }
+
+    #[test]
+    fn beginner_solve_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(30);
+        let solution: Vec<Turn> = beginner::solve(&cube)
+            .unwrap()
+            .into_iter()
+            .flat_map(|stage| stage.turns)
+            .collect();
+
+        let analysis = analyse(&cube, &solution).unwrap();
+        assert!(analysis.solved());
+        assert!(analysis.remaining.is_empty());
+        let stages: Vec<CfopStage> = analysis.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, STAGES);
+        let replayed: Vec<Turn> = analysis
+            .stages
+            .into_iter()
+            .flat_map(|stage| stage.turns)
+            .collect();
+        assert_eq!(replayed, solution);
+    }
+
+    #[test]
+    fn other_cross_face_test() {
+        let mut cube = Cube::cube_htm();
+        cube.turn(Turn::F as u8).unwrap();
+
+        // only the blue cross, opposite to the turned face, stays solved
+        let analysis = analyse(&cube, &parse("F'")).unwrap();
+        assert_eq!(analysis.cross_face, Some(Faces::Blue));
+        let turns: Vec<usize> = analysis.stages.iter().map(|s| s.turns.len()).collect();
+        assert_eq!(turns, [0, 0, 0, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn incidental_cross_test() {
+        // turned over, the yellow cross and a pair are solved but the white cross is not
+        let mut cube = Cube::cube_htm();
+        for turn in parse("R U R' U2 L' U' L F U F' U B' U2 B") {
+            cube.turn(turn as u8).unwrap();
+        }
+        let cube = rotation_to_bottom(Faces::Yellow).apply(&cube);
+        assert!(!cross_solved(&cube));
+
+        let stages = beginner::solve(&cube).unwrap();
+        let solution: Vec<Turn> = stages
+            .iter()
+            .flat_map(|stage| stage.turns.clone())
+            .collect();
+        let analysis = analyse(&cube, &solution).unwrap();
+        assert_eq!(analysis.cross_face, Some(Faces::White));
+        assert!(analysis.solved());
+        assert_eq!(analysis.stages[0].turns, stages[0].turns);
+        assert!(analysis.stages.iter().all(|stage| !stage.is_skipped()));
+    }
+
+    #[test]
+    fn unfinished_solve_test() {
+        let mut cube = Cube::cube_htm();
+        for turn in parse("D R2 U' F B' L") {
+            cube.turn(turn as u8).unwrap();
+        }
+        let analysis = analyse(&cube, &parse("U2 R")).unwrap();
+        assert!(!analysis.solved());
+        assert_eq!(analysis.cross_face, None);
+        assert!(analysis.stages.is_empty());
+        assert_eq!(analysis.remaining, parse("U2 R"));
+
+        let solved = analyse(&Cube::cube_htm(), &[]).unwrap();
+        assert!(solved.solved());
+        assert!(solved.stages.iter().all(|stage| stage.is_skipped()));
+
+        assert_eq!(
+            analyse(&Cube::cube_qtm(), &[Turn::U2]),
+            Err(CubeError::InvalidTurn(Turn::U2 as u8, 12))
+        );
+    }
+}
@@ -3,22 +3,84 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
 
 use crate::{
     cubies::*,
     errors::CubeError,
+    goal::Goal,
     moves::{MetricKind, Turn},
-    orientation::Orientation,
-    permutation::Permutation,
+    orientation::{CornerOrientation, EdgeOrientation, Orientation},
+    permutation::{CornerPermutation, EdgePermutation, Permutation},
+    symmetry::Symmetry,
+    turn_table::{CubieTurn, TURN_TABLE},
 };
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
+/// The conditions a cube has to meet to be solvable.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Solvability {
+    /// true if the edge and corner permutations have the same parity
+    pub parity_matches: bool,
+    /// sum of the edge orientations modulo 2, a solvable cube has no flipped edge left over
+    pub edge_flip: u8,
+    /// sum of the corner orientations modulo 3, a solvable cube has no twisted corner left over
+    pub corner_twist: u8,
+}
+
+impl Solvability {
+    /// Returns true if every condition is met.
+    pub fn is_solvable(&self) -> bool {
+        self.parity_matches && self.edge_flip == 0 && self.corner_twist == 0
+    }
+}
+
+/// A cycle of cubies moved out of place or twisted, see `Cube::edge_cycles`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Cycle {
+    /// cubicles of the cycle, the cubie in each cubicle belongs in the next one
+    pub cubicles: Vec<u8>,
+    /// sum of the orientations of the cubies in the cycle, modulo the number of orientations
+    pub twist: u8,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cubicles: Vec<String> = self.cubicles.iter().map(u8::to_string).collect();
+        write!(f, "({})", cubicles.join(" "))?;
+        if self.twist != 0 {
+            write!(f, "+{}", self.twist)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cycles of `permutation` that move or twist a cubie.
+fn cycles<const N: usize, const M: u8>(
+    permutation: &Permutation<N>,
+    orientation: &Orientation<N, M>,
+) -> Vec<Cycle> {
+    permutation
+        .cycles()
+        .into_iter()
+        .map(|cubicles| {
+            let twist = cubicles
+                .iter()
+                .map(|&cubicle| orientation.orientation_at_index(cubicle))
+                .sum::<u8>()
+                % M;
+            Cycle { cubicles, twist }
+        })
+        .filter(|cycle| cycle.cubicles.len() > 1 || cycle.twist != 0)
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Clone, Copy)]
 // Cube object simulation a 3x3x3 Rubik's Cube
 pub struct Cube {
-    edge_orientation: Orientation,
-    corner_orientation: Orientation,
-    edge_permutation: Permutation,
-    corner_permutation: Permutation,
+    edge_orientation: EdgeOrientation,
+    corner_orientation: CornerOrientation,
+    edge_permutation: EdgePermutation,
+    corner_permutation: CornerPermutation,
     turn_metric: MetricKind,
 }
 
@@ -78,36 +140,119 @@ impl Cube {
         let mut cube_faces = [[[Faces::White; 3]; 3]; 6];
 
         for (i, face) in cube_array.iter().enumerate() {
-            for (j, row) in face.into_iter().enumerate() {
+            for (j, row) in face.iter().enumerate() {
                 for (k, elem) in row.iter().enumerate() {
-                    let face: Faces = Faces::from_str(elem)?;
-                    if j == 1 && k == 1 && face as usize != i {
-                        return Err(CubeError::InvalidFaceOrder(face, i as usize));
-                    }
-                    cube_faces[i][j][k] = face;
+                    cube_faces[i][j][k] = Faces::from_str(elem)?;
                 }
             }
         }
 
-        Ok(Cube::cube_from_faces(&cube_faces, turn_metric))
+        Cube::cube_from_faces(&cube_faces, turn_metric)
+    }
+
+    /// Initializes a Cube object from a string of 54 facelet colors.
+    ///
+    /// The facelets are read face by face in the order W, Y, G, B, R, O, each face row by
+    /// row, in the same layout as `cube_from_array`. Colors are given by their initials and
+    /// whitespace is ignored, so the string returned by `facelets` can be read back.
+    ///
+    /// # Arguments
+    ///
+    /// * `facelets` - facelet colors of the cube
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, CubeError, MetricKind};
+    ///
+    /// let facelets = "WWWWWWWWW YYYYYYYYY GGGGGGGGG BBBBBBBBB RRRRRRRRR OOOOOOOOO";
+    /// let cube = Cube::from_facelets(facelets, MetricKind::HalfTurnMetric).unwrap();
+    /// assert!(cube.solved());
+    ///
+    /// assert_eq!(
+    ///     Cube::from_facelets("WWW", MetricKind::HalfTurnMetric),
+    ///     Err(CubeError::InvalidFaceletCount(3))
+    /// );
+    /// ```
+    pub fn from_facelets(facelets: &str, turn_metric: MetricKind) -> Result<Cube, CubeError> {
+        Cube::cube_from_faces(&faces_from_facelets(facelets)?, turn_metric)
+    }
+
+    /// Initializes a Cube object from a string of 54 facelet colors read with any face to the
+    /// front and any face up, as given by a scanner.
+    ///
+    /// The facelets are read as in `from_facelets`, but the centers may be in any order that a
+    /// whole cube rotation can give. The facelets are rotated to the Green front, Yellow up
+    /// frame, and the cube is returned with the rotation that was applied. Returns
+    /// `CubeError::InvalidFaceOrder` if no rotation brings every center to its face.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, MetricKind};
+    ///
+    /// // a solved cube held with the white face up and the green face to the front
+    /// let facelets = "YYYYYYYYY WWWWWWWWW GGGGGGGGG BBBBBBBBB OOOOOOOOO RRRRRRRRR";
+    /// let (cube, rotation) = Cube::from_facelets_normalised(facelets, MetricKind::HalfTurnMetric)
+    ///     .unwrap();
+    /// assert!(cube.solved());
+    /// assert!(!rotation.is_reflection());
+    /// ```
+    pub fn from_facelets_normalised(
+        facelets: &str,
+        turn_metric: MetricKind,
+    ) -> Result<(Cube, &'static Symmetry), CubeError> {
+        let cube_faces = faces_from_facelets(facelets)?;
+
+        // the center of every face has to move to the face of its color
+        let rotation = Symmetry::rotations().find(|rotation| {
+            cube_faces.iter().enumerate().all(|(face, facelets)| {
+                rotation.apply_color(Faces::from_repr(face).unwrap()) == facelets[1][1]
+            })
+        });
+        match rotation {
+            Some(rotation) => Ok((
+                Cube::cube_from_faces(&rotation.move_facelets(&cube_faces), turn_metric)?,
+                rotation,
+            )),
+            None => {
+                let (face, facelets) = cube_faces
+                    .iter()
+                    .enumerate()
+                    .find(|&(face, facelets)| facelets[1][1] as usize != face)
+                    .unwrap();
+                Err(CubeError::InvalidFaceOrder(facelets[1][1], face))
+            }
+        }
     }
 
     /// Initializes a Cube object with values from 6 x 3 x 3 array of Face instances.
     ///
-    /// Helper function for cube_from_array.
+    /// Helper function for cube_from_array. Returns an error if a center is not in the order
+    /// W, Y, G, B, R, O, if the facelets of a cubicle do not form a cubie or if a cubie appears
+    /// more than once.
     ///
     /// # Arguments
     ///
     /// * `cube_faces` - 6 x 3 x 3 array of Face instances
     ///
-    fn cube_from_faces(cube_faces: &[[[Faces; 3]; 3]; 6], turn_metric: MetricKind) -> Cube {
+    pub(crate) fn cube_from_faces(
+        cube_faces: &[[[Faces; 3]; 3]; 6],
+        turn_metric: MetricKind,
+    ) -> Result<Cube, CubeError> {
+        for (i, face) in cube_faces.iter().enumerate() {
+            if face[1][1] as usize != i {
+                return Err(CubeError::InvalidFaceOrder(face[1][1], i));
+            }
+        }
+
         let mut edge_permutation = Vec::new();
         let mut corner_permutation = Vec::new();
         let mut edge_orientation = Orientation::edge();
         let mut corner_orientation = Orientation::corner();
 
         // sets corner cubies orientation and values
-        for corner_idx in CORNER_FACELETS.iter() {
+        for (cubicle_idx, corner_idx) in CORNER_FACELETS.iter().enumerate() {
             let (primary_facelet_idx, secondary_facelet_idx, tertiary_facelet_idx) = corner_idx;
 
             let facelet_a = cube_faces[primary_facelet_idx.0 as usize]
@@ -117,22 +262,20 @@ impl Cube {
             let facelet_c = cube_faces[tertiary_facelet_idx.0 as usize]
                 [tertiary_facelet_idx.1 as usize][tertiary_facelet_idx.2 as usize];
 
-            let corner_cubie = Corner::new(facelet_a, facelet_b, facelet_c);
-
-            let corner_cubie_idx = corner_cubie.cubie_index();
+            let (corner_cubie_idx, orientation) = Corner::new(facelet_a, facelet_b, facelet_c)
+                .cubie_and_orientation()
+                .ok_or(CubeError::InvalidCorner(cubicle_idx as u8))?;
             corner_permutation.push(corner_cubie_idx);
 
-            let primary_facelet: Faces = CORNER_CUBIES[corner_cubie_idx as usize].facelet_a();
-
-            match corner_cubie.get_orientation(primary_facelet) {
-                1 => corner_orientation.add_two(corner_cubie_idx),
-                2 => corner_orientation.add_one(corner_cubie_idx),
+            match orientation {
+                1 => corner_orientation.add_one(cubicle_idx as u8),
+                2 => corner_orientation.add_two(cubicle_idx as u8),
                 _ => {}
             }
         }
 
         // sets edge cubies orientation and values
-        for edge_idx in EDGE_FACELETS.iter() {
+        for (cubicle_idx, edge_idx) in EDGE_FACELETS.iter().enumerate() {
             let (primary_facelet_idx, secondary_facelet_idx) = edge_idx;
 
             let facelet_a = cube_faces[primary_facelet_idx.0 as usize]
@@ -140,29 +283,79 @@ impl Cube {
             let facelet_b = cube_faces[secondary_facelet_idx.0 as usize]
                 [secondary_facelet_idx.1 as usize][secondary_facelet_idx.2 as usize];
 
-            let edge_cubie = Edge::new(facelet_a, facelet_b);
-
-            let edge_cubie_idx = edge_cubie.cubie_index();
+            let (edge_cubie_idx, orientation) = Edge::new(facelet_a, facelet_b)
+                .cubie_and_orientation()
+                .ok_or(CubeError::InvalidEdge(cubicle_idx as u8))?;
             edge_permutation.push(edge_cubie_idx);
 
-            let primary_facelet: Faces = EDGE_CUBIES[edge_cubie_idx as usize].facelet_a();
-
-            match edge_cubie.get_orientation(primary_facelet) {
-                1 => edge_orientation.add_one(edge_cubie_idx),
-                _ => {}
+            if orientation == 1 {
+                edge_orientation.add_one(cubicle_idx as u8);
             }
         }
 
-        let edge_permutation = Permutation::new_with_permutation(&edge_permutation);
-        let corner_permutation = Permutation::new_with_permutation(&corner_permutation);
+        let mut edge_permutation_set = Permutation::edge();
+        let mut corner_permutation_set = Permutation::corner();
+        edge_permutation_set.set_permutation(edge_permutation)?;
+        corner_permutation_set.set_permutation(corner_permutation)?;
 
-        Cube {
+        Ok(Cube {
             edge_orientation,
             corner_orientation,
-            edge_permutation,
-            corner_permutation,
+            edge_permutation: edge_permutation_set,
+            corner_permutation: corner_permutation_set,
             turn_metric,
+        })
+    }
+
+    /// Returns the 6 x 3 x 3 array of facelet colors, in the layout read by `cube_from_array`.
+    pub fn faces(&self) -> [[[Faces; 3]; 3]; 6] {
+        let mut cube_faces = [[[Faces::White; 3]; 3]; 6];
+        for (face_idx, face) in cube_faces.iter_mut().enumerate() {
+            face[1][1] = Faces::from_repr(face_idx).unwrap();
+        }
+
+        for (cubicle_idx, (a, b, c)) in CORNER_FACELETS.iter().enumerate() {
+            let cubicle_idx = cubicle_idx as u8;
+            let cubie_idx = self.corner_permutation.cubie_in_cubicle(cubicle_idx);
+            let corner = CORNER_CUBIES[cubie_idx as usize]
+                .orient_corner(self.corner_orientation.orientation_at_index(cubicle_idx));
+            cube_faces[a.0 as usize][a.1 as usize][a.2 as usize] = corner.facelet_a();
+            cube_faces[b.0 as usize][b.1 as usize][b.2 as usize] = corner.facelet_b();
+            cube_faces[c.0 as usize][c.1 as usize][c.2 as usize] = corner.facelet_c();
+        }
+
+        for (cubicle_idx, (a, b)) in EDGE_FACELETS.iter().enumerate() {
+            let cubicle_idx = cubicle_idx as u8;
+            let cubie_idx = self.edge_permutation.cubie_in_cubicle(cubicle_idx);
+            let edge = EDGE_CUBIES[cubie_idx as usize]
+                .orient_edge(self.edge_orientation.orientation_at_index(cubicle_idx));
+            cube_faces[a.0 as usize][a.1 as usize][a.2 as usize] = edge.facelet_a();
+            cube_faces[b.0 as usize][b.1 as usize][b.2 as usize] = edge.facelet_b();
         }
+
+        cube_faces
+    }
+
+    /// Returns the 54 facelet colors as a string, in the format read by `from_facelets`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, MetricKind, Turn};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.turn(Turn::R as u8).unwrap();
+    ///
+    /// let facelets = cube.facelets();
+    /// assert_eq!(Cube::from_facelets(&facelets, MetricKind::HalfTurnMetric), Ok(cube));
+    /// ```
+    pub fn facelets(&self) -> String {
+        self.faces()
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|face| face.to_string())
+            .collect()
     }
 
     /// creates a scrambled rubiks cube
@@ -182,14 +375,39 @@ impl Cube {
     /// cube.scramble(num_scramble_turns);
     /// ```
     pub fn scramble(&mut self, num_turns: u32) {
+        self.scramble_with_rng(num_turns, &mut rand::thread_rng());
+    }
+
+    /// scrambles the cube with turns sampled from `rng` and returns the turns performed
+    ///
+    /// Seeding `rng` makes the scramble reproducible.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rand::{rngs::StdRng, SeedableRng};
+    /// use rubikscube::Cube;
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// let turns = cube.scramble_with_rng(20, &mut StdRng::seed_from_u64(7));
+    ///
+    /// let mut other = Cube::cube_htm();
+    /// for turn in turns {
+    ///     other.turn(turn as u8).unwrap();
+    /// }
+    /// assert_eq!(cube, other);
+    /// ```
+    pub fn scramble_with_rng<R: Rng + ?Sized>(&mut self, num_turns: u32, rng: &mut R) -> Vec<Turn> {
         let between = Uniform::from(0..self.turn_metric as u8);
-        let mut rng = rand::thread_rng();
 
-        for _ in 0..num_turns {
-            let sampled_index: u8 = between.sample(&mut rng);
-            let sampled_turn: Turn = Turn::from_u8(sampled_index).unwrap();
-            self._turn(sampled_turn);
-        }
+        (0..num_turns)
+            .map(|_| {
+                let sampled_index: u8 = between.sample(rng);
+                let sampled_turn: Turn = Turn::from_u8(sampled_index).unwrap();
+                self._turn(sampled_turn);
+                sampled_turn
+            })
+            .collect()
     }
     /// Performs the specified turn on the cube object.
     ///
@@ -205,6 +423,7 @@ impl Cube {
     /// let mut cube = Cube::cube_htm();
     /// cube.turn(0); // Turn L
     /// ```
+    #[inline]
     pub fn turn(&mut self, twist: u8) -> Result<(), CubeError> {
         if twist >= self.turn_metric as u8 {
             Err(CubeError::InvalidTurn(twist, self.turn_metric as u8))
@@ -217,74 +436,24 @@ impl Cube {
 
     /// Performs the specified turn on the cube object.
     ///
+    /// The turn is applied as a single composition with its precomputed cubie level
+    /// transformation from the turn table.
+    ///
     /// # Arguments
     ///
     /// * `m` - instance of Turn enum
     ///
+    #[inline]
     fn _turn(&mut self, m: Turn) {
-        // unpack cubicle indices
-        let ((a, b, c, d), (w, x, y, z)) = match m {
-            Turn::L | Turn::L_ | Turn::L2 => (L_EDGE_CUBICLES, L_CORNER_CUBICLES),
-            Turn::R | Turn::R_ | Turn::R2 => (R_EDGE_CUBICLES, R_CORNER_CUBICLES),
-            Turn::F | Turn::F_ | Turn::F2 => (F_EDGE_CUBICLES, F_CORNER_CUBICLES),
-            Turn::B | Turn::B_ | Turn::B2 => (B_EDGE_CUBICLES, B_CORNER_CUBICLES),
-            Turn::U | Turn::U_ | Turn::U2 => (U_EDGE_CUBICLES, U_CORNER_CUBICLES),
-            Turn::D | Turn::D_ | Turn::D2 => (D_EDGE_CUBICLES, D_CORNER_CUBICLES),
-        };
+        let CubieTurn { edges, corners } = &TURN_TABLE[m as usize];
 
-        // updating edge and corner cubie orientation based on move
-        match m {
-            Turn::L | Turn::R | Turn::L_ | Turn::R_ => {
-                let cubie_a = self.edge_permutation.cubie_in_cubicle(a);
-                let cubie_b = self.edge_permutation.cubie_in_cubicle(b);
-                let cubie_c = self.edge_permutation.cubie_in_cubicle(c);
-                let cubie_d = self.edge_permutation.cubie_in_cubicle(d);
-                let cubie_w = self.corner_permutation.cubie_in_cubicle(w);
-                let cubie_x = self.corner_permutation.cubie_in_cubicle(x);
-                let cubie_y = self.corner_permutation.cubie_in_cubicle(y);
-                let cubie_z = self.corner_permutation.cubie_in_cubicle(z);
-
-                self.corner_orientation.add_one(cubie_w);
-                self.corner_orientation.add_two(cubie_x);
-                self.corner_orientation.add_one(cubie_y);
-                self.corner_orientation.add_two(cubie_z);
-
-                self.edge_orientation.add_one(cubie_a);
-                self.edge_orientation.add_one(cubie_b);
-                self.edge_orientation.add_one(cubie_c);
-                self.edge_orientation.add_one(cubie_d);
-            }
-            Turn::F | Turn::B | Turn::F_ | Turn::B_ => {
-                let cubie_w = self.corner_permutation.cubie_in_cubicle(w);
-                let cubie_x = self.corner_permutation.cubie_in_cubicle(x);
-                let cubie_y = self.corner_permutation.cubie_in_cubicle(y);
-                let cubie_z = self.corner_permutation.cubie_in_cubicle(z);
-
-                self.corner_orientation.add_one(cubie_w);
-                self.corner_orientation.add_two(cubie_x);
-                self.corner_orientation.add_one(cubie_y);
-                self.corner_orientation.add_two(cubie_z);
-            }
-            _ => {}
-        }
-
-        // updating edge and corner cubie permutation based on move
-        match m {
-            Turn::L | Turn::R | Turn::F | Turn::B | Turn::U | Turn::D => {
-                self.edge_permutation.swap_four_cubies(a, b, c, d);
-                self.corner_permutation.swap_four_cubies(w, x, y, z);
-            }
-            Turn::L_ | Turn::R_ | Turn::F_ | Turn::B_ | Turn::U_ | Turn::D_ => {
-                self.edge_permutation.swap_four_cubies(d, c, b, a);
-                self.corner_permutation.swap_four_cubies(z, y, x, w);
-            }
-            Turn::L2 | Turn::R2 | Turn::F2 | Turn::B2 | Turn::U2 | Turn::D2 => {
-                self.edge_permutation.swap_two_cubies(a, c);
-                self.edge_permutation.swap_two_cubies(b, d);
-                self.corner_permutation.swap_two_cubies(w, y);
-                self.corner_permutation.swap_two_cubies(x, z);
-            }
-        }
+        self.edge_orientation
+            .cycle(&edges.targets, &edges.sources, &edges.twists);
+        self.corner_orientation
+            .cycle(&corners.targets, &corners.sources, &corners.twists);
+        self.edge_permutation.cycle(&edges.targets, &edges.sources);
+        self.corner_permutation
+            .cycle(&corners.targets, &corners.sources);
     }
 
     /// Checks if current configuration of cube is solvable.
@@ -310,9 +479,33 @@ impl Cube {
     /// assert!(cube.is_solvable());
     /// ```
     pub fn is_solvable(&self) -> bool {
-        (self.edge_permutation.parity() == self.corner_permutation.parity())
-            && (self.edge_orientation.sum() % 2 == 0)
-            && (self.corner_orientation.sum() % 3 == 0)
+        self.solvability().is_solvable()
+    }
+
+    /// Returns each of the conditions checked by `is_solvable`, to explain why a cube can
+    /// not be solved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, MetricKind};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// let [eo, co, ep, cp] = cube.get_state();
+    /// cube.set_state(eo, vec![1, 0, 0, 0, 0, 0, 0, 0], ep, cp).unwrap();
+    ///
+    /// let solvability = cube.solvability();
+    /// assert!(solvability.parity_matches);
+    /// assert_eq!(solvability.edge_flip, 0);
+    /// assert_eq!(solvability.corner_twist, 1);
+    /// assert!(!solvability.is_solvable());
+    /// ```
+    pub fn solvability(&self) -> Solvability {
+        Solvability {
+            parity_matches: self.edge_permutation.parity() == self.corner_permutation.parity(),
+            edge_flip: self.edge_orientation.sum() % NUM_EDGE_ORIENTATION,
+            corner_twist: self.corner_orientation.sum() % NUM_CORNER_ORIENTATION,
+        }
     }
 
     /// calculates the representation of the cube as a one hot array of size 480.
@@ -323,31 +516,97 @@ impl Cube {
     ///
     /// The 20 cubies each with 24 possible states gives us the 480 length one-hot array.
     ///
-    pub fn representation(&self) -> [bool; 480] {
-        let mut repr = [false; 480];
+    pub fn representation(&self) -> [bool; REPRESENTATION_SIZE] {
+        let mut repr = [false; REPRESENTATION_SIZE];
+        self.write_representation(&mut repr).unwrap();
+        repr
+    }
 
+    /// Writes the one-hot representation, as returned by `representation`, into `buffer`.
+    ///
+    /// Returns `CubeError::InvalidBufferLength` if `buffer` does not hold exactly 480
+    /// values. Works with any element type convertible from `bool`, such as `f32` and `u8`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, REPRESENTATION_SIZE};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.scramble(20);
+    ///
+    /// let mut buffer = vec![0u8; REPRESENTATION_SIZE];
+    /// cube.write_representation(&mut buffer).unwrap();
+    ///
+    /// let expected: Vec<u8> = cube.representation().iter().map(|&bit| bit as u8).collect();
+    /// assert_eq!(buffer, expected);
+    /// ```
+    pub fn write_representation<T: Copy + From<bool>>(
+        &self,
+        buffer: &mut [T],
+    ) -> Result<(), CubeError> {
+        if buffer.len() != REPRESENTATION_SIZE {
+            return Err(CubeError::InvalidBufferLength(
+                REPRESENTATION_SIZE,
+                buffer.len(),
+            ));
+        }
+
+        buffer.fill(T::from(false));
         // corner cubicles representation
         for corner_idx in 0..NUM_CORNERS {
             let cubie_idx = self.corner_permutation.cubie_in_cubicle(corner_idx);
-            let cubie_orientation =
-                self.corner_orientation.orientation_at_index(cubie_idx) as usize;
-            let index = (NUM_STATES * cubie_idx) as usize
-                + (NUM_CORNER_ORIENTATION * corner_idx) as usize
-                + cubie_orientation;
-            repr[index] = true;
+            let cubie_orientation = self.corner_orientation.orientation_at_index(corner_idx);
+            buffer[corner_state_index(corner_idx, cubie_idx, cubie_orientation)] = T::from(true);
         }
 
         // edge cubicles representation
         for edge_idx in 0..NUM_EDGES {
             let cubie_idx = self.edge_permutation.cubie_in_cubicle(edge_idx);
-            let cubie_orientation = self.edge_orientation.orientation_at_index(cubie_idx) as usize;
-            let index = (NUM_STATES as usize * (cubie_idx + NUM_CORNERS) as usize
-                + (NUM_EDGE_ORIENTATION * edge_idx) as usize
-                + cubie_orientation) as usize;
-            repr[index] = true;
+            let cubie_orientation = self.edge_orientation.orientation_at_index(edge_idx);
+            buffer[edge_state_index(edge_idx, cubie_idx, cubie_orientation)] = T::from(true);
         }
 
-        repr
+        Ok(())
+    }
+
+    /// Recovers a cube from its `representation`.
+    ///
+    /// Every block of 24 values of a cubie must have exactly one bit set, otherwise
+    /// `CubeError::InvalidOneHot` is returned with the cubie, corners numbered 0 to 7 and
+    /// edges 8 to 19. Two corners or two edges in the same cubicle give
+    /// `CubeError::DuplicateCubicle`. Solvability is not checked, use `is_solvable` for that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, CubeError, MetricKind};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.scramble(20);
+    ///
+    /// let mut repr = cube.representation();
+    /// assert_eq!(Cube::from_representation(&repr, MetricKind::HalfTurnMetric), Ok(cube));
+    ///
+    /// repr[..24].fill(false);
+    /// assert_eq!(
+    ///     Cube::from_representation(&repr, MetricKind::HalfTurnMetric),
+    ///     Err(CubeError::InvalidOneHot(0, 0))
+    /// );
+    /// ```
+    pub fn from_representation(
+        representation: &[bool; REPRESENTATION_SIZE],
+        turn_metric: MetricKind,
+    ) -> Result<Cube, CubeError> {
+        let mut indices = [0; (NUM_CORNERS + NUM_EDGES) as usize];
+        for (cubie, block) in representation.chunks_exact(NUM_STATES as usize).enumerate() {
+            let set: Vec<usize> = (0..block.len()).filter(|&idx| block[idx]).collect();
+            if set.len() != 1 {
+                return Err(CubeError::InvalidOneHot(cubie, set.len()));
+            }
+            indices[cubie] = set[0] as u8;
+        }
+        Cube::from_state_indices(&indices, turn_metric)
     }
 
     /// returns the turn metric of the cube
@@ -405,9 +664,83 @@ impl Cube {
         solved
     }
 
+    /// Returns the rotation solving the cube if it is solved up to a whole cube rotation,
+    /// `None` otherwise.
+    ///
+    /// The cube is solved in some orientation if turning it in hand, with the centers left in
+    /// place as by `Symmetry::rotate`, solves it. The identity is returned for a solved cube.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::symmetry::Symmetry;
+    /// use rubikscube::{Cube, Turn};
+    ///
+    /// let rotated = Symmetry::rotations().nth(5).unwrap().rotate(&Cube::cube_htm());
+    /// assert!(!rotated.solved());
+    ///
+    /// let rotation = rotated.solved_in_any_orientation().unwrap();
+    /// assert!(rotation.rotate(&rotated).solved());
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.turn(Turn::R as u8).unwrap();
+    /// assert_eq!(cube.solved_in_any_orientation(), None);
+    /// ```
+    pub fn solved_in_any_orientation(&self) -> Option<&'static Symmetry> {
+        Symmetry::rotations().find(|rotation| rotation.rotate(self).solved())
+    }
+
+    /// Returns true if every cubie selected by `goal` is solved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, Goal, Turn};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.turn(Turn::R2 as u8).unwrap();
+    ///
+    /// assert!(cube.matches(&Goal::edges_oriented().union(Goal::corners_oriented())));
+    /// assert!(!cube.matches(&Goal::cross()));
+    /// ```
+    pub fn matches(&self, goal: &Goal) -> bool {
+        let [eo, co, ep, cp] = self.cubicles();
+        goal.unsolved(eo, co, ep, cp) == (0, 0)
+    }
+
+    /// Returns the cycles of edge cubies that are out of place or twisted.
+    ///
+    /// Cubicles are numbered as in the permutation returned by `get_state`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, Turn};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.turn(Turn::U2 as u8).unwrap();
+    ///
+    /// let cycles: Vec<String> = cube.edge_cycles().iter().map(ToString::to_string).collect();
+    /// assert_eq!(cycles, vec!["(0 2)", "(1 3)"]);
+    /// assert_eq!(cube.corner_cycles().len(), 2);
+    /// ```
+    pub fn edge_cycles(&self) -> Vec<Cycle> {
+        cycles(&self.edge_permutation, &self.edge_orientation)
+    }
+
+    /// Returns the cycles of corner cubies that are out of place or twisted.
+    ///
+    /// Cubicles are numbered as in the permutation returned by `get_state`.
+    pub fn corner_cycles(&self) -> Vec<Cycle> {
+        cycles(&self.corner_permutation, &self.corner_orientation)
+    }
+
     pub fn get_state(&self) -> [Vec<u8>; 4] {
-        let edge_orientation_state: Vec<u8> = self.edge_orientation.orientations();
-        let corner_orientation_state: Vec<u8> = self.corner_orientation.orientations();
+        let edge_orientation_state: Vec<u8> =
+            self.edge_orientation.orientations(&self.edge_permutation);
+        let corner_orientation_state: Vec<u8> = self
+            .corner_orientation
+            .orientations(&self.corner_permutation);
         let edge_permutation_state = self.edge_permutation.permutation();
         let corner_permuation_state = self.corner_permutation.permutation();
 
@@ -419,6 +752,91 @@ impl Cube {
         ]
     }
 
+    /// Creates a cube from the cubie in every cubicle and the orientation of every cubicle,
+    /// without validating them.
+    pub(crate) fn from_cubicles(
+        edge_orientation: &[u8],
+        corner_orientation: &[u8],
+        edge_permutation: &[u8],
+        corner_permutation: &[u8],
+        turn_metric: MetricKind,
+    ) -> Cube {
+        Cube {
+            edge_orientation: Orientation::new_with_orientation(edge_orientation),
+            corner_orientation: Orientation::new_with_orientation(corner_orientation),
+            edge_permutation: Permutation::new_with_permutation(edge_permutation),
+            corner_permutation: Permutation::new_with_permutation(corner_permutation),
+            turn_metric,
+        }
+    }
+
+    /// Creates a cube from the index of the state of every cubie within its block of
+    /// `representation`, corners first, validating that no two cubies share a cubicle.
+    pub(crate) fn from_state_indices(
+        indices: &[u8; (NUM_CORNERS + NUM_EDGES) as usize],
+        turn_metric: MetricKind,
+    ) -> Result<Cube, CubeError> {
+        let (corners, edges) = indices.split_at(NUM_CORNERS as usize);
+        let mut corner_orientation = [0; NUM_CORNERS as usize];
+        let mut corner_permutation = [None; NUM_CORNERS as usize];
+        for (cubie, &index) in corners.iter().enumerate() {
+            let cubicle = (index / NUM_CORNER_ORIENTATION) as usize;
+            if corner_permutation[cubicle].replace(cubie as u8).is_some() {
+                return Err(CubeError::DuplicateCubicle(cubicle as u8));
+            }
+            corner_orientation[cubicle] = index % NUM_CORNER_ORIENTATION;
+        }
+
+        let mut edge_orientation = [0; NUM_EDGES as usize];
+        let mut edge_permutation = [None; NUM_EDGES as usize];
+        for (cubie, &index) in edges.iter().enumerate() {
+            let cubicle = (index / NUM_EDGE_ORIENTATION) as usize;
+            if edge_permutation[cubicle].replace(cubie as u8).is_some() {
+                return Err(CubeError::DuplicateCubicle(cubicle as u8));
+            }
+            edge_orientation[cubicle] = index % NUM_EDGE_ORIENTATION;
+        }
+
+        // every cubicle is filled once no two cubies share one
+        Ok(Cube::from_cubicles(
+            &edge_orientation,
+            &corner_orientation,
+            &edge_permutation.map(Option::unwrap),
+            &corner_permutation.map(Option::unwrap),
+            turn_metric,
+        ))
+    }
+
+    /// Returns the orientations and permutations indexed by cubicle, in the same order as
+    /// `get_state`.
+    pub(crate) fn cubicles(&self) -> [&[u8]; 4] {
+        [
+            self.edge_orientation.as_slice(),
+            self.corner_orientation.as_slice(),
+            self.edge_permutation.as_slice(),
+            self.corner_permutation.as_slice(),
+        ]
+    }
+
+    /// Sets the state of the cube, in the same order as returned by `get_state`.
+    ///
+    /// Every component is validated before the cube is modified: permutations must contain
+    /// each cubie index exactly once, and orientations must be below 2 for edges and 3 for
+    /// corners. On error the cube is left unchanged. Solvability is not checked, use
+    /// `is_solvable` for that.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, CubeError};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// let [eo, co, ep, mut cp] = cube.get_state();
+    /// cp[0] = 1;
+    ///
+    /// assert_eq!(cube.set_state(eo, co, ep, cp), Err(CubeError::DuplicateCubie(1)));
+    /// assert!(cube.solved());
+    /// ```
     pub fn set_state(
         &mut self,
         edge_orienatation_state: Vec<u8>,
@@ -426,26 +844,69 @@ impl Cube {
         edge_permutation_state: Vec<u8>,
         corner_permuation_state: Vec<u8>,
     ) -> Result<(), CubeError> {
-        self.edge_orientation
-            .set_orientations(edge_orienatation_state)?;
-        self.corner_orientation
-            .set_orientations(corner_orientation_state)?;
+        let mut edge_orientation = Orientation::edge();
+        let mut corner_orientation = Orientation::corner();
+        let mut edge_permutation = Permutation::edge();
+        let mut corner_permutation = Permutation::corner();
+
+        edge_permutation.set_permutation(edge_permutation_state)?;
+        corner_permutation.set_permutation(corner_permuation_state)?;
+        edge_orientation.set_orientations(edge_orienatation_state, &edge_permutation)?;
+        corner_orientation.set_orientations(corner_orientation_state, &corner_permutation)?;
+
+        self.edge_orientation = edge_orientation;
+        self.corner_orientation = corner_orientation;
+        self.edge_permutation = edge_permutation;
+        self.corner_permutation = corner_permutation;
+
+        Ok(())
+    }
+
+    /// Sets the state of the cube without validating it.
+    ///
+    /// Intended for performance sensitive callers whose states are known to be valid, such as
+    /// states previously returned by `get_state`. Passing an invalid state may cause later
+    /// turns to panic.
+    pub fn set_state_unchecked(
+        &mut self,
+        edge_orienatation_state: Vec<u8>,
+        corner_orientation_state: Vec<u8>,
+        edge_permutation_state: Vec<u8>,
+        corner_permuation_state: Vec<u8>,
+    ) {
         self.edge_permutation
-            .set_permutation(edge_permutation_state)?;
+            .set_permutation_unchecked(edge_permutation_state);
         self.corner_permutation
-            .set_permutation(corner_permuation_state)?;
+            .set_permutation_unchecked(corner_permuation_state);
+        self.edge_orientation
+            .set_orientations_unchecked(edge_orienatation_state, &self.edge_permutation);
+        self.corner_orientation
+            .set_orientations_unchecked(corner_orientation_state, &self.corner_permutation);
+    }
+}
 
-        Ok(())
+/// Reads the facelet colors of a string in the format of `Cube::from_facelets`, without
+/// checking that they form a cube.
+fn faces_from_facelets(facelets: &str) -> Result<[[[Faces; 3]; 3]; 6], CubeError> {
+    let colors: Vec<char> = facelets.chars().filter(|c| !c.is_whitespace()).collect();
+    if colors.len() != NUM_FACELETS {
+        return Err(CubeError::InvalidFaceletCount(colors.len()));
     }
+
+    let mut cube_faces = [[[Faces::White; 3]; 3]; 6];
+    for (idx, color) in colors.iter().enumerate() {
+        let (i, j, k) = (idx / 9, idx / 3 % 3, idx % 3);
+        cube_faces[i][j][k] = Faces::from_str(color.encode_utf8(&mut [0; 4]))?;
+    }
+    Ok(cube_faces)
 }
 
 impl Display for Cube {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let corner_cubies: Vec<Corner> = (0..NUM_CORNERS)
-            .into_iter()
             .map(|idx| {
                 let cubie_idx = self.corner_permutation.cubie_in_cubicle(idx);
-                (cubie_idx, CORNER_CUBIES[cubie_idx as usize].clone())
+                (idx, CORNER_CUBIES[cubie_idx as usize])
             })
             .map(|(idx, corner)| -> Corner {
                 corner.orient_corner(self.corner_orientation.orientation_at_index(idx))
@@ -453,10 +914,9 @@ impl Display for Cube {
             .collect();
 
         let edge_cubies: Vec<Edge> = (0..NUM_EDGES)
-            .into_iter()
             .map(|idx| {
                 let cubie_idx = self.edge_permutation.cubie_in_cubicle(idx);
-                (cubie_idx, EDGE_CUBIES[cubie_idx as usize].clone())
+                (idx, EDGE_CUBIES[cubie_idx as usize])
             })
             .map(|(idx, edge)| -> Edge {
                 edge.orient_edge(self.edge_orientation.orientation_at_index(idx))
@@ -544,7 +1004,8 @@ mod tests {
 
     use crate::cubies::Faces;
     use crate::errors::CubeError;
-    use crate::{Cube, MetricKind, Turn};
+    use crate::symmetry::Symmetry;
+    use crate::{Cube, MetricKind, Turn, REPRESENTATION_SIZE};
 
     #[test]
     fn cube_sanity_test() {
@@ -859,10 +1320,214 @@ mod tests {
         }
     }
 
+    #[test]
+    fn set_state_round_trip_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(100);
+
+        let [eo, co, ep, cp] = cube.get_state();
+        let mut other_cube = Cube::cube_htm();
+        other_cube.set_state(eo, co, ep, cp).unwrap();
+
+        assert_eq!(cube, other_cube);
+    }
+
+    #[test]
+    fn set_state_err_test() {
+        let mut cube = Cube::cube_htm();
+        let [eo, co, ep, cp] = cube.get_state();
+
+        let mut bad_ep = ep.clone();
+        bad_ep[3] = 0;
+        assert_eq!(
+            cube.set_state(eo.clone(), co.clone(), bad_ep, cp.clone()),
+            Err(CubeError::DuplicateCubie(0))
+        );
+
+        let mut bad_cp = cp.clone();
+        bad_cp[7] = 8;
+        assert_eq!(
+            cube.set_state(eo.clone(), co.clone(), ep.clone(), bad_cp),
+            Err(CubeError::CubieOutOfRange(8, 8))
+        );
+
+        let mut bad_co = co.clone();
+        bad_co[2] = 3;
+        assert_eq!(
+            cube.set_state(eo.clone(), bad_co, ep.clone(), cp.clone()),
+            Err(CubeError::InvalidOrientation(3, 3))
+        );
+
+        let mut bad_eo = eo.clone();
+        bad_eo[2] = 2;
+        assert_eq!(
+            cube.set_state(bad_eo, co.clone(), ep.clone(), cp.clone()),
+            Err(CubeError::InvalidOrientation(2, 2))
+        );
+
+        assert_eq!(
+            cube.set_state(eo, co, ep[..11].to_vec(), cp),
+            Err(CubeError::InvalidStateLength(12, 11))
+        );
+
+        assert!(cube.solved());
+    }
+
+    #[test]
+    fn from_representation_round_trip_test() {
+        for scramble_length in 0..200 {
+            let mut cube = Cube::cube_qtm();
+            cube.scramble(scramble_length);
+            let repr = cube.representation();
+            let decoded = Cube::from_representation(&repr, MetricKind::QuarterTurnMetric);
+
+            assert_eq!(decoded, Ok(cube));
+            assert_eq!(decoded.unwrap().representation(), repr);
+        }
+    }
+
+    #[test]
+    fn write_representation_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(20);
+
+        let mut buffer = [1.0f32; REPRESENTATION_SIZE];
+        assert_eq!(cube.write_representation(&mut buffer), Ok(()));
+        let expected: Vec<f32> = cube
+            .representation()
+            .iter()
+            .map(|&b| f32::from(b))
+            .collect();
+        assert_eq!(buffer.to_vec(), expected);
+
+        let mut short = [false; REPRESENTATION_SIZE - 1];
+        assert_eq!(
+            cube.write_representation(&mut short),
+            Err(CubeError::InvalidBufferLength(
+                REPRESENTATION_SIZE,
+                REPRESENTATION_SIZE - 1
+            ))
+        );
+    }
+
+    #[test]
+    fn from_representation_err_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(20);
+        let repr = cube.representation();
+        let from_repr =
+            |repr: &[bool; 480]| Cube::from_representation(repr, MetricKind::HalfTurnMetric);
+
+        let mut empty = repr;
+        empty[24 * 9..24 * 10].fill(false);
+        assert_eq!(from_repr(&empty), Err(CubeError::InvalidOneHot(9, 0)));
+
+        let mut two_states = repr;
+        two_states[24 * 3..24 * 4].fill(true);
+        assert_eq!(from_repr(&two_states), Err(CubeError::InvalidOneHot(3, 24)));
+
+        // corners 0 and 1 in the solved cubicle of corner 0
+        let mut shared = Cube::cube_htm().representation();
+        shared[24 + 3] = false;
+        shared[24] = true;
+        assert_eq!(from_repr(&shared), Err(CubeError::DuplicateCubicle(0)));
+
+        // edges 0 and 1 in the solved cubicle of edge 1
+        let mut shared = Cube::cube_htm().representation();
+        shared[24 * 8] = false;
+        shared[24 * 8 + 2] = true;
+        assert_eq!(from_repr(&shared), Err(CubeError::DuplicateCubicle(1)));
+    }
+
     #[test]
     fn cube_quarter_turn_test() {
         let mut cube = Cube::cube_qtm();
         cube.scramble(1000);
         assert!(cube.is_solvable());
     }
+
+    #[test]
+    fn facelets_round_trip_test() {
+        let solved = "WWWWWWWWWYYYYYYYYYGGGGGGGGGBBBBBBBBBRRRRRRRRROOOOOOOOO";
+        assert_eq!(Cube::cube_htm().facelets(), solved);
+
+        for _ in 0..100 {
+            let mut cube = Cube::cube_qtm();
+            cube.scramble(30);
+            let facelets = cube.facelets();
+            assert_eq!(
+                Cube::from_facelets(&facelets, MetricKind::QuarterTurnMetric),
+                Ok(cube)
+            );
+        }
+    }
+
+    #[test]
+    fn facelets_err_test() {
+        let solved: Vec<char> = Cube::cube_htm().facelets().chars().collect();
+        let from_chars = |chars: &[char]| {
+            let facelets: String = chars.iter().collect();
+            Cube::from_facelets(&facelets, MetricKind::HalfTurnMetric)
+        };
+
+        let mut mirrored = solved.clone();
+        mirrored.swap(36, 29);
+        assert_eq!(from_chars(&mirrored), Err(CubeError::InvalidCorner(0)));
+
+        let mut unknown_edge = solved.clone();
+        unknown_edge[28] = 'W';
+        assert_eq!(from_chars(&unknown_edge), Err(CubeError::InvalidEdge(0)));
+
+        let mut duplicate = solved.clone();
+        duplicate[28] = 'O';
+        assert_eq!(from_chars(&duplicate), Err(CubeError::DuplicateCubie(1)));
+
+        let mut centers = solved.clone();
+        centers.swap(4, 13);
+        assert_eq!(
+            from_chars(&centers),
+            Err(CubeError::InvalidFaceOrder(Faces::Yellow, 0))
+        );
+
+        let mut color = solved;
+        color[0] = 'X';
+        assert_eq!(from_chars(&color), Err(CubeError::InvalidFaceletColor));
+    }
+
+    #[test]
+    fn normalise_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(30);
+
+        for rotation in Symmetry::rotations() {
+            let facelets: String = rotation
+                .move_facelets(&cube.faces())
+                .iter()
+                .flatten()
+                .flatten()
+                .map(|face| face.to_string())
+                .collect();
+            let (normalised, applied) =
+                Cube::from_facelets_normalised(&facelets, MetricKind::HalfTurnMetric).unwrap();
+            assert_eq!(normalised, cube);
+
+            let rotated = rotation.rotate(&cube);
+            assert_eq!(applied.rotate(&rotated), cube);
+            assert_eq!(
+                rotation
+                    .rotate(&Cube::cube_htm())
+                    .solved_in_any_orientation(),
+                Some(applied)
+            );
+        }
+
+        // white and yellow swapped on their own is a reflection
+        let mut mirrored: Vec<char> = Cube::cube_htm().facelets().chars().collect();
+        mirrored.swap(4, 13);
+        let mirrored: String = mirrored.into_iter().collect();
+        assert_eq!(
+            Cube::from_facelets_normalised(&mirrored, MetricKind::HalfTurnMetric),
+            Err(CubeError::InvalidFaceOrder(Faces::Yellow, 0))
+        );
+    }
 }
@@ -77,7 +77,8 @@ impl Corner {
         }
     }
 
-    pub fn cubie_index(&self) -> u8 {
+    /// Returns the index of the corner cubie with the same colors, if there is one.
+    pub fn cubie_index(&self) -> Option<u8> {
         let mut cubie_face_set = HashSet::new();
         cubie_face_set.insert(self.facelet_a);
         cubie_face_set.insert(self.facelet_b);
@@ -90,11 +91,27 @@ impl Corner {
             corner_face_set.insert(corner.facelet_c());
 
             if corner_face_set == cubie_face_set {
-                return i as u8;
+                return Some(i as u8);
             }
         }
 
-        panic!("Corner cubie not found!")
+        None
+    }
+
+    /// Returns the index of the corner cubie with these colors and the orientation it has
+    /// when they are read in the order of a cubicle's facelets, if there is one. A mirrored
+    /// corner, with the right colors in the wrong cyclic order, has none.
+    pub(crate) fn cubie_and_orientation(&self) -> Option<(u8, u8)> {
+        let cubie = self.cubie_index()?;
+        let orientation = match self.get_orientation(CORNER_CUBIES[cubie as usize].facelet_a()) {
+            1 => 2,
+            2 => 1,
+            _ => 0,
+        };
+        if CORNER_CUBIES[cubie as usize].orient_corner(orientation) != *self {
+            return None;
+        }
+        Some((cubie, orientation))
     }
 
     /// Get the corner's facelet a.
@@ -148,7 +165,8 @@ impl Edge {
         }
     }
 
-    pub fn cubie_index(&self) -> u8 {
+    /// Returns the index of the edge cubie with the same colors, if there is one.
+    pub fn cubie_index(&self) -> Option<u8> {
         let mut cubie_face_set = HashSet::new();
         cubie_face_set.insert(self.facelet_a);
         cubie_face_set.insert(self.facelet_b);
@@ -159,11 +177,19 @@ impl Edge {
             edge_face_set.insert(edge.facelet_b());
 
             if edge_face_set == cubie_face_set {
-                return i as u8;
+                return Some(i as u8);
             }
         }
 
-        panic!("Edge cubie not found!")
+        None
+    }
+
+    /// Returns the index of the edge cubie with these colors and the orientation it has when
+    /// they are read in the order of a cubicle's facelets, if there is one.
+    pub(crate) fn cubie_and_orientation(&self) -> Option<(u8, u8)> {
+        let cubie = self.cubie_index()?;
+        let orientation = self.get_orientation(EDGE_CUBIES[cubie as usize].facelet_a());
+        Some((cubie, orientation))
     }
 
     /// Get the edge's facelet a.
@@ -179,6 +205,27 @@ impl Edge {
 
 pub const NUM_CORNERS: u8 = 8;
 pub const NUM_EDGES: u8 = 12;
+pub const NUM_CORNER_ORIENTATION: u8 = 3;
+pub const NUM_EDGE_ORIENTATION: u8 = 2;
+pub const NUM_STATES: u8 = 24;
+pub const NUM_FACELETS: usize = 54;
+
+/// Length of the one-hot representation, one block of `NUM_STATES` per cubie.
+pub const REPRESENTATION_SIZE: usize = NUM_STATES as usize * (NUM_CORNERS + NUM_EDGES) as usize;
+
+/// Index in the representation of corner `cubie` in `cubicle` with `orientation`.
+pub const fn corner_state_index(cubicle: u8, cubie: u8, orientation: u8) -> usize {
+    (NUM_STATES * cubie) as usize
+        + (NUM_CORNER_ORIENTATION * cubicle) as usize
+        + orientation as usize
+}
+
+/// Index in the representation of edge `cubie` in `cubicle` with `orientation`.
+pub const fn edge_state_index(cubicle: u8, cubie: u8, orientation: u8) -> usize {
+    NUM_STATES as usize * (cubie + NUM_CORNERS) as usize
+        + (NUM_EDGE_ORIENTATION * cubicle) as usize
+        + orientation as usize
+}
 
 type FaceletIdxs = (Faces, u8, u8);
 
@@ -0,0 +1,287 @@
+//! Generates supervised datasets of scrambled cubes labelled with their distance to solved.
+//!
+//! Every sample is a cube scrambled with a number of random turns, its scramble depth, and
+//! optionally its exact distance to solved found by `solver::solve`. The scramble depth is
+//! only an upper bound on the distance, as random turns may cancel out. Depths are spread
+//! evenly from 0 to the maximum depth and the samples are shuffled.
+//!
+//! Generation is deterministic: the same [`DatasetConfig`] always gives the same samples with
+//! a given version of this crate.
+//!
+//! # File format
+//!
+//! [`write_npy`] writes an [NPY] version 1.0 file holding a one dimensional array of
+//! records, one per sample, with the fields
+//!
+//! * `state`: `uint8` array, either the 480 bits of `Cube::representation` or the 20 cubie
+//!   state indices of `encoding::DenseIndex`, see [`StateFormat`],
+//! * `depth`: little endian `uint32` scramble depth,
+//! * `distance`: little endian `int32` distance to solved, `-1` when unknown.
+//!
+//! In Python, `numpy.load(path)["state"]` returns the states as a 2D array.
+//!
+//! [NPY]: https://numpy.org/doc/stable/reference/generated/numpy.lib.format.html
+
+use std::io::{self, Write};
+
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    encoding::{DenseIndex, Encoding},
+    moves::MetricKind,
+    solver, Cube,
+};
+
+/// Encoding of the states written to a dataset file.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum StateFormat {
+    /// the 480 one-hot bits of `Cube::representation`
+    Representation,
+    /// the 20 cubie state indices of `encoding::DenseIndex`, each below 24
+    Packed,
+}
+
+impl StateFormat {
+    /// Number of bytes of an encoded state.
+    pub fn size(&self) -> usize {
+        match self {
+            StateFormat::Representation => crate::REPRESENTATION_SIZE,
+            StateFormat::Packed => DenseIndex.size(),
+        }
+    }
+
+    fn encode(&self, cube: &Cube) -> Vec<u8> {
+        match self {
+            StateFormat::Representation => cube.representation().map(u8::from).to_vec(),
+            StateFormat::Packed => DenseIndex
+                .encode(cube)
+                .into_iter()
+                .map(|index| index as u8)
+                .collect(),
+        }
+    }
+}
+
+/// Configuration of a generated dataset.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DatasetConfig {
+    pub num_samples: usize,
+    /// Largest scramble depth.
+    pub max_depth: u32,
+    pub turn_metric: MetricKind,
+    pub seed: u64,
+    /// Longest solution searched for to label samples with their exact distance, `None` to
+    /// only label them with their scramble depth.
+    pub solver_depth: Option<usize>,
+}
+
+impl Default for DatasetConfig {
+    fn default() -> Self {
+        DatasetConfig {
+            num_samples: 10_000,
+            max_depth: 20,
+            turn_metric: MetricKind::HalfTurnMetric,
+            seed: 0,
+            solver_depth: None,
+        }
+    }
+}
+
+/// A scrambled cube and its labels.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Sample {
+    pub cube: Cube,
+    /// Number of random turns the cube was scrambled with.
+    pub depth: u32,
+    /// Length of a shortest solution, `None` if not searched for or longer than the
+    /// configured `solver_depth`.
+    pub distance: Option<u32>,
+}
+
+/// Generates the samples described by `config`.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::dataset::{generate, DatasetConfig};
+///
+/// let config = DatasetConfig {
+///     num_samples: 100,
+///     max_depth: 3,
+///     solver_depth: Some(3),
+///     ..DatasetConfig::default()
+/// };
+/// let samples = generate(&config);
+///
+/// assert_eq!(samples, generate(&config));
+/// for sample in samples {
+///     assert!(sample.distance.unwrap() <= sample.depth);
+/// }
+/// ```
+pub fn generate(config: &DatasetConfig) -> Vec<Sample> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut samples: Vec<Sample> = (0..config.num_samples)
+        .map(|idx| {
+            let depth = (idx % (config.max_depth as usize + 1)) as u32;
+            let mut cube = Cube::new(config.turn_metric);
+            cube.scramble_with_rng(depth, &mut rng);
+            // the scramble itself is a solution, so searching deeper is never needed
+            let distance = config.solver_depth.and_then(|solver_depth| {
+                solver::solve(&cube, solver_depth.min(depth as usize))
+                    .map(|solution| solution.len() as u32)
+            });
+            Sample {
+                cube,
+                depth,
+                distance,
+            }
+        })
+        .collect();
+    samples.shuffle(&mut rng);
+    samples
+}
+
+/// Writes the samples as an NPY file, see the [module documentation](self) for the format.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::dataset::{generate, write_npy, DatasetConfig, StateFormat};
+///
+/// let config = DatasetConfig {
+///     num_samples: 10,
+///     ..DatasetConfig::default()
+/// };
+/// let mut buffer = Vec::new();
+/// write_npy(&generate(&config), StateFormat::Packed, &mut buffer).unwrap();
+///
+/// assert!(buffer.starts_with(b"\x93NUMPY"));
+/// // a 128 byte header followed by 10 records of 20 state bytes, a depth and a distance
+/// assert_eq!(buffer.len(), 128 + 10 * (20 + 4 + 4));
+/// ```
+pub fn write_npy<W: Write>(
+    samples: &[Sample],
+    format: StateFormat,
+    writer: &mut W,
+) -> io::Result<()> {
+    const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+    // magic, version and the 2 byte header length precede the header
+    const PREAMBLE_LENGTH: usize = MAGIC.len() + 2;
+    const ALIGNMENT: usize = 64;
+
+    let mut header = format!(
+        "{{'descr': [('state', '|u1', ({},)), ('depth', '<u4'), ('distance', '<i4')], \
+         'fortran_order': False, 'shape': ({},), }}",
+        format.size(),
+        samples.len()
+    );
+    // the header ends with a newline and is padded so the data is aligned
+    let padding = (ALIGNMENT - (PREAMBLE_LENGTH + header.len() + 1) % ALIGNMENT) % ALIGNMENT;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for sample in samples {
+        let distance = sample.distance.map_or(-1, |distance| distance as i32);
+        writer.write_all(&format.encode(&sample.cube))?;
+        writer.write_all(&sample.depth.to_le_bytes())?;
+        writer.write_all(&distance.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate, write_npy, DatasetConfig, StateFormat};
+    use crate::encoding::{DenseIndex, Encoding};
+    use crate::MetricKind;
+
+    #[test]
+    fn generate_test() {
+        let config = DatasetConfig {
+            num_samples: 60,
+            max_depth: 4,
+            turn_metric: MetricKind::QuarterTurnMetric,
+            seed: 3,
+            solver_depth: Some(4),
+        };
+        let samples = generate(&config);
+        assert_eq!(samples.len(), 60);
+        assert_eq!(samples, generate(&config));
+        assert_ne!(samples, generate(&DatasetConfig { seed: 4, ..config }));
+
+        for depth in 0..=4 {
+            assert_eq!(samples.iter().filter(|s| s.depth == depth).count(), 12);
+        }
+        for sample in samples {
+            let distance = sample.distance.unwrap();
+            assert!(distance <= sample.depth);
+            assert_eq!(distance % 2, sample.depth % 2);
+            assert_eq!(sample.cube.solved(), distance == 0);
+            assert_eq!(sample.cube.turn_metric(), MetricKind::QuarterTurnMetric);
+        }
+    }
+
+    #[test]
+    fn unlabelled_distance_test() {
+        let config = DatasetConfig {
+            num_samples: 20,
+            max_depth: 3,
+            solver_depth: Some(1),
+            ..DatasetConfig::default()
+        };
+        for sample in generate(&config) {
+            if sample.depth <= 1 {
+                assert_eq!(sample.distance, Some(sample.depth));
+            }
+        }
+        let config = DatasetConfig {
+            solver_depth: None,
+            ..config
+        };
+        assert!(generate(&config).iter().all(|s| s.distance.is_none()));
+    }
+
+    #[test]
+    fn write_npy_test() {
+        let config = DatasetConfig {
+            num_samples: 5,
+            solver_depth: None,
+            ..DatasetConfig::default()
+        };
+        let samples = generate(&config);
+
+        for format in [StateFormat::Representation, StateFormat::Packed] {
+            let mut buffer = Vec::new();
+            write_npy(&samples, format, &mut buffer).unwrap();
+
+            let header_length = u16::from_le_bytes([buffer[8], buffer[9]]) as usize;
+            let data = &buffer[10 + header_length..];
+            assert_eq!((10 + header_length) % 64, 0);
+            let header = std::str::from_utf8(&buffer[10..10 + header_length]).unwrap();
+            assert!(header.contains(&format!("({},)", format.size())));
+            assert!(header.contains("'shape': (5,)"));
+            assert!(header.ends_with('\n'));
+
+            let record = format.size() + 8;
+            assert_eq!(data.len(), 5 * record);
+            for (sample, record) in samples.iter().zip(data.chunks_exact(record)) {
+                let (state, labels) = record.split_at(format.size());
+                if format == StateFormat::Packed {
+                    let expected: Vec<u8> = DenseIndex
+                        .encode(&sample.cube)
+                        .iter()
+                        .map(|&v| v as u8)
+                        .collect();
+                    assert_eq!(state, &expected[..]);
+                } else {
+                    assert_eq!(state.iter().map(|&b| b as usize).sum::<usize>(), 20);
+                }
+                assert_eq!(labels[..4], sample.depth.to_le_bytes());
+                assert_eq!(labels[4..], (-1i32).to_le_bytes());
+            }
+        }
+    }
+}
@@ -0,0 +1,367 @@
+//! Encodings of a [`Cube`] as flat `f32` buffers for neural networks.
+//!
+//! Every encoding implements the [`Encoding`] trait, so models can switch between them
+//! through a `&dyn Encoding` without changing the code producing the cubes. Buffers are
+//! laid out row-major according to `Encoding::shape`.
+
+use crate::{
+    cubies::{
+        Faces, NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES, NUM_EDGE_ORIENTATION, NUM_FACELETS,
+        NUM_STATES, REPRESENTATION_SIZE,
+    },
+    errors::CubeError,
+    moves::MetricKind,
+    Cube,
+};
+
+const NUM_FACES: usize = 6;
+const NUM_CUBIES: usize = (NUM_CORNERS + NUM_EDGES) as usize;
+
+/// A lossless encoding of a cube as a buffer of `f32`.
+pub trait Encoding {
+    /// Shape of the encoded tensor.
+    fn shape(&self) -> &'static [usize];
+
+    /// Number of values in the encoded buffer, the product of `shape`.
+    fn size(&self) -> usize {
+        self.shape().iter().product()
+    }
+
+    /// Writes the encoding of `cube` to `buffer`, which must have `size` values.
+    fn encode_into(&self, cube: &Cube, buffer: &mut [f32]) -> Result<(), CubeError>;
+
+    /// Returns the encoding of `cube`.
+    fn encode(&self, cube: &Cube) -> Vec<f32> {
+        let mut buffer = vec![0.0; self.size()];
+        self.encode_into(cube, &mut buffer).unwrap();
+        buffer
+    }
+
+    /// Recovers the cube from its encoding.
+    fn decode(&self, values: &[f32], turn_metric: MetricKind) -> Result<Cube, CubeError>;
+}
+
+fn check_length(encoding: &dyn Encoding, length: usize) -> Result<(), CubeError> {
+    if length != encoding.size() {
+        return Err(CubeError::InvalidBufferLength(encoding.size(), length));
+    }
+    Ok(())
+}
+
+/// Reads the value at `index` as an integer below `limit`.
+fn integer(values: &[f32], index: usize, limit: usize) -> Result<usize, CubeError> {
+    let value = values[index];
+    if value.fract() != 0.0 || !(0.0..limit as f32).contains(&value) {
+        return Err(CubeError::InvalidEncodedValue(index));
+    }
+    Ok(value as usize)
+}
+
+/// Reads the color of `facelet` from its one-hot channels, with the channel of each color
+/// at `index(color)`.
+fn one_hot_color(
+    values: &[f32],
+    facelet: usize,
+    index: impl Fn(usize) -> usize,
+) -> Result<Faces, CubeError> {
+    let mut set = Vec::new();
+    for color in 0..NUM_FACES {
+        if integer(values, index(color), 2)? == 1 {
+            set.push(color);
+        }
+    }
+    if set.len() != 1 {
+        return Err(CubeError::InvalidOneHot(facelet, set.len()));
+    }
+    Ok(Faces::from_repr(set[0]).unwrap())
+}
+
+/// One-hot encoding of the color of each of the 54 facelets, of shape 54 x 6.
+///
+/// Facelets are in the order of `Cube::facelets` and colors in the order of [`Faces`].
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::encoding::{Encoding, FaceletOneHot};
+/// use rubikscube::{Cube, MetricKind, Turn};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.turn(Turn::F as u8).unwrap();
+///
+/// let values = FaceletOneHot.encode(&cube);
+/// assert_eq!(values.iter().sum::<f32>(), 54.0);
+/// assert_eq!(FaceletOneHot.decode(&values, MetricKind::HalfTurnMetric), Ok(cube));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct FaceletOneHot;
+
+impl Encoding for FaceletOneHot {
+    fn shape(&self) -> &'static [usize] {
+        &[NUM_FACELETS, NUM_FACES]
+    }
+
+    fn encode_into(&self, cube: &Cube, buffer: &mut [f32]) -> Result<(), CubeError> {
+        check_length(self, buffer.len())?;
+        buffer.fill(0.0);
+        for (facelet, &color) in cube.faces().iter().flatten().flatten().enumerate() {
+            buffer[NUM_FACES * facelet + color as usize] = 1.0;
+        }
+        Ok(())
+    }
+
+    /// Returns `CubeError::InvalidEncodedValue` for values other than 0 and 1 and
+    /// `CubeError::InvalidOneHot` for facelets without exactly one color set.
+    fn decode(&self, values: &[f32], turn_metric: MetricKind) -> Result<Cube, CubeError> {
+        check_length(self, values.len())?;
+        let mut faces = [[[Faces::White; 3]; 3]; 6];
+        for (facelet, color) in faces.iter_mut().flatten().flatten().enumerate() {
+            *color = one_hot_color(values, facelet, |channel| NUM_FACES * facelet + channel)?;
+        }
+        Cube::cube_from_faces(&faces, turn_metric)
+    }
+}
+
+/// The one-hot `Cube::representation`, of shape 20 x 24.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::encoding::{Encoding, OneHot};
+/// use rubikscube::{Cube, MetricKind};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.scramble(20);
+///
+/// let values = OneHot.encode(&cube);
+/// assert_eq!(values.iter().sum::<f32>(), 20.0);
+/// assert_eq!(OneHot.decode(&values, MetricKind::HalfTurnMetric), Ok(cube));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct OneHot;
+
+impl Encoding for OneHot {
+    fn shape(&self) -> &'static [usize] {
+        &[NUM_CUBIES, NUM_STATES as usize]
+    }
+
+    fn encode_into(&self, cube: &Cube, buffer: &mut [f32]) -> Result<(), CubeError> {
+        check_length(self, buffer.len())?;
+        for (value, &bit) in buffer.iter_mut().zip(cube.representation().iter()) {
+            *value = f32::from(bit);
+        }
+        Ok(())
+    }
+
+    /// Returns `CubeError::InvalidEncodedValue` for values other than 0 and 1, see
+    /// `Cube::from_representation` for the other errors.
+    fn decode(&self, values: &[f32], turn_metric: MetricKind) -> Result<Cube, CubeError> {
+        check_length(self, values.len())?;
+        let mut representation = [false; REPRESENTATION_SIZE];
+        for (index, bit) in representation.iter_mut().enumerate() {
+            *bit = integer(values, index, 2)? == 1;
+        }
+        Cube::from_representation(&representation, turn_metric)
+    }
+}
+
+/// Encoding of the state of each of the 20 cubies as an index below 24, of shape 20.
+///
+/// Corners come first, then edges, in cubie order. The index of a corner is
+/// `3 * cubicle + orientation` and the index of an edge `2 * cubicle + orientation`, so the
+/// value of cubie `i` is the offset of its set bit within block `i` of
+/// `Cube::representation`, making this the dense form of that 20 x 24 one-hot encoding.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::encoding::{DenseIndex, Encoding};
+/// use rubikscube::{Cube, MetricKind};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.scramble(20);
+///
+/// let values = DenseIndex.encode(&cube);
+/// assert_eq!(values.len(), 20);
+/// assert_eq!(DenseIndex.decode(&values, MetricKind::HalfTurnMetric), Ok(cube));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DenseIndex;
+
+impl Encoding for DenseIndex {
+    fn shape(&self) -> &'static [usize] {
+        &[NUM_CUBIES]
+    }
+
+    fn encode_into(&self, cube: &Cube, buffer: &mut [f32]) -> Result<(), CubeError> {
+        check_length(self, buffer.len())?;
+        let [edge_orientation, corner_orientation, edge_permutation, corner_permutation] =
+            cube.cubicles();
+        for (cubicle, &cubie) in corner_permutation.iter().enumerate() {
+            let index =
+                NUM_CORNER_ORIENTATION as usize * cubicle + corner_orientation[cubicle] as usize;
+            buffer[cubie as usize] = index as f32;
+        }
+        for (cubicle, &cubie) in edge_permutation.iter().enumerate() {
+            let index =
+                NUM_EDGE_ORIENTATION as usize * cubicle + edge_orientation[cubicle] as usize;
+            buffer[(NUM_CORNERS + cubie) as usize] = index as f32;
+        }
+        Ok(())
+    }
+
+    /// Returns `CubeError::InvalidEncodedValue` for values that are not integers below 24
+    /// and `CubeError::DuplicateCubicle` if two corners or two edges share a cubicle.
+    fn decode(&self, values: &[f32], turn_metric: MetricKind) -> Result<Cube, CubeError> {
+        check_length(self, values.len())?;
+        let mut indices = [0; NUM_CUBIES];
+        for (cubie, index) in indices.iter_mut().enumerate() {
+            *index = integer(values, cubie, NUM_STATES as usize)? as u8;
+        }
+        Cube::from_state_indices(&indices, turn_metric)
+    }
+}
+
+/// One-hot color channels over the facelets of each face, of shape 6 x 6 x 3 x 3.
+///
+/// The encoding is one-hot rather than a 6 x 3 x 3 grid of color indices, as separate
+/// channels keep the colors unordered, where a color index per facelet would make some
+/// colors closer to each other than others.
+///
+/// The color channel axis comes first, so the axes are color, face, row and column, and
+/// facelet `(face, row, column)` of color `color` is at index
+/// `54 * color + 9 * face + 3 * row + column`. Colors are in the order of [`Faces`] and
+/// faces, rows and columns are laid out as in `Cube::faces`.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::encoding::{ColorTensor, Encoding};
+/// use rubikscube::{Cube, MetricKind};
+///
+/// let values = ColorTensor.encode(&Cube::cube_htm());
+/// assert_eq!(values.iter().sum::<f32>(), 54.0);
+/// // the white channel is set on the white face only
+/// assert_eq!(values[..9], [1.0; 9]);
+/// assert_eq!(values[9..54], [0.0; 45]);
+/// assert!(ColorTensor.decode(&values, MetricKind::HalfTurnMetric).unwrap().solved());
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ColorTensor;
+
+impl Encoding for ColorTensor {
+    fn shape(&self) -> &'static [usize] {
+        &[NUM_FACES, NUM_FACES, 3, 3]
+    }
+
+    fn encode_into(&self, cube: &Cube, buffer: &mut [f32]) -> Result<(), CubeError> {
+        check_length(self, buffer.len())?;
+        buffer.fill(0.0);
+        for (facelet, &color) in cube.faces().iter().flatten().flatten().enumerate() {
+            buffer[NUM_FACELETS * color as usize + facelet] = 1.0;
+        }
+        Ok(())
+    }
+
+    /// Returns `CubeError::InvalidEncodedValue` for values other than 0 and 1 and
+    /// `CubeError::InvalidOneHot` for facelets without exactly one color set.
+    fn decode(&self, values: &[f32], turn_metric: MetricKind) -> Result<Cube, CubeError> {
+        check_length(self, values.len())?;
+        let mut faces = [[[Faces::White; 3]; 3]; 6];
+        for (facelet, color) in faces.iter_mut().flatten().flatten().enumerate() {
+            *color = one_hot_color(values, facelet, |channel| NUM_FACELETS * channel + facelet)?;
+        }
+        Cube::cube_from_faces(&faces, turn_metric)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorTensor, DenseIndex, Encoding, FaceletOneHot, OneHot};
+    use crate::errors::CubeError;
+    use crate::{Cube, MetricKind};
+
+    const ENCODINGS: [&dyn Encoding; 4] = [&OneHot, &FaceletOneHot, &DenseIndex, &ColorTensor];
+
+    #[test]
+    fn round_trip_test() {
+        for _ in 0..100 {
+            let mut cube = Cube::cube_qtm();
+            cube.scramble(30);
+            for encoding in ENCODINGS {
+                let values = encoding.encode(&cube);
+                assert_eq!(values.len(), encoding.size());
+                assert_eq!(
+                    encoding.decode(&values, MetricKind::QuarterTurnMetric),
+                    Ok(cube)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn dense_index_matches_representation_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(20);
+        let representation = cube.representation();
+        for (cubie, &index) in DenseIndex.encode(&cube).iter().enumerate() {
+            let block = &representation[24 * cubie..][..24];
+            assert!(block[index as usize]);
+            assert_eq!(block.iter().filter(|&&bit| bit).count(), 1);
+        }
+    }
+
+    #[test]
+    fn decode_err_test() {
+        let metric = MetricKind::HalfTurnMetric;
+        let solved = Cube::cube_htm();
+        for encoding in ENCODINGS {
+            assert_eq!(
+                encoding.decode(&[0.0; 3], metric),
+                Err(CubeError::InvalidBufferLength(encoding.size(), 3))
+            );
+        }
+
+        let mut values = FaceletOneHot.encode(&solved);
+        values[7] = 1.0;
+        assert_eq!(
+            FaceletOneHot.decode(&values, metric),
+            Err(CubeError::InvalidOneHot(1, 2))
+        );
+        values[7] = 0.5;
+        assert_eq!(
+            FaceletOneHot.decode(&values, metric),
+            Err(CubeError::InvalidEncodedValue(7))
+        );
+
+        let mut values = DenseIndex.encode(&solved);
+        values[1] = 0.0;
+        assert_eq!(
+            DenseIndex.decode(&values, metric),
+            Err(CubeError::DuplicateCubicle(0))
+        );
+        values[1] = 24.0;
+        assert_eq!(
+            DenseIndex.decode(&values, metric),
+            Err(CubeError::InvalidEncodedValue(1))
+        );
+
+        // the center of the white face set in the yellow channel too
+        let mut values = ColorTensor.encode(&solved);
+        values[54 + 4] = 1.0;
+        assert_eq!(
+            ColorTensor.decode(&values, metric),
+            Err(CubeError::InvalidOneHot(4, 2))
+        );
+        values[4] = 0.0;
+        assert_eq!(
+            ColorTensor.decode(&values, metric),
+            Err(CubeError::InvalidFaceOrder(crate::Faces::Yellow, 0))
+        );
+        values[4] = -1.0;
+        assert_eq!(
+            ColorTensor.decode(&values, metric),
+            Err(CubeError::InvalidEncodedValue(4))
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Gym style reinforcement learning environment around a single [`Cube`].
+
+use crate::{cube::Cube, cubies::REPRESENTATION_SIZE, errors::CubeError, moves::MetricKind};
+
+/// Observation returned by the environment, the one-hot `Cube::representation`.
+pub type Observation = [bool; REPRESENTATION_SIZE];
+
+/// Function giving the distance of a cube to solved, such as the solution length of a solver.
+pub type DistanceFn = Box<dyn Fn(&Cube) -> u32>;
+
+/// Reward shaping of the environment.
+///
+/// The reward of a step is `-step_penalty`, plus `solved` if the step solved the cube, plus
+/// `distance_scale` times the decrease of the distance to solved when a distance function
+/// is set with [`CubeEnv::with_distance`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Reward {
+    pub solved: f32,
+    pub step_penalty: f32,
+    pub distance_scale: f32,
+}
+
+impl Default for Reward {
+    /// Sparse reward of 1 when the cube is solved.
+    fn default() -> Self {
+        Reward {
+            solved: 1.0,
+            step_penalty: 0.0,
+            distance_scale: 0.0,
+        }
+    }
+}
+
+/// Configuration of a [`CubeEnv`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct EnvConfig {
+    pub turn_metric: MetricKind,
+    /// Number of steps after which an episode is truncated.
+    pub max_steps: u32,
+    pub reward: Reward,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            turn_metric: MetricKind::HalfTurnMetric,
+            max_steps: 100,
+            reward: Reward::default(),
+        }
+    }
+}
+
+/// Extra information about a step.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct StepInfo {
+    /// Number of steps taken in the episode so far.
+    pub steps: u32,
+    pub solved: bool,
+    /// True if the episode ended because `max_steps` was reached before solving the cube.
+    pub truncated: bool,
+    /// Distance to solved, if a distance function is set.
+    pub distance: Option<u32>,
+}
+
+/// Gym style environment: actions are turn indices and observations are cube representations.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::env::{CubeEnv, EnvConfig};
+///
+/// let mut env = CubeEnv::new(EnvConfig::default());
+/// let _observation = env.reset(10);
+///
+/// let (_observation, _reward, done, info) = env.step(0).unwrap();
+/// assert_eq!(info.steps, 1);
+/// assert_eq!(done, info.solved || info.truncated);
+/// ```
+pub struct CubeEnv {
+    cube: Cube,
+    config: EnvConfig,
+    distance_fn: Option<DistanceFn>,
+    steps: u32,
+    distance: Option<u32>,
+    done: bool,
+}
+
+impl CubeEnv {
+    pub fn new(config: EnvConfig) -> CubeEnv {
+        CubeEnv {
+            cube: Cube::new(config.turn_metric),
+            config,
+            distance_fn: None,
+            steps: 0,
+            distance: None,
+            done: true,
+        }
+    }
+
+    /// Sets the function giving the distance of a cube to solved, used for distance based
+    /// reward shaping.
+    pub fn with_distance<F: Fn(&Cube) -> u32 + 'static>(mut self, distance_fn: F) -> CubeEnv {
+        self.distance_fn = Some(Box::new(distance_fn));
+        self
+    }
+
+    /// Returns the number of actions, the number of turns of the turn metric.
+    pub fn action_space(&self) -> u8 {
+        self.config.turn_metric as u8
+    }
+
+    /// Returns the configuration of the environment.
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    /// Returns the current cube.
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Starts a new episode from a cube scrambled with `scramble_depth` random turns.
+    pub fn reset(&mut self, scramble_depth: u32) -> Observation {
+        self.cube = Cube::new(self.config.turn_metric);
+        self.cube.scramble(scramble_depth);
+        self.steps = 0;
+        self.distance = self.distance_fn.as_ref().map(|f| f(&self.cube));
+        self.done = false;
+
+        self.cube.representation()
+    }
+
+    /// Performs `action` and returns the observation, reward, whether the episode is done and
+    /// extra information about the step.
+    ///
+    /// Returns an error for actions outside of the action space, or if the episode is done
+    /// and `reset` has not been called since.
+    pub fn step(&mut self, action: u8) -> Result<(Observation, f32, bool, StepInfo), CubeError> {
+        if self.done {
+            return Err(CubeError::EpisodeDone);
+        }
+        self.cube.turn(action)?;
+        self.steps += 1;
+
+        let reward_config = self.config.reward;
+        let solved = self.cube.solved();
+        let mut reward = -reward_config.step_penalty;
+        if solved {
+            reward += reward_config.solved;
+        }
+
+        if let Some(distance_fn) = &self.distance_fn {
+            let distance = distance_fn(&self.cube);
+            if let Some(previous_distance) = self.distance {
+                reward +=
+                    reward_config.distance_scale * (previous_distance as f32 - distance as f32);
+            }
+            self.distance = Some(distance);
+        }
+
+        let truncated = !solved && self.steps >= self.config.max_steps;
+        self.done = solved || truncated;
+
+        let info = StepInfo {
+            steps: self.steps,
+            solved,
+            truncated,
+            distance: self.distance,
+        };
+
+        Ok((self.cube.representation(), reward, self.done, info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CubeEnv, EnvConfig, Reward};
+    use crate::{errors::CubeError, MetricKind, Turn};
+
+    #[test]
+    fn step_before_reset_test() {
+        let mut env = CubeEnv::new(EnvConfig::default());
+        assert_eq!(env.step(0).unwrap_err(), CubeError::EpisodeDone);
+    }
+
+    #[test]
+    fn action_space_test() {
+        let mut config = EnvConfig::default();
+        assert_eq!(CubeEnv::new(config).action_space(), 18);
+
+        config.turn_metric = MetricKind::QuarterTurnMetric;
+        let mut env = CubeEnv::new(config);
+        env.reset(5);
+        assert_eq!(env.action_space(), 12);
+        assert_eq!(env.step(12).unwrap_err(), CubeError::InvalidTurn(12, 12));
+    }
+
+    #[test]
+    fn solved_reward_test() {
+        let config = EnvConfig {
+            reward: Reward {
+                solved: 10.0,
+                step_penalty: 0.5,
+                distance_scale: 0.0,
+            },
+            ..EnvConfig::default()
+        };
+        let mut env = CubeEnv::new(config);
+        env.reset(0);
+
+        let (_, reward, done, info) = env.step(Turn::L as u8).unwrap();
+        assert_eq!((reward, done, info.solved), (-0.5, false, false));
+
+        let (observation, reward, done, info) = env.step(Turn::L_ as u8).unwrap();
+        assert_eq!((reward, done, info.solved), (9.5, true, true));
+        assert_eq!(observation, env.cube().representation());
+        assert_eq!(env.step(0).unwrap_err(), CubeError::EpisodeDone);
+    }
+
+    #[test]
+    fn truncation_test() {
+        let config = EnvConfig {
+            max_steps: 3,
+            ..EnvConfig::default()
+        };
+        let mut env = CubeEnv::new(config);
+        env.reset(0);
+
+        for _ in 0..2 {
+            let (_, _, done, _) = env.step(Turn::U as u8).unwrap();
+            assert!(!done);
+        }
+        let (_, _, done, info) = env.step(Turn::R as u8).unwrap();
+        assert!(done && info.truncated && !info.solved);
+    }
+
+    #[test]
+    fn distance_reward_test() {
+        let config = EnvConfig {
+            reward: Reward {
+                solved: 0.0,
+                step_penalty: 0.0,
+                distance_scale: 1.0,
+            },
+            ..EnvConfig::default()
+        };
+        // number of misplaced or misoriented cubies, as a stand-in for a solver
+        let mut env = CubeEnv::new(config).with_distance(|cube| {
+            let [eo, co, ep, cp] = cube.get_state();
+            let misplaced = |p: &Vec<u8>| {
+                p.iter()
+                    .enumerate()
+                    .filter(|(i, &c)| *i != c as usize)
+                    .count()
+            };
+            let twisted = |o: &Vec<u8>| o.iter().filter(|&&o| o != 0).count();
+            (misplaced(&ep) + misplaced(&cp) + twisted(&eo) + twisted(&co)) as u32
+        });
+        env.reset(0);
+
+        let (_, reward, _, info) = env.step(Turn::U as u8).unwrap();
+        assert_eq!(info.distance, Some(8));
+        assert_eq!(reward, -8.0);
+
+        let (_, reward, _, info) = env.step(Turn::U_ as u8).unwrap();
+        assert_eq!(info.distance, Some(0));
+        assert_eq!(reward, 8.0);
+    }
+}
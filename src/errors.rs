@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::cubies::Faces;
+use crate::moves::Turn;
 
 #[derive(Debug, PartialEq)]
 pub enum CubeError {
@@ -8,6 +9,26 @@ pub enum CubeError {
     InvalidFaceletColor,
     InvalidTurn(u8, u8),
     InvalidState,
+    InvalidStateLength(u8, usize),
+    CubieOutOfRange(u8, u8),
+    DuplicateCubie(u8),
+    InvalidOrientation(u8, u8),
+    InvalidBufferLength(usize, usize),
+    EpisodeDone,
+    InvalidFaceletCount(usize),
+    InvalidCorner(u8),
+    InvalidEdge(u8),
+    InvalidMove(String),
+    InvalidHistoryPosition(usize, usize),
+    InvalidEncodedValue(usize),
+    InvalidOneHot(usize, usize),
+    DuplicateCubicle(u8),
+    TooManyCoordinates(u64, u64),
+    InvalidAlgorithmLine(usize),
+    InvalidCubeSize(usize),
+    InvalidLayer(usize, usize),
+    MixedTurnMetrics(usize),
+    UnsupportedTurn(Turn),
 }
 
 impl std::error::Error for CubeError {}
@@ -34,6 +55,136 @@ impl fmt::Display for CubeError {
             CubeError::InvalidState => {
                 write!(f, "Invalid State",)
             }
+            CubeError::InvalidStateLength(expected, found) => {
+                write!(
+                    f,
+                    "Invalid State length, Expected {} values got {}",
+                    expected, found
+                )
+            }
+            CubeError::CubieOutOfRange(cubie, limit) => {
+                write!(
+                    f,
+                    "Invalid cubie, Expected int between 0 and {} got {}",
+                    limit - 1,
+                    cubie
+                )
+            }
+            CubeError::DuplicateCubie(cubie) => {
+                write!(
+                    f,
+                    "Invalid permutation, cubie {} found more than once",
+                    cubie
+                )
+            }
+            CubeError::InvalidBufferLength(expected, found) => {
+                write!(
+                    f,
+                    "Invalid buffer length, Expected {} values got {}",
+                    expected, found
+                )
+            }
+            CubeError::EpisodeDone => {
+                write!(f, "Episode is done, reset the environment before stepping")
+            }
+            CubeError::InvalidFaceletCount(found) => {
+                write!(
+                    f,
+                    "Invalid facelet string, Expected 54 facelets got {}",
+                    found
+                )
+            }
+            CubeError::InvalidCorner(cubicle) => {
+                write!(
+                    f,
+                    "Invalid corner, facelets in corner cubicle {} do not form a corner cubie",
+                    cubicle
+                )
+            }
+            CubeError::InvalidEdge(cubicle) => {
+                write!(
+                    f,
+                    "Invalid edge, facelets in edge cubicle {} do not form an edge cubie",
+                    cubicle
+                )
+            }
+            CubeError::InvalidMove(notation) => {
+                write!(f, "Invalid move, could not parse {:?}", notation)
+            }
+            CubeError::InvalidHistoryPosition(limit, position) => {
+                write!(
+                    f,
+                    "Invalid history position, Expected int between 0 and {} got {}",
+                    limit, position
+                )
+            }
+            CubeError::InvalidEncodedValue(index) => {
+                write!(f, "Invalid encoded value at index {}", index)
+            }
+            CubeError::InvalidOneHot(block, found) => {
+                write!(
+                    f,
+                    "Invalid one-hot block {}, Expected 1 value set got {}",
+                    block, found
+                )
+            }
+            CubeError::DuplicateCubicle(cubicle) => {
+                write!(
+                    f,
+                    "Invalid state, cubicle {} holds more than one cubie",
+                    cubicle
+                )
+            }
+            CubeError::TooManyCoordinates(limit, found) => {
+                write!(
+                    f,
+                    "Too many coordinates, Expected at most {} got {}",
+                    limit, found
+                )
+            }
+            CubeError::InvalidAlgorithmLine(line) => {
+                write!(
+                    f,
+                    "Invalid algorithm line, Expected `OLL <name>: <moves>` or `PLL <name>: <moves>` got line {}",
+                    line
+                )
+            }
+            CubeError::InvalidCubeSize(found) => {
+                write!(
+                    f,
+                    "Invalid facelet string, Expected 6 * n * n facelets for a cube of size n of at least 2 got {}",
+                    found
+                )
+            }
+            CubeError::InvalidLayer(depth, size) => {
+                write!(
+                    f,
+                    "Invalid layer, Expected int between 1 and {} got {}",
+                    size, depth
+                )
+            }
+            CubeError::MixedTurnMetrics(index) => {
+                write!(
+                    f,
+                    "Mixed turn metrics, cube {} does not use the turn metric of cube 0",
+                    index
+                )
+            }
+            CubeError::UnsupportedTurn(turn) => {
+                write!(
+                    f,
+                    "Unsupported turn, {} is not a turn of the puzzle in its turn metric",
+                    turn.to_string().trim()
+                )
+            }
+            CubeError::InvalidOrientation(orientation, limit) => {
+                write!(
+                    f,
+                    "Invalid orientation, Expected int between 0 and {} got {}",
+                    limit - 1,
+                    orientation
+                )
+            }
         }
     }
 }
@@ -0,0 +1,228 @@
+//! Partial states of the cube, for solving only some of its cubies.
+
+use crate::cubies::{NUM_CORNERS, NUM_EDGES};
+
+/// Selects the cubies whose permutation or orientation has to be solved.
+///
+/// Cubies are numbered as the cubicles they belong to in the solved cube, in the same order
+/// as the permutations returned by `Cube::get_state`. A cubie's permutation is solved when
+/// it is in its own cubicle, its orientation is solved when it is not flipped or twisted,
+/// wherever it is.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{Cube, Goal, Turn};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.turn(Turn::U as u8).unwrap();
+///
+/// assert!(cube.matches(&Goal::cross()));
+/// assert!(cube.matches(&Goal::edges_oriented()));
+/// assert!(!cube.matches(&Goal::new().edge(0)));
+/// assert!(cube.matches(&Goal::new().edge_orientation(0)));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+pub struct Goal {
+    edge_permutation: u16,
+    edge_orientation: u16,
+    corner_permutation: u8,
+    corner_orientation: u8,
+}
+
+impl Goal {
+    /// Returns a goal met by every cube.
+    pub fn new() -> Goal {
+        Goal::default()
+    }
+
+    /// Returns the goal of a solved cube.
+    pub fn solved() -> Goal {
+        Goal {
+            edge_permutation: u16::MAX >> (16 - NUM_EDGES),
+            edge_orientation: u16::MAX >> (16 - NUM_EDGES),
+            corner_permutation: u8::MAX,
+            corner_orientation: u8::MAX,
+        }
+    }
+
+    /// Returns the goal of the four edges around the white face on D.
+    pub fn cross() -> Goal {
+        (8..12).fold(Goal::new(), Goal::edge)
+    }
+
+    /// Returns the goal of every edge being oriented, wherever it is.
+    pub fn edges_oriented() -> Goal {
+        (0..NUM_EDGES as usize).fold(Goal::new(), Goal::edge_orientation)
+    }
+
+    /// Returns the goal of every corner being oriented, wherever it is.
+    pub fn corners_oriented() -> Goal {
+        (0..NUM_CORNERS as usize).fold(Goal::new(), Goal::corner_orientation)
+    }
+
+    /// Adds the permutation and orientation of edge `cubie`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cubie` is not an edge, below 12.
+    pub fn edge(self, cubie: usize) -> Goal {
+        self.edge_permutation(cubie).edge_orientation(cubie)
+    }
+
+    /// Adds the permutation of edge `cubie`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cubie` is not an edge, below 12.
+    pub fn edge_permutation(mut self, cubie: usize) -> Goal {
+        self.edge_permutation |= edge_bit(cubie);
+        self
+    }
+
+    /// Adds the orientation of edge `cubie`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cubie` is not an edge, below 12.
+    pub fn edge_orientation(mut self, cubie: usize) -> Goal {
+        self.edge_orientation |= edge_bit(cubie);
+        self
+    }
+
+    /// Adds the permutation and orientation of corner `cubie`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cubie` is not a corner, below 8.
+    pub fn corner(self, cubie: usize) -> Goal {
+        self.corner_permutation(cubie).corner_orientation(cubie)
+    }
+
+    /// Adds the permutation of corner `cubie`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cubie` is not a corner, below 8.
+    pub fn corner_permutation(mut self, cubie: usize) -> Goal {
+        self.corner_permutation |= corner_bit(cubie);
+        self
+    }
+
+    /// Adds the orientation of corner `cubie`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cubie` is not a corner, below 8.
+    pub fn corner_orientation(mut self, cubie: usize) -> Goal {
+        self.corner_orientation |= corner_bit(cubie);
+        self
+    }
+
+    /// Returns the goal requiring everything either goal requires.
+    pub fn union(self, other: Goal) -> Goal {
+        Goal {
+            edge_permutation: self.edge_permutation | other.edge_permutation,
+            edge_orientation: self.edge_orientation | other.edge_orientation,
+            corner_permutation: self.corner_permutation | other.corner_permutation,
+            corner_orientation: self.corner_orientation | other.corner_orientation,
+        }
+    }
+
+    /// Number of edges and corners, in that order, of a cube with the given cubies in every
+    /// cubicle and orientation of every cubicle which fail the goal.
+    pub(crate) fn unsolved(
+        &self,
+        edge_orientation: &[u8],
+        corner_orientation: &[u8],
+        edge_permutation: &[u8],
+        corner_permutation: &[u8],
+    ) -> (usize, usize) {
+        (
+            unsolved(
+                self.edge_permutation.into(),
+                self.edge_orientation.into(),
+                edge_orientation,
+                edge_permutation,
+            ),
+            unsolved(
+                self.corner_permutation.into(),
+                self.corner_orientation.into(),
+                corner_orientation,
+                corner_permutation,
+            ),
+        )
+    }
+}
+
+fn edge_bit(cubie: usize) -> u16 {
+    assert!(cubie < NUM_EDGES as usize, "edge {} out of range", cubie);
+    1 << cubie
+}
+
+fn corner_bit(cubie: usize) -> u8 {
+    assert!(
+        cubie < NUM_CORNERS as usize,
+        "corner {} out of range",
+        cubie
+    );
+    1 << cubie
+}
+
+fn unsolved(
+    permutation_mask: u32,
+    orientation_mask: u32,
+    orientation: &[u8],
+    permutation: &[u8],
+) -> usize {
+    permutation
+        .iter()
+        .zip(orientation)
+        .enumerate()
+        .filter(|&(cubicle, (&cubie, &o))| {
+            let bit = 1 << cubie;
+            (permutation_mask & bit != 0 && cubie as usize != cubicle)
+                || (orientation_mask & bit != 0 && o != 0)
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Goal;
+    use crate::{Cube, Turn};
+
+    #[test]
+    fn matches_test() {
+        let mut cube = Cube::cube_htm();
+        assert!(cube.matches(&Goal::solved()));
+        assert!(cube.matches(&Goal::new()));
+
+        cube.turn(Turn::R as u8).unwrap();
+        assert!(!cube.matches(&Goal::solved()));
+        assert!(!cube.matches(&Goal::edges_oriented()));
+        assert!(!cube.matches(&Goal::corners_oriented()));
+        // R leaves the left half of the cube alone
+        let left = [3, 4, 7, 11]
+            .iter()
+            .fold(Goal::new(), |goal, &edge| goal.edge(edge));
+        let left = [0, 3, 4, 7]
+            .iter()
+            .fold(left, |goal, &corner| goal.corner(corner));
+        assert!(cube.matches(&left));
+        assert!(!cube.matches(&left.edge_permutation(1)));
+
+        // U keeps every cubie oriented and only moves the top layer
+        let mut cube = Cube::cube_htm();
+        cube.turn(Turn::U as u8).unwrap();
+        let oriented = Goal::edges_oriented().union(Goal::corners_oriented());
+        assert!(cube.matches(&Goal::cross().union(oriented)));
+        assert!(!cube.matches(&oriented.corner_permutation(0)));
+    }
+
+    #[test]
+    #[should_panic(expected = "edge 12 out of range")]
+    fn out_of_range_test() {
+        Goal::new().edge(12);
+    }
+}
@@ -0,0 +1,220 @@
+use rand::Rng;
+
+use crate::{errors::CubeError, moves::Turn, Cube};
+
+/// Wraps a [`Cube`] and records every turn applied to it, so turns can be undone and redone.
+///
+/// Undoing a turn applies its inverse, see `Turn::inverse`, rather than replaying the
+/// history from the start. Applying a new turn after undoing discards the undone turns.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CubeHistory {
+    cube: Cube,
+    /// turns recorded, the first `position` of which are applied to `cube`
+    turns: Vec<Turn>,
+    position: usize,
+}
+
+impl CubeHistory {
+    /// Starts an empty history from `cube`.
+    pub fn new(cube: Cube) -> CubeHistory {
+        CubeHistory {
+            cube,
+            turns: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Returns the cube in its current state.
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Returns the cube, dropping the history.
+    pub fn into_cube(self) -> Cube {
+        self.cube
+    }
+
+    /// Returns the turns applied to the cube, oldest first.
+    pub fn history(&self) -> &[Turn] {
+        &self.turns[..self.position]
+    }
+
+    /// Returns the turns that were undone and can be redone, next to redo first.
+    pub fn undone(&self) -> &[Turn] {
+        &self.turns[self.position..]
+    }
+
+    /// Performs the specified turn on the cube and records it, see `Cube::turn`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, CubeHistory, Turn};
+    ///
+    /// let mut history = CubeHistory::new(Cube::cube_htm());
+    /// history.turn(Turn::R as u8).unwrap();
+    /// history.turn(Turn::U as u8).unwrap();
+    ///
+    /// assert_eq!(history.undo(), Some(Turn::U));
+    /// assert_eq!(history.history(), &[Turn::R]);
+    ///
+    /// history.turn(Turn::F as u8).unwrap();
+    /// assert_eq!(history.redo(), None);
+    /// assert_eq!(history.history(), &[Turn::R, Turn::F]);
+    /// ```
+    pub fn turn(&mut self, twist: u8) -> Result<(), CubeError> {
+        self.cube.turn(twist)?;
+        self.record(Turn::from_u8(twist)?);
+        Ok(())
+    }
+
+    /// Scrambles the cube with `num_turns` random turns and records them.
+    pub fn scramble(&mut self, num_turns: u32) {
+        self.scramble_with_rng(num_turns, &mut rand::thread_rng());
+    }
+
+    /// Scrambles the cube with turns sampled from `rng` and records them.
+    pub fn scramble_with_rng<R: Rng + ?Sized>(&mut self, num_turns: u32, rng: &mut R) {
+        for turn in self.cube.scramble_with_rng(num_turns, rng) {
+            self.record(turn);
+        }
+    }
+
+    fn record(&mut self, turn: Turn) {
+        self.turns.truncate(self.position);
+        self.turns.push(turn);
+        self.position += 1;
+    }
+
+    /// Undoes the last applied turn and returns it, or returns `None` if there is none.
+    pub fn undo(&mut self) -> Option<Turn> {
+        let turn = *self.history().last()?;
+        self.position -= 1;
+        self.cube.turn(turn.inverse() as u8).unwrap();
+        Some(turn)
+    }
+
+    /// Redoes the last undone turn and returns it, or returns `None` if there is none.
+    pub fn redo(&mut self) -> Option<Turn> {
+        let turn = *self.undone().first()?;
+        self.position += 1;
+        self.cube.turn(turn as u8).unwrap();
+        Some(turn)
+    }
+
+    /// Undoes or redoes turns until exactly `position` turns of the history are applied.
+    ///
+    /// Positions up to the number of applied turns plus the number of undone turns are valid.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::{Cube, CubeError, CubeHistory};
+    ///
+    /// let mut history = CubeHistory::new(Cube::cube_htm());
+    /// history.scramble(10);
+    ///
+    /// history.reset_to(0).unwrap();
+    /// assert!(history.cube().solved());
+    /// assert_eq!(history.undone().len(), 10);
+    ///
+    /// history.reset_to(10).unwrap();
+    /// assert_eq!(history.history().len(), 10);
+    /// assert_eq!(history.reset_to(11), Err(CubeError::InvalidHistoryPosition(10, 11)));
+    /// ```
+    pub fn reset_to(&mut self, position: usize) -> Result<(), CubeError> {
+        if position > self.turns.len() {
+            return Err(CubeError::InvalidHistoryPosition(
+                self.turns.len(),
+                position,
+            ));
+        }
+        while self.position > position {
+            self.undo();
+        }
+        while self.position < position {
+            self.redo();
+        }
+        Ok(())
+    }
+
+    /// Forgets every recorded turn, keeping the cube in its current state.
+    pub fn clear(&mut self) {
+        self.turns.clear();
+        self.position = 0;
+    }
+}
+
+impl From<Cube> for CubeHistory {
+    fn from(cube: Cube) -> CubeHistory {
+        CubeHistory::new(cube)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CubeHistory;
+    use crate::errors::CubeError;
+    use crate::{Cube, MetricKind, Turn};
+
+    #[test]
+    fn undo_redo_test() {
+        let mut history = CubeHistory::new(Cube::cube_qtm());
+        history.scramble(50);
+        let scrambled = *history.cube();
+
+        while history.undo().is_some() {}
+        assert!(history.cube().solved());
+        assert_eq!(history.undone().len(), 50);
+
+        while history.redo().is_some() {}
+        assert_eq!(*history.cube(), scrambled);
+        assert_eq!(history.history().len(), 50);
+    }
+
+    #[test]
+    fn history_matches_cube_test() {
+        let mut history = CubeHistory::new(Cube::cube_htm());
+        history.scramble(20);
+        history.turn(Turn::D2 as u8).unwrap();
+
+        let mut cube = Cube::cube_htm();
+        for &turn in history.history() {
+            cube.turn(turn as u8).unwrap();
+        }
+        assert_eq!(cube, *history.cube());
+        assert_eq!(history.history().last(), Some(&Turn::D2));
+    }
+
+    #[test]
+    fn reset_to_test() {
+        let mut history = CubeHistory::new(Cube::cube_htm());
+        history.turn(Turn::R as u8).unwrap();
+        let after_one = *history.cube();
+        history.turn(Turn::U as u8).unwrap();
+        history.turn(Turn::F_ as u8).unwrap();
+
+        history.reset_to(1).unwrap();
+        assert_eq!(*history.cube(), after_one);
+        assert_eq!(history.undone(), &[Turn::U, Turn::F_]);
+
+        history.reset_to(3).unwrap();
+        history.reset_to(1).unwrap();
+        history.turn(Turn::L as u8).unwrap();
+        assert_eq!(history.history(), &[Turn::R, Turn::L]);
+        assert_eq!(
+            history.reset_to(3),
+            Err(CubeError::InvalidHistoryPosition(2, 3))
+        );
+    }
+
+    #[test]
+    fn invalid_turn_is_not_recorded_test() {
+        let mut history = CubeHistory::new(Cube::new(MetricKind::QuarterTurnMetric));
+        assert_eq!(
+            history.turn(Turn::R2 as u8),
+            Err(CubeError::InvalidTurn(Turn::R2 as u8, 12))
+        );
+        assert!(history.history().is_empty());
+    }
+}
@@ -0,0 +1,543 @@
+//! Recognition of the OLL and PLL cases of the last layer.
+//!
+//! A [`Database`] holds the algorithm of every case, parsed from a text file with one case
+//! per line, `OLL <name>: <moves>` or `PLL <name>: <moves>`. Blank lines and lines starting
+//! with `#` are skipped. [`Database::builtin`] holds the 57 OLL and 21 PLL cases with common
+//! algorithms, and further databases can be loaded and merged into it.
+//!
+//! Moves are read by [`parse_algorithm`], which accepts the notation algorithms are usually
+//! written in, with the yellow face on U and the green face on F. A case is recognised by
+//! trying the algorithms of the database after each adjusting U turn (AUF) until one of them
+//! orients the last layer, for OLL, or solves the cube up to a last U turn, for PLL.
+
+use std::{io, path::Path};
+
+use crate::{
+    beginner::{first_two_layers_solved, last_layer_oriented},
+    errors::CubeError,
+    moves::{MetricKind, Turn},
+    Cube,
+};
+
+/// The bundled database, see [`Database::builtin`].
+const BUILTIN: &str = include_str!("last_layer.txt");
+
+/// Adjusting U turns, tried in this order.
+const AUFS: [Option<Turn>; 4] = [None, Some(Turn::U), Some(Turn::U_), Some(Turn::U2)];
+
+/// Faces in the order of the turns L, R, F, B, U and D.
+const L: usize = 0;
+const R: usize = 1;
+const F: usize = 2;
+const B: usize = 3;
+const U: usize = 4;
+const D: usize = 5;
+
+/// Rotations of the whole cube a quarter turn like R, U and F, as the position each position
+/// takes its face from.
+const ROTATIONS: [[usize; 6]; 3] = [
+    [L, R, D, U, F, B], // x
+    [F, B, R, L, U, D], // y
+    [D, U, F, B, L, R], // z
+];
+const X: usize = 0;
+const Y: usize = 1;
+const Z: usize = 2;
+
+/// Face turns and rotation making up a clockwise move, as the face turned with its number of
+/// clockwise quarter turns, followed by the rotated axis with its number of quarter turns.
+type Decomposition = (&'static [(usize, u8)], Option<(usize, u8)>);
+
+fn decompose(base: &str) -> Option<Decomposition> {
+    let decomposition: Decomposition = match base {
+        "L" => (&[(L, 1)], None),
+        "R" => (&[(R, 1)], None),
+        "F" => (&[(F, 1)], None),
+        "B" => (&[(B, 1)], None),
+        "U" => (&[(U, 1)], None),
+        "D" => (&[(D, 1)], None),
+        // a wide turn is a turn of the opposite face and a rotation of the whole cube
+        "r" | "Rw" => (&[(L, 1)], Some((X, 1))),
+        "l" | "Lw" => (&[(R, 1)], Some((X, 3))),
+        "f" | "Fw" => (&[(B, 1)], Some((Z, 1))),
+        "b" | "Bw" => (&[(F, 1)], Some((Z, 3))),
+        "u" | "Uw" => (&[(D, 1)], Some((Y, 1))),
+        "d" | "Dw" => (&[(U, 1)], Some((Y, 3))),
+        // a slice turn is a turn of both faces around it and a rotation of the whole cube
+        "M" => (&[(R, 1), (L, 3)], Some((X, 3))),
+        "E" => (&[(U, 1), (D, 3)], Some((Y, 3))),
+        "S" => (&[(F, 3), (B, 1)], Some((Z, 1))),
+        "x" => (&[], Some((X, 1))),
+        "y" => (&[], Some((Y, 1))),
+        "z" => (&[], Some((Z, 1))),
+        _ => return None,
+    };
+    Some(decomposition)
+}
+
+/// Parses an algorithm into face turns.
+///
+/// Besides the face turns read by `Turn::parse_sequence`, the algorithm may contain wide
+/// turns (`r` or `Rw`), slice turns (`M`, `E` and `S`) and rotations of the whole cube (`x`,
+/// `y` and `z`), each followed by `'`, `2` or `2'`. Parentheses are ignored. Centers never
+/// move, so these are written as turns of the faces seen after the rotations they imply.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::last_layer::parse_algorithm;
+/// use rubikscube::Turn;
+///
+/// let turns = parse_algorithm("(r U R' U') M2").unwrap();
+/// assert_eq!(turns, Turn::parse_sequence("L F R' F' R2 L2").unwrap());
+/// ```
+pub fn parse_algorithm(moves: &str) -> Result<Vec<Turn>, CubeError> {
+    // face seen in each position, changed by rotations
+    let mut faces = [L, R, F, B, U, D];
+    let mut turns = Vec::new();
+    for token in moves.split_whitespace() {
+        let stripped = token.trim_matches(|c| c == '(' || c == ')');
+        if stripped.is_empty() {
+            continue;
+        }
+        let (base, amount) = if let Some(base) = stripped.strip_suffix("2'") {
+            (base, 2)
+        } else if let Some(base) = stripped.strip_suffix('2') {
+            (base, 2)
+        } else if let Some(base) = stripped.strip_suffix('\'') {
+            (base, 3)
+        } else {
+            (stripped, 1)
+        };
+        let (face_turns, rotation) =
+            decompose(base).ok_or_else(|| CubeError::InvalidMove(token.to_string()))?;
+
+        for &(position, quarters) in face_turns {
+            let offset = match quarters * amount % 4 {
+                1 => 0,
+                2 => 12,
+                _ => 6,
+            };
+            turns.push(Turn::from_u8(offset + faces[position] as u8)?);
+        }
+        if let Some((axis, quarters)) = rotation {
+            for _ in 0..quarters * amount % 4 {
+                faces = ROTATIONS[axis].map(|position| faces[position]);
+            }
+        }
+    }
+    Ok(turns)
+}
+
+/// Step of the last layer a case belongs to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Step {
+    /// Orientation of the last layer
+    Oll,
+    /// Permutation of the last layer
+    Pll,
+}
+
+/// A case and its algorithm.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Case {
+    pub step: Step,
+    pub name: String,
+    /// Algorithm as written in the database.
+    pub notation: String,
+    /// The algorithm as face turns, see [`parse_algorithm`].
+    pub turns: Vec<Turn>,
+}
+
+/// A recognised case and the turns solving it.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Recognition {
+    pub case: Case,
+    /// U turn before the algorithm.
+    pub auf: Option<Turn>,
+    /// U turn after the algorithm, only needed for PLL cases.
+    pub final_auf: Option<Turn>,
+}
+
+impl Recognition {
+    /// Returns the adjusting turns and the algorithm, one after the other.
+    pub fn turns(&self) -> Vec<Turn> {
+        self.auf
+            .into_iter()
+            .chain(self.case.turns.iter().copied())
+            .chain(self.final_auf)
+            .collect()
+    }
+}
+
+/// Algorithms of last layer cases.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Database {
+    cases: Vec<Case>,
+}
+
+/// Returns a copy of `cube` in the half turn metric after `turns`.
+fn after(cube: &Cube, turns: impl IntoIterator<Item = Turn>) -> Cube {
+    let [eo, co, ep, cp] = cube.cubicles();
+    let mut cube = Cube::from_cubicles(eo, co, ep, cp, MetricKind::HalfTurnMetric);
+    for turn in turns {
+        cube.turn(turn as u8).unwrap();
+    }
+    cube
+}
+
+impl Database {
+    /// Returns the bundled database of the 57 OLL and the 21 PLL cases.
+    pub fn builtin() -> Database {
+        Database::parse(BUILTIN).unwrap()
+    }
+
+    /// Parses a database, see the [module documentation](self) for the format.
+    ///
+    /// Returns `CubeError::InvalidAlgorithmLine` with the line number, counted from 1, of a
+    /// line that is not a case, or the error of `parse_algorithm` for invalid moves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::last_layer::{Database, Step};
+    ///
+    /// let database = Database::parse("# Sune\nOLL 27: R U R' U R U2 R'").unwrap();
+    /// assert_eq!(database.cases()[0].step, Step::Oll);
+    /// assert_eq!(database.cases()[0].name, "27");
+    /// ```
+    pub fn parse(text: &str) -> Result<Database, CubeError> {
+        let mut cases = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || CubeError::InvalidAlgorithmLine(index + 1);
+            let (case, notation) = line.split_once(':').ok_or_else(invalid)?;
+            let (step, name) = case.trim().split_once(' ').ok_or_else(invalid)?;
+            let step = match step {
+                "OLL" => Step::Oll,
+                "PLL" => Step::Pll,
+                _ => return Err(invalid()),
+            };
+            let (name, notation) = (name.trim(), notation.trim());
+            if name.is_empty() {
+                return Err(invalid());
+            }
+            cases.push(Case {
+                step,
+                name: name.to_string(),
+                notation: notation.to_string(),
+                turns: parse_algorithm(notation)?,
+            });
+        }
+        Ok(Database { cases })
+    }
+
+    /// Reads and parses a database file, invalid databases giving an error of kind
+    /// `io::ErrorKind::InvalidData`.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Database> {
+        let text = std::fs::read_to_string(path)?;
+        Database::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Adds the cases of `other`, replacing the cases with the same step and name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::last_layer::Database;
+    ///
+    /// let mut database = Database::builtin();
+    /// database.extend(Database::parse("PLL H: R2 U2 R U2 R2 U2 R2 U2 R U2 R2").unwrap());
+    /// assert_eq!(database.cases().len(), 78);
+    /// ```
+    pub fn extend(&mut self, other: Database) {
+        for case in other.cases {
+            match self
+                .cases
+                .iter_mut()
+                .find(|known| known.step == case.step && known.name == case.name)
+            {
+                Some(known) => *known = case,
+                None => self.cases.push(case),
+            }
+        }
+    }
+
+    pub fn cases(&self) -> &[Case] {
+        &self.cases
+    }
+
+    /// Recognises the case of a cube with its first two layers solved, the yellow face on U.
+    ///
+    /// The case is an OLL case until the last layer is oriented, then a PLL case. Returns
+    /// `None` if the first two layers are not solved, if the cube is solved up to a U turn or
+    /// if the case is not in the database.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::last_layer::{Database, Step};
+    /// use rubikscube::{Cube, Turn};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// for turn in Turn::parse_sequence("R U2 R' U' R U' R' U2").unwrap() {
+    ///     cube.turn(turn as u8).unwrap();
+    /// }
+    ///
+    /// let recognition = Database::builtin().recognise(&cube).unwrap();
+    /// assert_eq!((recognition.case.step, recognition.case.name.as_str()), (Step::Oll, "27"));
+    /// assert_eq!(recognition.auf, Some(Turn::U2));
+    /// ```
+    pub fn recognise(&self, cube: &Cube) -> Option<Recognition> {
+        if !first_two_layers_solved(cube) {
+            return None;
+        }
+        let step = if last_layer_oriented(cube) {
+            if AUFS.iter().any(|&auf| after(cube, auf).solved()) {
+                return None;
+            }
+            Step::Pll
+        } else {
+            Step::Oll
+        };
+
+        for case in self.cases.iter().filter(|case| case.step == step) {
+            for auf in AUFS {
+                let solved = after(cube, auf.into_iter().chain(case.turns.iter().copied()));
+                let final_auf = match step {
+                    Step::Oll if last_layer_oriented(&solved) => Some(None),
+                    Step::Oll => None,
+                    Step::Pll => AUFS
+                        .iter()
+                        .find(|&&final_auf| after(&solved, final_auf).solved())
+                        .copied(),
+                };
+                if let Some(final_auf) = final_auf {
+                    return Some(Recognition {
+                        case: case.clone(),
+                        auf,
+                        final_auf,
+                    });
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{after, parse_algorithm, Database, Step, AUFS};
+    use crate::beginner::last_layer_oriented;
+    use crate::{errors::CubeError, Cube, Faces, MetricKind, Turn};
+
+    fn inverse(turns: &[Turn]) -> Vec<Turn> {
+        turns.iter().rev().map(|turn| turn.inverse()).collect()
+    }
+
+    #[test]
+    fn builtin_test() {
+        let database = Database::builtin();
+        let count = |step| database.cases().iter().filter(|c| c.step == step).count();
+        assert_eq!((count(Step::Oll), count(Step::Pll)), (57, 21));
+    }
+
+    #[test]
+    fn every_case_test() {
+        let database = Database::builtin();
+        for case in database.cases() {
+            for auf in AUFS {
+                // the inverse of the algorithm gives the case, turned by a U turn
+                let cube = after(
+                    &Cube::cube_htm(),
+                    inverse(&case.turns).into_iter().chain(auf),
+                );
+                let recognition = database.recognise(&cube).unwrap();
+                assert_eq!(recognition.case, *case);
+
+                let solved = after(&cube, recognition.turns());
+                match case.step {
+                    Step::Oll => assert!(last_layer_oriented(&solved)),
+                    Step::Pll => assert!(solved.solved()),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn oll_numbering_test() {
+        // OLL cases without any yellow edge on U, with a yellow cross and with yellow corners
+        let groups = [
+            (vec![1, 2, 3, 4, 17, 18, 19, 20], 0),
+            ((21..=27).collect(), 4),
+        ];
+        let database = Database::builtin();
+        let yellow_on_u = |name: u8, positions: &[(usize, usize)]| {
+            let case = database
+                .cases()
+                .iter()
+                .find(|c| c.name == name.to_string())
+                .unwrap();
+            let face =
+                after(&Cube::cube_htm(), inverse(&case.turns)).faces()[Faces::Yellow as usize];
+            positions
+                .iter()
+                .filter(|&&(row, column)| face[row][column] == Faces::Yellow)
+                .count()
+        };
+        for (names, edges) in groups {
+            for name in names {
+                assert_eq!(yellow_on_u(name, &[(0, 1), (1, 0), (1, 2), (2, 1)]), edges);
+            }
+        }
+        for name in [20, 28, 57] {
+            assert_eq!(yellow_on_u(name, &[(0, 0), (0, 2), (2, 0), (2, 2)]), 4);
+        }
+    }
+
+    #[test]
+    fn every_orientation_test() {
+        let database = Database::builtin();
+        let [_, _, ep, cp] = Cube::cube_htm().cubicles().map(<[u8]>::to_vec);
+        let mut cases = std::collections::HashSet::new();
+        for twists in 0..27 {
+            for flips in 0..8 {
+                let mut co = [0; 8];
+                let mut eo = [0; 12];
+                for cubicle in 0..3 {
+                    co[cubicle] = twists / 3u8.pow(cubicle as u32) % 3;
+                    eo[cubicle] = flips >> cubicle & 1;
+                }
+                co[3] = (6 - co[..3].iter().sum::<u8>()) % 3;
+                eo[3] = eo[..3].iter().sum::<u8>() % 2;
+                let cube = Cube::from_cubicles(&eo, &co, &ep, &cp, MetricKind::HalfTurnMetric);
+                if last_layer_oriented(&cube) {
+                    continue;
+                }
+                cases.insert(database.recognise(&cube).unwrap().case.name);
+            }
+        }
+        assert_eq!(cases.len(), 57);
+    }
+
+    #[test]
+    fn every_permutation_test() {
+        let database = Database::builtin();
+        let mut cube = Cube::cube_htm();
+        let mut cases = std::collections::HashSet::new();
+        // states of the permutation group of the last layer, found breadth first
+        let generators: Vec<Vec<Turn>> = ["Ua", "Aa", "T"]
+            .iter()
+            .map(|name| {
+                let case = database.cases().iter().find(|c| c.name == *name).unwrap();
+                case.turns.clone()
+            })
+            .chain(std::iter::once(vec![Turn::U]))
+            .collect();
+        let mut seen = std::collections::HashSet::new();
+        let mut frontier = vec![cube];
+        seen.insert(cube);
+        while let Some(next) = frontier.pop() {
+            cube = next;
+            match database.recognise(&cube) {
+                Some(recognition) => {
+                    assert_eq!(recognition.case.step, Step::Pll);
+                    assert!(after(&cube, recognition.turns()).solved());
+                    cases.insert(recognition.case.name);
+                }
+                None => assert!(AUFS.iter().any(|&auf| after(&cube, auf).solved())),
+            }
+            for generator in &generators {
+                let neighbour = after(&cube, generator.iter().copied());
+                if seen.insert(neighbour) {
+                    frontier.push(neighbour);
+                }
+            }
+        }
+        // corner and edge permutations of equal parity
+        assert_eq!(seen.len(), 24 * 24 / 2);
+        assert_eq!(cases.len(), 21);
+    }
+
+    #[test]
+    fn parse_algorithm_test() {
+        let parse = |moves| parse_algorithm(moves).unwrap();
+        assert_eq!(parse("x y' z2 (x')"), []);
+        assert_eq!(parse("M2"), parse("R2 L2"));
+        assert_eq!(parse("E"), parse("U D'"));
+        assert_eq!(parse("S'"), parse("F B'"));
+        assert_eq!(parse("Rw U r'"), parse("L F L'"));
+        assert_eq!(parse("y R U R' y'"), parse("B U B'"));
+        assert_eq!(parse("R2'"), [Turn::R2]);
+        assert_eq!(
+            parse_algorithm("R Q"),
+            Err(CubeError::InvalidMove("Q".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(
+            Database::parse("OLL 1: R\n\n# comment\nPLL H R2"),
+            Err(CubeError::InvalidAlgorithmLine(4))
+        );
+        assert_eq!(
+            Database::parse("CLL 1: R"),
+            Err(CubeError::InvalidAlgorithmLine(1))
+        );
+        assert_eq!(
+            Database::parse("OLL: R"),
+            Err(CubeError::InvalidAlgorithmLine(1))
+        );
+        assert_eq!(
+            Database::parse("PLL T: R X"),
+            Err(CubeError::InvalidMove("X".to_string()))
+        );
+
+        let mut database =
+            Database::parse("OLL 45: F R U R' U' F'\nPLL H: M2 U M2 U2 M2 U M2").unwrap();
+        database.extend(
+            Database::parse(
+                "PLL H: R2 U2 R U2 R2 U2 R2 U2 R U2 R2\nPLL Z: M' U M2 U M2 U M' U2 M2",
+            )
+            .unwrap(),
+        );
+        let names: Vec<_> = database.cases().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["45", "H", "Z"]);
+        assert_eq!(database.cases()[1].turns.len(), 11);
+    }
+
+    #[test]
+    fn load_test() {
+        let path = std::env::temp_dir().join(format!("last_layer_{}.txt", std::process::id()));
+        std::fs::write(&path, "PLL T: R U R' U' R' F R2 U' R' U' R U R' F'\n").unwrap();
+        let database = Database::load(&path).unwrap();
+        assert_eq!(database.cases()[0].name, "T");
+
+        std::fs::write(&path, "PLL T R U R'\n").unwrap();
+        let err = Database::load(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn unrecognised_test() {
+        let database = Database::builtin();
+        let mut cube = Cube::cube_htm();
+        assert_eq!(database.recognise(&cube), None);
+        cube.turn(Turn::U as u8).unwrap();
+        assert_eq!(database.recognise(&cube), None);
+        cube.turn(Turn::R as u8).unwrap();
+        assert_eq!(database.recognise(&cube), None);
+
+        let sune = Database::parse("OLL 27: R U R' U R U2 R'").unwrap();
+        let cube = after(
+            &Cube::cube_htm(),
+            parse_algorithm("F R U R' U' F'").unwrap(),
+        );
+        assert_eq!(sune.recognise(&cube), None);
+    }
+}
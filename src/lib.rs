@@ -9,17 +9,44 @@
 //!
 //! [The Fundamental Theorem of Cubology]: http://www.sfu.ca/~jtmulhol/math302/puzzles-rc-cubology.html
 
+mod batch;
+pub mod beginner;
+pub mod bfs;
+pub mod cfop;
 mod cube;
 mod cubies;
+pub mod dataset;
+pub mod encoding;
+pub mod env;
 mod errors;
+mod goal;
+mod history;
+pub mod last_layer;
 mod moves;
+pub mod nxn;
 mod orientation;
 mod permutation;
+pub mod pocket;
+#[cfg(feature = "python")]
+mod python;
+pub mod render;
+pub mod solver;
+pub mod subgroup;
+pub mod supercube;
+pub mod symmetry;
+pub mod thistlethwaite;
+mod turn_table;
+#[cfg(feature = "wasm")]
+mod wasm;
 
 extern crate strum;
 #[macro_use]
 extern crate strum_macros;
 
-pub use cube::Cube;
+pub use batch::CubeBatch;
+pub use cube::{Cube, Cycle, Solvability};
+pub use cubies::{Faces, REPRESENTATION_SIZE};
 pub use errors::CubeError;
+pub use goal::Goal;
+pub use history::CubeHistory;
 pub use moves::{MetricKind, Turn};
@@ -0,0 +1,399 @@
+//! Command line interface to the `rubikscube` crate.
+//!
+//! Cubes are read and written as facelet strings, see `Cube::from_facelets`, and moves in
+//! standard notation such as `"R U R' U'"`.
+//!
+//! The process exits with
+//!
+//! * `0` on success,
+//! * `1` if `check` finds the cube unsolvable, `solve` finds no solution or `analyse` is
+//!   given a solve that does not solve the cube,
+//! * `2` if the arguments can not be parsed,
+//! * `3` if a move is invalid,
+//! * `4` if a cube description is invalid,
+//! * `5` on any other error, such as failing to write a file.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use rand::{rngs::StdRng, SeedableRng};
+
+use rubikscube::dataset::{self, DatasetConfig, StateFormat as DatasetFormat};
+use rubikscube::{
+    beginner, cfop, render, solver, thistlethwaite, Cube, CubeError, MetricKind, Turn,
+};
+
+#[derive(Parser)]
+#[command(
+    name = "rubikscube",
+    about = "Scramble, inspect and solve 3x3x3 Rubik's cubes"
+)]
+struct Cli {
+    /// Turn metric used for scrambles, moves and solutions
+    #[arg(long, value_enum, global = true, default_value_t = Metric::Htm)]
+    metric: Metric,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print a random scramble and the facelets of the scrambled cube
+    Scramble {
+        /// Number of random turns
+        #[arg(long, default_value_t = 25)]
+        length: u32,
+        /// Seed for a reproducible scramble
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Apply moves to a cube and print the resulting state
+    Apply {
+        /// Moves to apply, such as "R U R' U'"
+        moves: String,
+        /// Facelets of the starting cube, solved by default
+        #[arg(long)]
+        from: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StateFormat::Facelets)]
+        format: StateFormat,
+    },
+    /// Draw a cube as an unfolded net
+    Render {
+        /// Facelets of the cube, solved by default
+        facelets: Option<String>,
+        /// Moves to apply before rendering
+        #[arg(long)]
+        moves: Option<String>,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = RenderFormat::Ansi)]
+        format: RenderFormat,
+    },
+    /// Report why a cube given by its facelets is or is not solvable
+    Check {
+        /// Facelets of the cube
+        facelets: String,
+    },
+    /// Find a solution for a cube given by its facelets
+    Solve {
+        /// Facelets of the cube
+        facelets: String,
+        /// Longest solution searched for by the optimal solver
+        #[arg(long, default_value_t = 7)]
+        max_depth: usize,
+        /// Solving method
+        #[arg(long, value_enum, default_value_t = SolveMethod::Optimal)]
+        method: SolveMethod,
+    },
+    /// Split a CFOP solve into cross, F2L pairs, OLL and PLL with the turns of each
+    Analyse {
+        /// Moves of the solve, such as "D R' F U2 ..."
+        solve: String,
+        /// Moves scrambling the cube before the solve
+        #[arg(long)]
+        scramble: String,
+    },
+    /// Write a shuffled NPY file of scrambled states labelled with their scramble depth
+    Dataset {
+        /// Path of the NPY file to write
+        #[arg(long)]
+        output: PathBuf,
+        /// Number of samples
+        #[arg(long, default_value_t = 10_000)]
+        samples: usize,
+        /// Largest scramble depth, depths are spread evenly from 0
+        #[arg(long, default_value_t = 20)]
+        max_depth: u32,
+        /// Seed of the generator, the same seed gives the same file
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// Encoding of the states
+        #[arg(long, value_enum, default_value_t = DatasetEncoding::Representation)]
+        encoding: DatasetEncoding,
+        /// Also label samples with their exact distance, searching solutions up to this length
+        #[arg(long)]
+        solver_depth: Option<usize>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Metric {
+    /// Half turn metric, with 18 turns
+    Htm,
+    /// Quarter turn metric, with 12 turns
+    Qtm,
+}
+
+impl From<Metric> for MetricKind {
+    fn from(metric: Metric) -> MetricKind {
+        match metric {
+            Metric::Htm => MetricKind::HalfTurnMetric,
+            Metric::Qtm => MetricKind::QuarterTurnMetric,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum StateFormat {
+    /// 54 facelet colors
+    Facelets,
+    /// Unfolded net of facelet colors
+    Net,
+    /// Orientations and permutations of edges and corners, indexed by cubie
+    State,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DatasetEncoding {
+    /// 480 one-hot bytes per state
+    Representation,
+    /// 20 cubie state indices per state
+    Packed,
+}
+
+impl From<DatasetEncoding> for DatasetFormat {
+    fn from(encoding: DatasetEncoding) -> DatasetFormat {
+        match encoding {
+            DatasetEncoding::Representation => DatasetFormat::Representation,
+            DatasetEncoding::Packed => DatasetFormat::Packed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum SolveMethod {
+    /// Shortest solution, only practical for cubes a few turns from solved
+    Optimal,
+    /// Thistlethwaite's algorithm, printing the turns of each of its four phases on a line
+    Thistlethwaite,
+    /// Layer by layer beginner method, printing the turns of each of its stages on a line
+    Beginner,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RenderFormat {
+    /// Colored terminal output
+    Ansi,
+    /// SVG image
+    Svg,
+}
+
+/// Exit code for a failed check or search.
+const EXIT_UNSOLVED: u8 = 1;
+/// Exit code for errors not caused by the cube or the moves given.
+const EXIT_OTHER: u8 = 5;
+
+fn exit_code(err: &CubeError) -> u8 {
+    match err {
+        CubeError::InvalidMove(_) | CubeError::InvalidTurn(..) | CubeError::UnsupportedTurn(_) => 3,
+        CubeError::InvalidFaceOrder(..)
+        | CubeError::InvalidFaceletColor
+        | CubeError::InvalidFaceletCount(_)
+        | CubeError::InvalidCorner(_)
+        | CubeError::InvalidEdge(_)
+        | CubeError::InvalidState
+        | CubeError::InvalidStateLength(..)
+        | CubeError::CubieOutOfRange(..)
+        | CubeError::DuplicateCubie(_)
+        | CubeError::DuplicateCubicle(_)
+        | CubeError::InvalidOneHot(..)
+        | CubeError::InvalidEncodedValue(_)
+        | CubeError::InvalidOrientation(..) => 4,
+        _ => EXIT_OTHER,
+    }
+}
+
+fn join(turns: &[Turn]) -> String {
+    turns
+        .iter()
+        .map(|turn| turn.to_string().trim().to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn cube_from(facelets: Option<&str>, turn_metric: MetricKind) -> Result<Cube, CubeError> {
+    match facelets {
+        Some(facelets) => Cube::from_facelets(facelets, turn_metric),
+        None => Ok(Cube::new(turn_metric)),
+    }
+}
+
+fn apply(cube: &mut Cube, moves: &str) -> Result<(), CubeError> {
+    for turn in Turn::parse_sequence(moves)? {
+        cube.turn(turn as u8)?;
+    }
+    Ok(())
+}
+
+fn run(cli: Cli) -> Result<u8, CubeError> {
+    let turn_metric = MetricKind::from(cli.metric);
+
+    match cli.command {
+        Command::Scramble { length, seed } => {
+            let mut rng = match seed {
+                Some(seed) => StdRng::seed_from_u64(seed),
+                None => StdRng::from_entropy(),
+            };
+            let mut cube = Cube::new(turn_metric);
+            let turns = cube.scramble_with_rng(length, &mut rng);
+            println!("{}", join(&turns));
+            println!("{}", cube.facelets());
+        }
+        Command::Apply {
+            moves,
+            from,
+            format,
+        } => {
+            let mut cube = cube_from(from.as_deref(), turn_metric)?;
+            apply(&mut cube, &moves)?;
+            match format {
+                StateFormat::Facelets => println!("{}", cube.facelets()),
+                StateFormat::Net => println!("{}", cube),
+                StateFormat::State => {
+                    for component in cube.get_state().iter() {
+                        let values: Vec<String> = component.iter().map(u8::to_string).collect();
+                        println!("{}", values.join(" "));
+                    }
+                }
+            }
+        }
+        Command::Render {
+            facelets,
+            moves,
+            format,
+        } => {
+            let mut cube = cube_from(facelets.as_deref(), turn_metric)?;
+            if let Some(moves) = moves {
+                apply(&mut cube, &moves)?;
+            }
+            match format {
+                RenderFormat::Ansi => print!("{}", render::ansi(&cube)),
+                RenderFormat::Svg => print!("{}", render::svg(&cube)),
+            }
+        }
+        Command::Check { facelets } => {
+            let solvability = Cube::from_facelets(&facelets, turn_metric)?.solvability();
+            println!(
+                "permutation parity: {}",
+                if solvability.parity_matches {
+                    "ok"
+                } else {
+                    "edge and corner parities differ"
+                }
+            );
+            println!(
+                "edge orientation:   {}",
+                match solvability.edge_flip {
+                    0 => "ok",
+                    _ => "an odd number of edges flipped",
+                }
+            );
+            println!(
+                "corner orientation: {}",
+                match solvability.corner_twist {
+                    0 => "ok".to_string(),
+                    twist => format!("corner orientations sum to {} modulo 3", twist),
+                }
+            );
+            if solvability.is_solvable() {
+                println!("solvable");
+            } else {
+                println!("unsolvable");
+                return Ok(EXIT_UNSOLVED);
+            }
+        }
+        Command::Solve {
+            facelets,
+            max_depth,
+            method,
+        } => {
+            let cube = Cube::from_facelets(&facelets, turn_metric)?;
+            if !cube.is_solvable() {
+                eprintln!("cube is not solvable, run `rubikscube check` for details");
+                return Ok(EXIT_UNSOLVED);
+            }
+            match method {
+                SolveMethod::Optimal => match solver::solve(&cube, max_depth) {
+                    Some(turns) => println!("{}", join(&turns)),
+                    None => {
+                        eprintln!("no solution found within {} turns", max_depth);
+                        return Ok(EXIT_UNSOLVED);
+                    }
+                },
+                SolveMethod::Thistlethwaite => {
+                    for phase in thistlethwaite::solve(&cube)?.phases {
+                        println!("{}: {}", phase.name, join(&phase.turns));
+                    }
+                }
+                SolveMethod::Beginner => {
+                    for stage in beginner::solve(&cube)? {
+                        println!("{}: {}", stage.name, join(&stage.turns));
+                    }
+                }
+            }
+        }
+        Command::Analyse { solve, scramble } => {
+            let mut cube = Cube::new(turn_metric);
+            apply(&mut cube, &scramble)?;
+            let analysis = cfop::analyse(&cube, &Turn::parse_sequence(&solve)?)?;
+            if let Some(face) = analysis.cross_face {
+                println!("cross face: {}", face);
+            }
+            for report in &analysis.stages {
+                if report.is_skipped() {
+                    println!("{}: skipped", report.stage);
+                } else {
+                    let count = report.turns.len();
+                    let plural = if count == 1 { "" } else { "s" };
+                    let turns = join(&report.turns);
+                    println!("{}: {} ({} turn{})", report.stage, turns, count, plural);
+                }
+            }
+            if !analysis.solved() {
+                println!("unsolved after: {}", join(&analysis.remaining));
+                return Ok(EXIT_UNSOLVED);
+            }
+        }
+        Command::Dataset {
+            output,
+            samples,
+            max_depth,
+            seed,
+            encoding,
+            solver_depth,
+        } => {
+            let config = DatasetConfig {
+                num_samples: samples,
+                max_depth,
+                turn_metric,
+                seed,
+                solver_depth,
+            };
+            let samples = dataset::generate(&config);
+            let written = File::create(&output).and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                dataset::write_npy(&samples, encoding.into(), &mut writer)?;
+                writer.flush()
+            });
+            if let Err(err) = written {
+                eprintln!("error: could not write {}: {}", output.display(), err);
+                return Ok(EXIT_OTHER);
+            }
+        }
+    }
+    Ok(0)
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(code) => ExitCode::from(code),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
@@ -1,8 +1,14 @@
 use std::fmt::Display;
+use std::str::FromStr;
 
 use crate::CubeError;
 
-#[derive(PartialEq, Clone, Copy, Debug, PartialOrd)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd)]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, hash, frozen, module = "rubikscube")
+)]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 #[repr(u8)]
 pub enum MetricKind {
     QuarterTurnMetric = 12,
@@ -18,7 +24,12 @@ impl Display for MetricKind {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, frozen, module = "rubikscube")
+)]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 #[repr(u8)]
 pub enum Turn {
     L,  // Clockwise Left turn
@@ -90,4 +101,119 @@ impl Turn {
             _ => Err(CubeError::InvalidTurn(value, 0)), // code never reaches here, check done in turn
         }
     }
+
+    /// Returns the turn undoing this one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::Turn;
+    ///
+    /// assert_eq!(Turn::R.inverse(), Turn::R_);
+    /// assert_eq!(Turn::R_.inverse(), Turn::R);
+    /// assert_eq!(Turn::R2.inverse(), Turn::R2);
+    /// ```
+    pub fn inverse(self) -> Turn {
+        let twist = self as u8;
+        match twist {
+            0..=5 => Turn::from_u8(twist + 6).unwrap(),
+            6..=11 => Turn::from_u8(twist - 6).unwrap(),
+            _ => self,
+        }
+    }
+
+    /// Parses a whitespace separated sequence of moves such as `"R U R' U'"`.
+    ///
+    /// Each move is read with `Turn::from_str`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::Turn;
+    ///
+    /// let turns = Turn::parse_sequence("R U2 F'").unwrap();
+    /// assert_eq!(turns, vec![Turn::R, Turn::U2, Turn::F_]);
+    /// ```
+    pub fn parse_sequence(moves: &str) -> Result<Vec<Turn>, CubeError> {
+        moves.split_whitespace().map(Turn::from_str).collect()
+    }
+}
+
+impl FromStr for Turn {
+    type Err = CubeError;
+
+    /// Parses a move in standard notation.
+    ///
+    /// A face letter alone is a clockwise turn, followed by `'` or `_` an anti-clockwise turn,
+    /// and followed by `2` a half turn.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let face = match s.chars().next() {
+            Some('L') => 0,
+            Some('R') => 1,
+            Some('F') => 2,
+            Some('B') => 3,
+            Some('U') => 4,
+            Some('D') => 5,
+            _ => return Err(CubeError::InvalidMove(s.to_string())),
+        };
+        let offset = match &s[1..] {
+            "" => 0,
+            "'" | "_" => 6,
+            "2" => 12,
+            _ => return Err(CubeError::InvalidMove(s.to_string())),
+        };
+        Turn::from_u8(face + offset)
+    }
+}
+
+/// Writes every half turn as two quarter turns if `turn_metric` has no half turns.
+pub(crate) fn in_metric(turns: Vec<Turn>, turn_metric: MetricKind) -> Vec<Turn> {
+    match turn_metric {
+        MetricKind::HalfTurnMetric => turns,
+        MetricKind::QuarterTurnMetric => turns
+            .into_iter()
+            .flat_map(|turn| match turn as u8 {
+                twist @ 12..=17 => vec![Turn::from_u8(twist - 12).unwrap(); 2],
+                _ => vec![turn],
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::Turn;
+    use crate::errors::CubeError;
+
+    #[test]
+    fn from_str_test() {
+        for twist in 0..18 {
+            let turn = Turn::from_u8(twist).unwrap();
+            assert_eq!(Turn::from_str(turn.to_string().trim()), Ok(turn));
+        }
+        assert_eq!(Turn::from_str("R'"), Ok(Turn::R_));
+        assert_eq!(
+            Turn::from_str("X"),
+            Err(CubeError::InvalidMove("X".to_string()))
+        );
+        assert_eq!(
+            Turn::from_str("R3"),
+            Err(CubeError::InvalidMove("R3".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_sequence_test() {
+        assert_eq!(Turn::parse_sequence("  "), Ok(vec![]));
+        assert_eq!(
+            Turn::parse_sequence("L2 D_\tB"),
+            Ok(vec![Turn::L2, Turn::D_, Turn::B])
+        );
+        assert_eq!(
+            Turn::parse_sequence("L2 Q"),
+            Err(CubeError::InvalidMove("Q".to_string()))
+        );
+    }
 }
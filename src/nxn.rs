@@ -0,0 +1,695 @@
+//! Cubes of any size from 2x2x2 up, turned by their outer faces and inner slices.
+//!
+//! An [`NxNCube`] keeps the color of each of its `6 * n * n` facelets and moves them along
+//! the layers of the cube, so it is slower than the cubie based 3x3x3 [`Cube`]. Its pieces
+//! are the 8 corners, the wing edges along each of the 12 edges and the center pieces of
+//! every face. Odd cubes also have a middle edge in every edge and a fixed center on every
+//! face, which together with the corners behave as a 3x3x3 cube.
+//!
+//! Center pieces of the same color can not be told apart, and the wings of an orbit can reach
+//! any permutation, so the parity cases met when reducing a 4x4x4 to a 3x3x3, a single
+//! flipped edge pair or two swapped edge pairs, are solvable states. See
+//! [`NxNCube::solvability`] for the conditions that do apply.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+
+use crate::{
+    cubies::{Edge, Faces, EDGE_CUBIES, NUM_FACELETS},
+    errors::CubeError,
+    moves::Turn,
+    symmetry::{facelet_positions, NORMALS, TURN_FACES},
+    Cube, MetricKind, Solvability,
+};
+
+const NUM_FACES: usize = 6;
+const NUM_TURNS: u8 = 18;
+const FACE_LETTERS: [char; NUM_FACES] = ['L', 'R', 'F', 'B', 'U', 'D'];
+
+type Point = [i32; 3];
+
+/// Outward normal, row direction and column direction of every face, indexed by [`Faces`],
+/// in the facelet layout of `Cube::faces`.
+fn frames() -> &'static [(Point, Point, Point); NUM_FACES] {
+    static FRAMES: OnceLock<[(Point, Point, Point); NUM_FACES]> = OnceLock::new();
+    FRAMES.get_or_init(|| {
+        let positions = facelet_positions();
+        let widen = |vector: [i8; 3]| vector.map(i32::from);
+        std::array::from_fn(|face| {
+            let origin = widen(positions[9 * face].0);
+            let step = |index: usize| {
+                let position = widen(positions[index].0);
+                std::array::from_fn(|axis| position[axis] - origin[axis])
+            };
+            (widen(NORMALS[face]), step(9 * face + 3), step(9 * face + 1))
+        })
+    })
+}
+
+fn dot(a: Point, b: Point) -> i32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: Point, b: Point) -> Point {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// Rotates `point` a quarter turn clockwise, looking at the face with outward normal `axis`.
+fn rotate(axis: Point, point: Point) -> Point {
+    let height = dot(point, axis);
+    let turned = cross(axis, point);
+    std::array::from_fn(|i| height * axis[i] - turned[i])
+}
+
+/// A turn of one layer of an [`NxNCube`], or of every layer from a face down to it.
+///
+/// Layers are written in SiGN notation: `R` turns the right face, `3R` the third layer from
+/// the right, `Rw` or `r` the two outer layers on the right and `3Rw` the three outer layers.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::nxn::LayerTurn;
+/// use rubikscube::Turn;
+///
+/// let turn: LayerTurn = "3Rw'".parse().unwrap();
+/// assert_eq!(turn, LayerTurn { turn: Turn::R_, depth: 3, wide: true });
+/// assert_eq!(turn.to_string(), "3Rw'");
+/// assert_eq!("r2".parse::<LayerTurn>().unwrap().to_string(), "Rw2");
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct LayerTurn {
+    /// face and direction of the turn
+    pub turn: Turn,
+    /// layer turned, counted from the face, which is layer 1
+    pub depth: usize,
+    /// true if every layer from the face down to `depth` turns along
+    pub wide: bool,
+}
+
+impl LayerTurn {
+    pub fn parse_sequence(moves: &str) -> Result<Vec<LayerTurn>, CubeError> {
+        moves.split_whitespace().map(LayerTurn::from_str).collect()
+    }
+}
+
+impl From<Turn> for LayerTurn {
+    fn from(turn: Turn) -> LayerTurn {
+        LayerTurn {
+            turn,
+            depth: 1,
+            wide: false,
+        }
+    }
+}
+
+impl fmt::Display for LayerTurn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let twist = self.turn as usize;
+        if self.depth > 2 || (self.depth == 2 && !self.wide) {
+            write!(f, "{}", self.depth)?;
+        }
+        write!(f, "{}", FACE_LETTERS[twist % NUM_FACES])?;
+        if self.wide && self.depth > 1 {
+            write!(f, "w")?;
+        }
+        write!(f, "{}", ["", "'", "2"][twist / NUM_FACES])
+    }
+}
+
+impl FromStr for LayerTurn {
+    type Err = CubeError;
+
+    /// Parses a layer turn in SiGN notation, the suffix as in `Turn::from_str`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || CubeError::InvalidMove(s.to_string());
+        let digits = s.chars().take_while(char::is_ascii_digit).count();
+        let (depth, rest) = s.split_at(digits);
+        let depth = match depth {
+            "" => None,
+            depth => Some(depth.parse::<usize>().map_err(|_| invalid())?),
+        };
+        let letter = rest.chars().next().ok_or_else(invalid)?;
+        let rest = &rest[letter.len_utf8()..];
+
+        let (wide, suffix) = match rest.strip_prefix('w') {
+            Some(suffix) if letter.is_ascii_uppercase() => (true, suffix),
+            None if letter.is_ascii_lowercase() && depth.is_none() => (true, rest),
+            None if letter.is_ascii_uppercase() => (false, rest),
+            _ => return Err(invalid()),
+        };
+        let depth = match depth {
+            Some(0) => return Err(invalid()),
+            Some(depth) => depth,
+            None if wide => 2,
+            None => 1,
+        };
+        let turn = Turn::from_str(&format!("{}{}", letter.to_ascii_uppercase(), suffix))
+            .map_err(|_| invalid())?;
+        Ok(LayerTurn { turn, depth, wide })
+    }
+}
+
+/// The conditions an [`NxNCube`] has to meet to be solvable.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct NxNSolvability {
+    /// the corners, with the middle edges and fixed centers of an odd cube, read as a 3x3x3
+    /// cube held with its fixed centers in place, `None` if they do not form one. Even cubes
+    /// have no middle edges, so only the corner twist applies to them.
+    pub reduced: Option<Solvability>,
+    /// true if every wing is a half of a distinct edge cubie, on the side of its edge it can
+    /// be turned to
+    pub wings_valid: bool,
+    /// true if every orbit of center pieces holds as many centers of each color
+    pub centers_valid: bool,
+}
+
+impl NxNSolvability {
+    /// Returns true if every condition is met.
+    pub fn is_solvable(&self) -> bool {
+        self.reduced.is_some_and(|reduced| reduced.is_solvable())
+            && self.wings_valid
+            && self.centers_valid
+    }
+}
+
+/// A cube of size `n` of at least 2, with its facelets in the layout of `Cube::faces`.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::nxn::{LayerTurn, NxNCube};
+///
+/// let mut cube = NxNCube::new(4);
+/// for turn in LayerTurn::parse_sequence("2R U 2R'").unwrap() {
+///     cube.turn(turn).unwrap();
+/// }
+/// assert!(!cube.solved());
+/// assert!(cube.is_solvable());
+/// assert_eq!(NxNCube::from_facelets(&cube.facelets()), Ok(cube));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct NxNCube {
+    size: usize,
+    facelets: Vec<Faces>,
+}
+
+impl NxNCube {
+    /// Returns a solved cube of size `size`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is below 2.
+    pub fn new(size: usize) -> NxNCube {
+        assert!(size >= 2, "cube of size {} has no corners", size);
+        let facelets = (0..NUM_FACES * size * size)
+            .map(|index| Faces::from_repr(index / (size * size)).unwrap())
+            .collect();
+        NxNCube { size, facelets }
+    }
+
+    /// Initializes a cube from a string of `6 * n * n` facelet colors, in the format returned
+    /// by `facelets`.
+    ///
+    /// The size of the cube is read from the number of facelets. Only the colors are
+    /// validated, use `solvability` to check that they form a cube.
+    pub fn from_facelets(facelets: &str) -> Result<NxNCube, CubeError> {
+        let colors = facelets
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(|color| Ok(Faces::from_str(color.encode_utf8(&mut [0; 4]))?))
+            .collect::<Result<Vec<Faces>, CubeError>>()?;
+        let size = (1..)
+            .find(|&size| NUM_FACES * size * size >= colors.len())
+            .unwrap();
+        if size < 2 || NUM_FACES * size * size != colors.len() {
+            return Err(CubeError::InvalidCubeSize(colors.len()));
+        }
+        Ok(NxNCube {
+            size,
+            facelets: colors,
+        })
+    }
+
+    /// Returns the number of layers along each axis.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the color of the facelet at `row` and `column` of `face`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `row` or `column` is not below the size of the cube.
+    pub fn facelet(&self, face: Faces, row: usize, column: usize) -> Faces {
+        assert!(row < self.size && column < self.size);
+        self.facelets[(face as usize * self.size + row) * self.size + column]
+    }
+
+    /// Returns the facelet colors as a string, face by face in the order W, Y, G, B, R, O,
+    /// each face row by row as in `Cube::facelets`.
+    pub fn facelets(&self) -> String {
+        self.facelets.iter().map(Faces::to_string).collect()
+    }
+
+    /// Returns true if every face has a single color.
+    ///
+    /// Even cubes have no fixed centers, so a cube solved in any orientation is solved.
+    pub fn solved(&self) -> bool {
+        self.facelets
+            .chunks_exact(self.size * self.size)
+            .all(|face| face.iter().all(|&color| color == face[0]))
+    }
+
+    /// Performs `turn` on the cube.
+    ///
+    /// Returns `CubeError::InvalidLayer` if the turn's depth is not between 1 and the size of
+    /// the cube. A wide turn of every layer rotates the whole cube.
+    pub fn turn(&mut self, turn: LayerTurn) -> Result<(), CubeError> {
+        if turn.depth == 0 || turn.depth > self.size {
+            return Err(CubeError::InvalidLayer(turn.depth, self.size));
+        }
+        let twist = turn.turn as usize;
+        let axis = frames()[TURN_FACES[twist % NUM_FACES] as usize].0;
+        let quarter_turns = [1, 3, 2][twist / NUM_FACES];
+        let layer = self.size as i32 + 1 - 2 * turn.depth as i32;
+
+        let mut facelets = self.facelets.clone();
+        for (index, &color) in self.facelets.iter().enumerate() {
+            let (mut center, mut normal) = self.position(index);
+            let height = dot(center, axis);
+            if height == layer || (turn.wide && height > layer) {
+                for _ in 0..quarter_turns {
+                    center = rotate(axis, center);
+                    normal = rotate(axis, normal);
+                }
+                facelets[self.index(center, normal)] = color;
+            }
+        }
+        self.facelets = facelets;
+        Ok(())
+    }
+
+    /// Scrambles the cube with `num_turns` random turns, of the outer layers and of the inner
+    /// layers up to the middle of the cube, and returns them.
+    ///
+    /// The middle layer of an odd cube is never turned, so its fixed centers stay in place.
+    pub fn scramble_with_rng<R: Rng + ?Sized>(
+        &mut self,
+        num_turns: u32,
+        rng: &mut R,
+    ) -> Vec<LayerTurn> {
+        let twists = Uniform::from(0..NUM_TURNS);
+        let depths = Uniform::from(1..=self.size / 2);
+        (0..num_turns)
+            .map(|_| {
+                let turn = LayerTurn {
+                    turn: Turn::from_u8(twists.sample(rng)).unwrap(),
+                    depth: depths.sample(rng),
+                    wide: false,
+                };
+                self.turn(turn).unwrap();
+                turn
+            })
+            .collect()
+    }
+
+    /// Scrambles the cube with `num_turns` random turns, see `scramble_with_rng`.
+    pub fn scramble(&mut self, num_turns: u32) -> Vec<LayerTurn> {
+        self.scramble_with_rng(num_turns, &mut rand::thread_rng())
+    }
+
+    /// Returns the conditions the cube meets to be solvable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::nxn::{LayerTurn, NxNCube};
+    ///
+    /// // the edge pair between the up and front faces flipped, unsolvable on a 3x3x3
+    /// let mut cube = NxNCube::new(4);
+    /// let parity = "2R2 B2 U2 2L U2 2R' U2 2R U2 F2 2R F2 2L' B2 2R2";
+    /// for turn in LayerTurn::parse_sequence(parity).unwrap() {
+    ///     cube.turn(turn).unwrap();
+    /// }
+    /// assert!(!cube.solved());
+    /// assert!(cube.solvability().is_solvable());
+    /// ```
+    pub fn solvability(&self) -> NxNSolvability {
+        NxNSolvability {
+            reduced: self.reduced_solvability(),
+            wings_valid: self.wings_valid(),
+            centers_valid: self.centers_valid(),
+        }
+    }
+
+    /// Returns true if the cube is solvable, see `solvability`.
+    pub fn is_solvable(&self) -> bool {
+        self.solvability().is_solvable()
+    }
+
+    /// Returns the representation of the cube as one-hot blocks of the 6 colors, one block for
+    /// every facelet in the order of `facelets`.
+    pub fn representation(&self) -> Vec<bool> {
+        let mut representation = vec![false; NUM_FACES * self.facelets.len()];
+        for (index, &color) in self.facelets.iter().enumerate() {
+            representation[NUM_FACES * index + color as usize] = true;
+        }
+        representation
+    }
+
+    /// Recovers a cube of size `size` from its `representation`.
+    ///
+    /// Returns `CubeError::InvalidBufferLength` if the representation does not have a block
+    /// for every facelet, and `CubeError::InvalidOneHot` with the facelet if a block does not
+    /// have exactly one bit set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is below 2.
+    pub fn from_representation(size: usize, representation: &[bool]) -> Result<NxNCube, CubeError> {
+        let mut cube = NxNCube::new(size);
+        let expected = NUM_FACES * cube.facelets.len();
+        if representation.len() != expected {
+            return Err(CubeError::InvalidBufferLength(
+                expected,
+                representation.len(),
+            ));
+        }
+        for (index, block) in representation.chunks_exact(NUM_FACES).enumerate() {
+            let set: Vec<usize> = (0..NUM_FACES).filter(|&color| block[color]).collect();
+            if set.len() != 1 {
+                return Err(CubeError::InvalidOneHot(index, set.len()));
+            }
+            cube.facelets[index] = Faces::from_repr(set[0]).unwrap();
+        }
+        Ok(cube)
+    }
+
+    /// Returns the position of a facelet as the center of its cubie, in units of half a
+    /// cubie from the center of the cube, and the outward normal of its face.
+    fn position(&self, index: usize) -> (Point, Point) {
+        let n = self.size as i32;
+        let (face, row, column) = (
+            index / (self.size * self.size),
+            (index / self.size % self.size) as i32,
+            (index % self.size) as i32,
+        );
+        let (normal, row_step, column_step) = frames()[face];
+        let center = std::array::from_fn(|axis| {
+            (n - 1) * normal[axis]
+                + (2 * row - n + 1) * row_step[axis]
+                + (2 * column - n + 1) * column_step[axis]
+        });
+        (center, normal)
+    }
+
+    /// Returns the index of the facelet of the cubie at `center` on the face with outward
+    /// normal `normal`.
+    fn index(&self, center: Point, normal: Point) -> usize {
+        let n = self.size as i32;
+        let face = frames().iter().position(|frame| frame.0 == normal).unwrap();
+        let (_, row_step, column_step) = frames()[face];
+        let row = ((dot(center, row_step) + n - 1) / 2) as usize;
+        let column = ((dot(center, column_step) + n - 1) / 2) as usize;
+        (face * self.size + row) * self.size + column
+    }
+
+    /// Facelets of every piece, grouped by the center of their cubie.
+    fn pieces(&self) -> Vec<Vec<usize>> {
+        let mut pieces: HashMap<Point, Vec<usize>> = HashMap::new();
+        for index in 0..self.facelets.len() {
+            pieces
+                .entry(self.position(index).0)
+                .or_default()
+                .push(index);
+        }
+        pieces.into_values().collect()
+    }
+
+    /// Reads the corners, and the middle edges and fixed centers of an odd cube, as a 3x3x3
+    /// cube. Even cubes get solved edges and centers.
+    fn reduced_solvability(&self) -> Option<Solvability> {
+        let n = self.size;
+        let odd = n % 2 == 1;
+        let mut facelets = String::with_capacity(NUM_FACELETS);
+        for face in 0..NUM_FACES {
+            for row in 0..3 {
+                for column in 0..3 {
+                    let corner = row != 1 && column != 1;
+                    let color = if corner || odd {
+                        let [row, column] = [row, column].map(|i| [0, n / 2, n - 1][i]);
+                        self.facelets[(face * n + row) * n + column]
+                    } else {
+                        Faces::from_repr(face).unwrap()
+                    };
+                    facelets.push_str(&color.to_string());
+                }
+            }
+        }
+
+        let (cube, _) =
+            Cube::from_facelets_normalised(&facelets, MetricKind::HalfTurnMetric).ok()?;
+        let solvability = cube.solvability();
+        Some(if odd {
+            solvability
+        } else {
+            Solvability {
+                parity_matches: true,
+                edge_flip: 0,
+                corner_twist: solvability.corner_twist,
+            }
+        })
+    }
+
+    /// Returns true if every wing is found once.
+    ///
+    /// A wing is told apart from the other wing of its edge cubie by the side of its edge it
+    /// is on, seen from the face of its first facelet towards the face of its second. Turns
+    /// rotate the wing along with both faces, so that side never changes.
+    fn wings_valid(&self) -> bool {
+        let mut wings = HashMap::new();
+        for piece in self.pieces().into_iter().filter(|piece| piece.len() == 2) {
+            let colors = [self.facelets[piece[0]], self.facelets[piece[1]]];
+            let cubie = match Edge::new(colors[0], colors[1]).cubie_index() {
+                Some(cubie) => cubie,
+                None => return false,
+            };
+            let (first, second) = if colors[0] == EDGE_CUBIES[cubie as usize].facelet_a() {
+                (piece[0], piece[1])
+            } else {
+                (piece[1], piece[0])
+            };
+            let (center, first_normal) = self.position(first);
+            let side = dot(center, cross(first_normal, self.position(second).1));
+            // middle edges are checked with the corners
+            if side != 0 && wings.insert((cubie, side), ()).is_some() {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns true if every orbit of center pieces holds as many centers of each color.
+    ///
+    /// Orbits are found by joining every center facelet with the facelet it is turned to by
+    /// a quarter turn of its layer, along each axis.
+    fn centers_valid(&self) -> bool {
+        let mut parents: Vec<usize> = (0..self.facelets.len()).collect();
+        fn root(parents: &mut [usize], mut index: usize) -> usize {
+            while parents[index] != index {
+                parents[index] = parents[parents[index]];
+                index = parents[index];
+            }
+            index
+        }
+
+        let centers: Vec<usize> = self
+            .pieces()
+            .into_iter()
+            .filter(|piece| piece.len() == 1)
+            .map(|piece| piece[0])
+            .collect();
+        for &(axis, _, _) in frames() {
+            for &index in &centers {
+                let (center, normal) = self.position(index);
+                let turned = self.index(rotate(axis, center), rotate(axis, normal));
+                let (a, b) = (root(&mut parents, index), root(&mut parents, turned));
+                parents[a] = b;
+            }
+        }
+
+        let mut orbits: HashMap<usize, [usize; NUM_FACES]> = HashMap::new();
+        for &index in &centers {
+            let orbit = root(&mut parents, index);
+            orbits.entry(orbit).or_default()[self.facelets[index] as usize] += 1;
+        }
+        orbits
+            .values()
+            .all(|counts| counts.iter().all(|&count| count == counts[0]))
+    }
+}
+
+impl From<&Cube> for NxNCube {
+    fn from(cube: &Cube) -> NxNCube {
+        NxNCube {
+            size: 3,
+            facelets: cube.faces().iter().flatten().flatten().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cross, dot, LayerTurn, NxNCube};
+    use crate::{Cube, CubeError, Turn};
+
+    fn turned(size: usize, moves: &str) -> NxNCube {
+        let mut cube = NxNCube::new(size);
+        for turn in LayerTurn::parse_sequence(moves).unwrap() {
+            cube.turn(turn).unwrap();
+        }
+        cube
+    }
+
+    #[test]
+    fn matches_cube_test() {
+        let mut cube = Cube::cube_htm();
+        let mut nxn = NxNCube::from(&cube);
+        assert_eq!(nxn, NxNCube::new(3));
+        for turn in cube.scramble_with_rng(30, &mut rand::thread_rng()) {
+            nxn.turn(turn.into()).unwrap();
+        }
+        assert_eq!(nxn.facelets(), cube.facelets());
+        assert!(nxn.is_solvable());
+    }
+
+    #[test]
+    fn layer_turn_test() {
+        for size in 2..=6 {
+            let mut cube = NxNCube::new(size);
+            let scramble = cube.scramble(40);
+            assert!(cube.is_solvable());
+            for turn in scramble.into_iter().rev() {
+                cube.turn(LayerTurn {
+                    turn: turn.turn.inverse(),
+                    ..turn
+                })
+                .unwrap();
+            }
+            assert_eq!(cube, NxNCube::new(size));
+        }
+
+        // a wide turn is the same as turning each of its layers
+        assert_eq!(turned(5, "3Rw' U"), turned(5, "R' 2R' 3R' U"));
+        assert_eq!(turned(4, "r"), turned(4, "R 2R"));
+        // turning every layer rotates the whole cube
+        assert!(turned(4, "4Rw").solved());
+        assert_eq!(turned(4, "3Rw L'"), turned(4, "4Rw"));
+        assert_eq!(turned(3, "3R"), turned(3, "L'"));
+        assert_eq!(
+            NxNCube::new(3).turn("4R".parse().unwrap()),
+            Err(CubeError::InvalidLayer(4, 3))
+        );
+    }
+
+    #[test]
+    fn parse_test() {
+        let turns = LayerTurn::parse_sequence("R 2L' Uw2 3Fw f' 12D").unwrap();
+        let written: Vec<String> = turns.iter().map(ToString::to_string).collect();
+        assert_eq!(written, ["R", "2L'", "Uw2", "3Fw", "Fw'", "12D"]);
+        assert_eq!(turns[1].turn, Turn::L_);
+
+        for invalid in ["", "0R", "2", "Rw3", "2r", "X", "rw"] {
+            assert_eq!(
+                invalid.parse::<LayerTurn>(),
+                Err(CubeError::InvalidMove(invalid.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn middle_slice_test() {
+        // the middle slice moves the fixed centers, the cube is still solvable held otherwise
+        let cube = turned(5, "3R U 3R' 3F");
+        assert!(cube.is_solvable());
+        assert_eq!(NxNCube::from_facelets(&cube.facelets()), Ok(cube));
+    }
+
+    #[test]
+    fn unsolvable_test() {
+        // moves the colors of the first piece with `len` facelets matching `select` one
+        // facelet along
+        let cycle = |size: usize, len: usize, select: &dyn Fn(&NxNCube, &[usize]) -> bool| {
+            let mut cube = NxNCube::new(size);
+            let piece = cube
+                .pieces()
+                .into_iter()
+                .find(|piece| piece.len() == len && select(&cube, piece))
+                .unwrap();
+            let colors: Vec<_> = piece.iter().map(|&index| cube.facelets[index]).collect();
+            for (i, &index) in piece.iter().enumerate() {
+                cube.facelets[index] = colors[(i + 1) % len];
+            }
+            cube.solvability()
+        };
+        let any = |_: &NxNCube, _: &[usize]| true;
+        let wing = |cube: &NxNCube, piece: &[usize]| {
+            let (center, normal) = cube.position(piece[0]);
+            dot(center, cross(normal, cube.position(piece[1]).1)) != 0
+        };
+
+        for size in [2, 4, 5] {
+            let twisted = cycle(size, 3, &any);
+            assert_ne!(twisted.reduced.unwrap().corner_twist, 0);
+            assert!(twisted.wings_valid && twisted.centers_valid);
+        }
+
+        let flipped = cycle(4, 2, &wing);
+        assert!(!flipped.wings_valid);
+        assert!(flipped.reduced.unwrap().is_solvable() && flipped.centers_valid);
+
+        let flipped = cycle(5, 2, &|cube, piece| !wing(cube, piece));
+        assert_eq!(flipped.reduced.unwrap().edge_flip, 1);
+        assert!(flipped.wings_valid);
+
+        // an x-center of the white face swapped with a t-center of the yellow face
+        let mut cube = NxNCube::new(5);
+        cube.facelets.swap(6, 25 + 7);
+        let swapped = cube.solvability();
+        assert!(!swapped.centers_valid);
+        assert!(swapped.reduced.unwrap().is_solvable() && swapped.wings_valid);
+    }
+
+    #[test]
+    fn representation_test() {
+        let mut cube = NxNCube::new(5);
+        cube.scramble(20);
+        let mut representation = cube.representation();
+        assert_eq!(representation.len(), 6 * 6 * 25);
+        assert_eq!(NxNCube::from_representation(5, &representation), Ok(cube));
+
+        representation[6..12].fill(true);
+        assert_eq!(
+            NxNCube::from_representation(5, &representation),
+            Err(CubeError::InvalidOneHot(1, 6))
+        );
+        assert_eq!(
+            NxNCube::from_representation(4, &representation),
+            Err(CubeError::InvalidBufferLength(6 * 6 * 16, 6 * 6 * 25))
+        );
+        assert_eq!(
+            NxNCube::from_facelets("WWWWWW"),
+            Err(CubeError::InvalidCubeSize(6))
+        );
+    }
+}
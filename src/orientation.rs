@@ -1,45 +1,62 @@
 use crate::{
     cubies::{NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES, NUM_EDGE_ORIENTATION},
     errors::CubeError,
+    permutation::Permutation,
 };
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct Orientation {
-    orientations: Vec<u8>,
-    n_orientation: u8,
+/// Orientations of `N` cubies with `M` possible orientations each, stored inline so that it
+/// can be copied without allocating.
+///
+/// Orientations are indexed by the cubicle a cubie is in, so that turns only need to gather
+/// the orientations of the cubies they move.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Clone, Copy)]
+pub struct Orientation<const N: usize, const M: u8> {
+    orientations: [u8; N],
 }
 
-impl Orientation {
-    pub fn edge() -> Orientation {
+pub type EdgeOrientation = Orientation<{ NUM_EDGES as usize }, NUM_EDGE_ORIENTATION>;
+pub type CornerOrientation = Orientation<{ NUM_CORNERS as usize }, NUM_CORNER_ORIENTATION>;
+
+impl EdgeOrientation {
+    pub fn edge() -> EdgeOrientation {
         Self {
-            orientations: vec![0; NUM_EDGES as usize],
-            n_orientation: NUM_EDGE_ORIENTATION,
+            orientations: [0; NUM_EDGES as usize],
         }
     }
+}
 
-    pub fn corner() -> Orientation {
+impl CornerOrientation {
+    pub fn corner() -> CornerOrientation {
         Self {
-            orientations: vec![0; NUM_CORNERS as usize],
-            n_orientation: NUM_CORNER_ORIENTATION,
+            orientations: [0; NUM_CORNERS as usize],
         }
     }
+}
+
+impl<const N: usize, const M: u8> Orientation<N, M> {
+    /// Creates orientations from a slice indexed by cubicle, which must hold exactly `N` values.
+    pub fn new_with_orientation(o: &[u8]) -> Orientation<N, M> {
+        let mut orientations = [0; N];
+        orientations.copy_from_slice(o);
+        Orientation { orientations }
+    }
 
     pub fn orientation_at_index(&self, idx: u8) -> u8 {
-        self.orientations[idx as usize] as u8
+        self.orientations[idx as usize]
     }
 
     pub fn add_one(&mut self, idx: u8) {
         self.orientations[idx as usize] = match self.orientations[idx as usize] {
             0 => 1,
             1 => {
-                if self.n_orientation == 2 {
+                if M == 2 {
                     0
                 } else {
                     2
                 }
             }
             2 => {
-                if self.n_orientation == 2 {
+                if M == 2 {
                     1
                 } else {
                     0
@@ -53,14 +70,14 @@ impl Orientation {
         self.orientations[idx as usize] = match self.orientations[idx as usize] {
             0 => 2,
             1 => {
-                if self.n_orientation == 2 {
+                if M == 2 {
                     1
                 } else {
                     0
                 }
             }
             2 => {
-                if self.n_orientation == 2 {
+                if M == 2 {
                     0
                 } else {
                     1
@@ -70,35 +87,97 @@ impl Orientation {
         }
     }
 
+    /// Moves the orientation in cubicle `sources[k]` to cubicle `targets[k]` and adds
+    /// `twists[k]` to it, for every `k`.
+    ///
+    /// Values of `twists` must be below the number of orientations. Cubicles outside of
+    /// `targets` are left untouched.
+    #[inline]
+    pub fn cycle<const K: usize>(
+        &mut self,
+        targets: &[u8; K],
+        sources: &[u8; K],
+        twists: &[u8; K],
+    ) {
+        let orientations = sources.map(|cubicle| self.orientations[cubicle as usize]);
+        for ((&cubicle, orientation), twist) in targets.iter().zip(orientations).zip(twists) {
+            self.orientations[cubicle as usize] = (orientation + twist) % M;
+        }
+    }
+
+    /// Get the orientations indexed by cubicle.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.orientations
+    }
+
     pub fn sum(&self) -> u8 {
         self.orientations.iter().sum()
     }
 
-    /// Get a reference to the orientation's orientations.
+    /// Get the orientation of every cubie, indexed by cubie rather than by cubicle.
+    ///
+    /// `permutation` gives the cubie placed in each cubicle.
     #[must_use]
-    pub fn orientations(&self) -> Vec<u8> {
-        self.orientations.to_vec()
+    pub fn orientations(&self, permutation: &Permutation<N>) -> Vec<u8> {
+        let mut orientations = vec![0; N];
+        for (cubicle, &orientation) in self.orientations.iter().enumerate() {
+            orientations[permutation.cubie_in_cubicle(cubicle as u8) as usize] = orientation;
+        }
+        orientations
     }
 
-    /// Set the orientation's orientations.
-    pub fn set_orientations(&mut self, orientations: Vec<u8>) -> Result<(), CubeError> {
-        if orientations.len() != self.orientations.len() {
-            return Err(CubeError::InvalidState);
+    /// Checks that `orientations` has one value per cubie, each below the number of orientations.
+    pub fn validate(&self, orientations: &[u8]) -> Result<(), CubeError> {
+        if orientations.len() != N {
+            return Err(CubeError::InvalidStateLength(N as u8, orientations.len()));
+        }
+
+        match orientations.iter().find(|&&o| o >= M) {
+            Some(&o) => Err(CubeError::InvalidOrientation(o, M)),
+            None => Ok(()),
         }
-        self.orientations = orientations;
+    }
+
+    /// Set the orientation of every cubie from values indexed by cubie.
+    ///
+    /// `permutation` gives the cubie placed in each cubicle. Returns an error, leaving the
+    /// orientations untouched, if `orientations` is invalid.
+    pub fn set_orientations(
+        &mut self,
+        orientations: Vec<u8>,
+        permutation: &Permutation<N>,
+    ) -> Result<(), CubeError> {
+        self.validate(&orientations)?;
+        self.set_orientations_unchecked(orientations, permutation);
         Ok(())
     }
+
+    /// Set the orientation of every cubie from values indexed by cubie, without validating them.
+    ///
+    /// The caller must ensure `orientations` passes [`Orientation::validate`], otherwise later
+    /// turns may panic.
+    pub fn set_orientations_unchecked(
+        &mut self,
+        orientations: Vec<u8>,
+        permutation: &Permutation<N>,
+    ) {
+        for (cubicle, orientation) in self.orientations.iter_mut().enumerate() {
+            *orientation = orientations[permutation.cubie_in_cubicle(cubicle as u8) as usize];
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::Orientation;
+    use super::{CornerOrientation, EdgeOrientation, Orientation};
     use crate::cubies::{
         Corner, Edge, CORNER_CUBIES, EDGE_CUBIES, NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES,
         NUM_EDGE_ORIENTATION,
     };
+    use crate::errors::CubeError;
+    use crate::permutation::Permutation;
 
-    impl Orientation {
+    impl<const N: usize, const M: u8> Orientation<N, M> {
         pub fn edge_by_index(&self, idx: u8) -> &Edge {
             &EDGE_CUBIES[idx as usize]
         }
@@ -106,18 +185,6 @@ mod tests {
         pub fn corner_by_index(&self, idx: u8) -> &Corner {
             &CORNER_CUBIES[idx as usize]
         }
-
-        pub fn new_with_orientation(o: Vec<u8>) -> Orientation {
-            assert!(o.len() == NUM_EDGES as usize || o.len() == NUM_CORNERS as usize);
-            Orientation {
-                n_orientation: if o.len() == NUM_EDGES as usize {
-                    NUM_EDGE_ORIENTATION
-                } else {
-                    NUM_CORNER_ORIENTATION
-                },
-                orientations: o,
-            }
-        }
     }
 
     #[test]
@@ -135,7 +202,7 @@ mod tests {
 
     #[test]
     fn first_edge_orientation_test() {
-        let edge_set = Orientation::new_with_orientation(vec![1; NUM_EDGES as usize]);
+        let edge_set = EdgeOrientation::new_with_orientation(&[1; NUM_EDGES as usize]);
 
         for i in 0..NUM_EDGES {
             let edge_orientation = edge_set.orientation_at_index(i);
@@ -145,7 +212,7 @@ mod tests {
 
     #[test]
     fn add_one_edge_test() {
-        let mut edge_set = Orientation::new_with_orientation(vec![1; NUM_EDGES as usize]);
+        let mut edge_set = EdgeOrientation::new_with_orientation(&[1; NUM_EDGES as usize]);
 
         for i in 0..NUM_EDGES {
             let edge_orientation = edge_set.orientation_at_index(i);
@@ -156,7 +223,7 @@ mod tests {
 
     #[test]
     fn edge_sum_test() {
-        let edge_set = Orientation::new_with_orientation(vec![1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0]);
+        let edge_set = EdgeOrientation::new_with_orientation(&[1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0]);
         assert_eq!(6, edge_set.sum());
     }
 
@@ -175,7 +242,7 @@ mod tests {
 
     #[test]
     fn first_corner_orientation_test() {
-        let corner_set = Orientation::new_with_orientation(vec![2; NUM_CORNERS as usize]);
+        let corner_set = CornerOrientation::new_with_orientation(&[2; NUM_CORNERS as usize]);
 
         for i in 0..NUM_CORNERS {
             let corner_orientation = corner_set.orientation_at_index(i);
@@ -185,7 +252,7 @@ mod tests {
 
     #[test]
     fn second_orientation_test() {
-        let corner_set = Orientation::new_with_orientation(vec![1; NUM_CORNERS as usize]);
+        let corner_set = CornerOrientation::new_with_orientation(&[1; NUM_CORNERS as usize]);
 
         for i in 0..NUM_CORNERS {
             let corner_orientation = corner_set.orientation_at_index(i);
@@ -195,7 +262,7 @@ mod tests {
 
     #[test]
     fn corner_add_one_test() {
-        let mut corner_set = Orientation::new_with_orientation(vec![1; NUM_CORNERS as usize]);
+        let mut corner_set = CornerOrientation::new_with_orientation(&[1; NUM_CORNERS as usize]);
 
         for i in 0..NUM_CORNERS {
             let corner_orientation = corner_set.orientation_at_index(i);
@@ -209,7 +276,7 @@ mod tests {
 
     #[test]
     fn add_two_test() {
-        let mut corner_set = Orientation::new_with_orientation(vec![1; NUM_CORNERS as usize]);
+        let mut corner_set = CornerOrientation::new_with_orientation(&[1; NUM_CORNERS as usize]);
 
         for i in 0..NUM_CORNERS {
             let corner_orientation = corner_set.orientation_at_index(i);
@@ -232,9 +299,45 @@ mod tests {
         assert_eq!(corner_set.sum(), 1);
     }
 
+    #[test]
+    fn set_orientations_test() {
+        let mut edge_set = Orientation::edge();
+        let mut corner_set = Orientation::corner();
+        let edge_permutation = Permutation::edge();
+        let mut corner_permutation = Permutation::corner();
+
+        assert_eq!(
+            edge_set.set_orientations(vec![2; NUM_EDGES as usize], &edge_permutation),
+            Err(CubeError::InvalidOrientation(2, NUM_EDGE_ORIENTATION))
+        );
+        assert_eq!(
+            corner_set.set_orientations(vec![0, 1, 2, 3, 0, 0, 0, 0], &corner_permutation),
+            Err(CubeError::InvalidOrientation(3, NUM_CORNER_ORIENTATION))
+        );
+        assert_eq!(
+            corner_set.set_orientations(vec![0; NUM_EDGES as usize], &corner_permutation),
+            Err(CubeError::InvalidStateLength(
+                NUM_CORNERS,
+                NUM_EDGES as usize
+            ))
+        );
+        assert_eq!(corner_set, Orientation::corner());
+
+        corner_permutation.swap_two_cubies(0, 1);
+        corner_set
+            .set_orientations(vec![2, 1, 0, 1, 2, 0, 2, 1], &corner_permutation)
+            .unwrap();
+        assert_eq!(corner_set.sum(), 9);
+        assert_eq!(corner_set.orientation_at_index(0), 1);
+        assert_eq!(
+            corner_set.orientations(&corner_permutation),
+            vec![2, 1, 0, 1, 2, 0, 2, 1]
+        );
+    }
+
     #[test]
     fn corner_sum_test() {
-        let corner_set = Orientation::new_with_orientation(vec![2, 1, 0, 1, 2, 0, 2, 1]);
+        let corner_set = CornerOrientation::new_with_orientation(&[2, 1, 0, 1, 2, 0, 2, 1]);
         assert_eq!(9, corner_set.sum());
     }
 }
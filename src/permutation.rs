@@ -3,31 +3,52 @@ use crate::{
     errors::CubeError,
 };
 
-#[derive(Debug, PartialEq, PartialOrd, Clone)]
-pub struct Permutation {
-    permutation: Vec<u8>,
+/// Permutation of `N` cubies, stored inline so that it can be copied without allocating.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Clone, Copy)]
+pub struct Permutation<const N: usize> {
+    permutation: [u8; N],
 }
 
-impl Permutation {
-    pub fn edge() -> Permutation {
-        Permutation {
-            permutation: (0..NUM_EDGES).collect(),
-        }
+pub type EdgePermutation = Permutation<{ NUM_EDGES as usize }>;
+pub type CornerPermutation = Permutation<{ NUM_CORNERS as usize }>;
+
+impl EdgePermutation {
+    pub const fn edge() -> EdgePermutation {
+        Permutation::identity()
     }
+}
 
-    pub fn corner() -> Permutation {
-        Permutation {
-            permutation: (0..NUM_CORNERS).collect(),
-        }
+impl CornerPermutation {
+    pub const fn corner() -> CornerPermutation {
+        Permutation::identity()
     }
+}
 
-    pub fn new_with_permutation(perm: &[u8]) -> Permutation {
-        Permutation {
-            permutation: perm.to_vec(),
+impl<const N: usize> Permutation<N> {
+    const fn identity() -> Permutation<N> {
+        let mut permutation = [0; N];
+        let mut i = 0;
+        while i < N {
+            permutation[i] = i as u8;
+            i += 1;
         }
+        Permutation { permutation }
+    }
+
+    /// Creates a permutation from a slice, which must hold exactly `N` values.
+    pub fn new_with_permutation(perm: &[u8]) -> Permutation<N> {
+        let mut permutation = [0; N];
+        permutation.copy_from_slice(perm);
+        Permutation { permutation }
     }
 
-    pub fn swap_four_cubies(&mut self, cubicle_a: u8, cubicle_b: u8, cubicle_c: u8, cubicle_d: u8) {
+    pub const fn swap_four_cubies(
+        &mut self,
+        cubicle_a: u8,
+        cubicle_b: u8,
+        cubicle_c: u8,
+        cubicle_d: u8,
+    ) {
         self.permutation
             .swap(cubicle_a as usize, cubicle_b as usize);
         self.permutation
@@ -36,15 +57,26 @@ impl Permutation {
             .swap(cubicle_a as usize, cubicle_d as usize);
     }
 
-    pub fn swap_two_cubies(&mut self, cubicle_a: u8, cubicle_b: u8) {
+    pub const fn swap_two_cubies(&mut self, cubicle_a: u8, cubicle_b: u8) {
         self.permutation
             .swap(cubicle_a as usize, cubicle_b as usize);
     }
 
+    /// Moves the cubie in cubicle `sources[k]` to cubicle `targets[k]`, for every `k`.
+    ///
+    /// Cubicles outside of `targets` are left untouched.
+    #[inline]
+    pub fn cycle<const K: usize>(&mut self, targets: &[u8; K], sources: &[u8; K]) {
+        let cubies = sources.map(|cubicle| self.permutation[cubicle as usize]);
+        for (&cubicle, cubie) in targets.iter().zip(cubies) {
+            self.permutation[cubicle as usize] = cubie;
+        }
+    }
+
     pub fn parity(&self) -> bool {
         let mut p = true;
-        for i in 0..self.permutation.len() {
-            for j in (i + 1)..self.permutation.len() {
+        for i in 0..N {
+            for j in (i + 1)..N {
                 if self.permutation[i] > self.permutation[j] {
                     p = !p;
                 }
@@ -53,42 +85,93 @@ impl Permutation {
         p
     }
 
-    pub fn cubie_in_cubicle(&self, idx: u8) -> u8 {
+    pub const fn cubie_in_cubicle(&self, idx: u8) -> u8 {
         self.permutation[idx as usize]
     }
 
+    /// Get the cubie in every cubicle without copying.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.permutation
+    }
+
     /// Get a reference to the permutation's permutation.
     #[must_use]
     pub fn permutation(&self) -> Vec<u8> {
         self.permutation.to_vec()
     }
 
+    /// Checks that `permutation` is a valid permutation of the same size as this one.
+    ///
+    /// Every cubie index must be below the number of cubies and appear exactly once.
+    pub fn validate(&self, permutation: &[u8]) -> Result<(), CubeError> {
+        if permutation.len() != N {
+            return Err(CubeError::InvalidStateLength(N as u8, permutation.len()));
+        }
+
+        let mut seen = [false; N];
+        for &cubie in permutation {
+            if cubie as usize >= N {
+                return Err(CubeError::CubieOutOfRange(cubie, N as u8));
+            }
+            if seen[cubie as usize] {
+                return Err(CubeError::DuplicateCubie(cubie));
+            }
+            seen[cubie as usize] = true;
+        }
+        Ok(())
+    }
+
+    /// Splits the permutation into disjoint cycles of cubicles, including cubies left in place.
+    ///
+    /// The cubie in each cubicle of a cycle belongs in the next cubicle of the cycle. Cycles
+    /// start at their smallest cubicle and are ordered by it.
+    pub fn cycles(&self) -> Vec<Vec<u8>> {
+        let mut visited = [false; N];
+        let mut cycles = Vec::new();
+        for start in 0..N {
+            if visited[start] {
+                continue;
+            }
+            let mut cycle = Vec::new();
+            let mut cubicle = start;
+            while !visited[cubicle] {
+                visited[cubicle] = true;
+                cycle.push(cubicle as u8);
+                cubicle = self.permutation[cubicle] as usize;
+            }
+            cycles.push(cycle);
+        }
+        cycles
+    }
+
     /// Set the permutation's permutation.
+    ///
+    /// Returns an error, leaving the permutation untouched, if `permutation` is not a valid
+    /// permutation of the cubies.
     pub fn set_permutation(&mut self, permutation: Vec<u8>) -> Result<(), CubeError> {
-        if permutation.len() != self.permutation.len() {
-            return Err(CubeError::InvalidState);
-        }
-        self.permutation = permutation;
+        self.validate(&permutation)?;
+        self.permutation.copy_from_slice(&permutation);
         Ok(())
     }
+
+    /// Set the permutation's permutation without validating it.
+    ///
+    /// The caller must ensure `permutation` passes [`Permutation::validate`], otherwise later
+    /// turns may panic or silently corrupt the cube.
+    pub fn set_permutation_unchecked(&mut self, permutation: Vec<u8>) {
+        self.permutation.copy_from_slice(&permutation);
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod tests {
     use super::*;
-    use crate::cubies::{NUM_CORNERS, NUM_EDGES};
-
-    impl Permutation {
-        pub fn new(size: u8) -> Permutation {
-            Permutation {
-                permutation: (0..size).collect(),
-            }
-        }
-    }
+    use crate::cubies::NUM_CORNERS;
 
     #[test]
     fn swap_four_corners_sanity_test() {
-        let mut permutation = Permutation::new(NUM_CORNERS);
+        let mut permutation = Permutation::corner();
 
         permutation.swap_four_cubies(1, 5, 6, 2);
         permutation.swap_four_cubies(1, 2, 6, 5);
@@ -100,7 +183,7 @@ mod tests {
     }
     #[test]
     fn swap_four_corners_test() {
-        let mut permutation = Permutation::new(NUM_CORNERS);
+        let mut permutation = Permutation::corner();
 
         permutation.swap_four_cubies(1, 5, 6, 2);
 
@@ -112,7 +195,7 @@ mod tests {
 
     #[test]
     fn swap_four_corners_twice_test() {
-        let mut permutation = Permutation::new(NUM_CORNERS);
+        let mut permutation = Permutation::corner();
 
         permutation.swap_four_cubies(2, 6, 7, 3); // F turn
         permutation.swap_four_cubies(3, 0, 4, 7); // L' turn
@@ -124,7 +207,7 @@ mod tests {
 
     #[test]
     fn swap_two_corner_sanity_test() {
-        let mut permutation = Permutation::new(NUM_CORNERS);
+        let mut permutation = Permutation::corner();
 
         permutation.swap_two_cubies(0, 1);
         permutation.swap_two_cubies(0, 1);
@@ -137,7 +220,7 @@ mod tests {
 
     #[test]
     fn swap_two_corner_test() {
-        let mut permutation = Permutation::new(NUM_CORNERS);
+        let mut permutation = Permutation::corner();
 
         permutation.swap_two_cubies(0, 1);
 
@@ -149,7 +232,7 @@ mod tests {
 
     #[test]
     fn swap_four_edges_sanity_test() {
-        let mut permutation = Permutation::new(NUM_EDGES);
+        let mut permutation = Permutation::edge();
 
         permutation.swap_four_cubies(1, 5, 6, 2); // R turn
         permutation.swap_four_cubies(1, 2, 6, 5); // R' turn
@@ -163,7 +246,7 @@ mod tests {
 
     #[test]
     fn swap_four_edges_test() {
-        let mut permutation = Permutation::new(NUM_EDGES);
+        let mut permutation = Permutation::edge();
 
         permutation.swap_four_cubies(0, 1, 2, 3); // R turn
 
@@ -176,7 +259,7 @@ mod tests {
 
     #[test]
     fn swap_four_edges_twice_test() {
-        let mut permutation = Permutation::new(NUM_EDGES);
+        let mut permutation = Permutation::edge();
 
         permutation.swap_four_cubies(0, 1, 2, 3); // U turn
         permutation.swap_four_cubies(1, 5, 9, 6); // R turn
@@ -190,7 +273,7 @@ mod tests {
 
     #[test]
     fn swap_two_edges_sanity_test() {
-        let mut permutation = Permutation::new(NUM_EDGES);
+        let mut permutation = Permutation::edge();
 
         permutation.swap_two_cubies(0, 1);
         permutation.swap_two_cubies(0, 1);
@@ -204,7 +287,7 @@ mod tests {
 
     #[test]
     fn swap_two_edges_test() {
-        let mut permutation = Permutation::new(NUM_EDGES);
+        let mut permutation = Permutation::edge();
 
         permutation.swap_two_cubies(0, 1);
 
@@ -215,10 +298,50 @@ mod tests {
         assert_eq!(permutation, swapped_permutation);
     }
 
+    #[test]
+    fn set_permutation_test() {
+        let mut permutation = Permutation::corner();
+
+        assert_eq!(
+            permutation.set_permutation(vec![0, 1, 2, 3, 4, 5, 6, 6]),
+            Err(CubeError::DuplicateCubie(6))
+        );
+        assert_eq!(
+            permutation.set_permutation(vec![0, 1, 2, 3, 4, 5, 6, 9]),
+            Err(CubeError::CubieOutOfRange(9, NUM_CORNERS))
+        );
+        assert_eq!(
+            permutation.set_permutation(vec![0, 1, 2]),
+            Err(CubeError::InvalidStateLength(NUM_CORNERS, 3))
+        );
+        assert_eq!(permutation, Permutation::corner());
+
+        permutation
+            .set_permutation(vec![7, 6, 5, 4, 3, 2, 1, 0])
+            .unwrap();
+        assert_eq!(
+            permutation,
+            Permutation::new_with_permutation(&[7, 6, 5, 4, 3, 2, 1, 0])
+        );
+    }
+
+    #[test]
+    fn cycle_test() {
+        let mut permutation = Permutation::corner();
+        permutation.cycle(&[2, 6, 7, 3], &[3, 2, 6, 7]); // F turn
+        permutation.cycle(&[3, 0, 4, 7], &[7, 3, 0, 4]); // L' turn
+
+        let mut swapped_permutation = Permutation::corner();
+        swapped_permutation.swap_four_cubies(2, 6, 7, 3);
+        swapped_permutation.swap_four_cubies(3, 0, 4, 7);
+
+        assert_eq!(permutation, swapped_permutation);
+    }
+
     #[test]
     fn parity_test() {
-        let mut edge_permutation = Permutation::new(NUM_EDGES);
-        let mut corner_permutation = Permutation::new(NUM_CORNERS);
+        let mut edge_permutation = Permutation::edge();
+        let mut corner_permutation = Permutation::corner();
 
         // U turn
         corner_permutation.swap_four_cubies(0, 1, 2, 3);
@@ -232,4 +355,17 @@ mod tests {
 
         assert_eq!(corner_permutation.parity(), edge_permutation.parity());
     }
+
+    #[test]
+    fn cycles_test() {
+        let mut permutation = Permutation::corner();
+        assert_eq!(permutation.cycles().len(), NUM_CORNERS as usize);
+
+        permutation.swap_four_cubies(1, 5, 6, 2);
+        permutation.swap_two_cubies(0, 3);
+        assert_eq!(
+            permutation.cycles(),
+            vec![vec![0, 3], vec![1, 2, 6, 5], vec![4], vec![7]]
+        );
+    }
 }
@@ -0,0 +1,492 @@
+//! The 2x2x2 pocket cube, small enough to keep the distance of every state from solved.
+//!
+//! A [`PocketCube`] tracks the corners with the same permutation, orientation and turn table
+//! as the 3x3x3 [`Cube`](crate::Cube). The down-back-left corner never moves: only the R, F
+//! and U faces are turned, which reaches every state of the pocket cube up to a whole cube
+//! rotation. That leaves `7! * 3^6 = 3674160` states, each with a coordinate below
+//! [`NUM_STATES`].
+//!
+//! The distances of all states are found by a breadth-first search the first time they are
+//! needed, once for each turn metric, and give the optimal solver, see [`PocketCube::solve`].
+
+use std::sync::OnceLock;
+
+use rand::distributions::{Distribution, Uniform};
+use rand::Rng;
+
+use crate::{
+    bfs::{
+        distance_table, factorial, rank_orientation, rank_permutation, unrank_orientation,
+        unrank_permutation,
+    },
+    cubies::{NUM_CORNERS, NUM_CORNER_ORIENTATION},
+    errors::CubeError,
+    moves::{MetricKind, Turn},
+    orientation::{CornerOrientation, Orientation},
+    permutation::{CornerPermutation, Permutation},
+    turn_table::{CubieTurn, TURN_TABLE},
+};
+
+/// Turns of the pocket cube: quarter turns first, then half turns.
+pub const TURNS: [Turn; 9] = [
+    Turn::R,
+    Turn::F,
+    Turn::U,
+    Turn::R_,
+    Turn::F_,
+    Turn::U_,
+    Turn::R2,
+    Turn::F2,
+    Turn::U2,
+];
+
+/// Number of states of the pocket cube.
+pub const NUM_STATES: u32 = (factorial(NUM_MOVING) * NUM_ORIENTATIONS as u64) as u32;
+
+/// Size of the one-hot representation, see [`PocketCube::representation`].
+pub const POCKET_REPRESENTATION_SIZE: usize = NUM_MOVING as usize * NUM_CUBIE_STATES;
+
+/// Cubicle of the down-back-left corner, which is never moved.
+const FIXED_CORNER: u8 = 4;
+
+/// Cubicles of the corners that move, in coordinate order.
+const MOVING_CORNERS: [u8; NUM_MOVING as usize] = [0, 1, 2, 3, 5, 6, 7];
+
+const NUM_MOVING: u8 = NUM_CORNERS - 1;
+
+/// Number of orientations of the moving corners, the last one following from the others.
+const NUM_ORIENTATIONS: u32 = (NUM_CORNER_ORIENTATION as u32).pow(NUM_MOVING as u32 - 1);
+
+/// Number of cubicle and orientation pairs of a moving corner.
+const NUM_CUBIE_STATES: usize = NUM_MOVING as usize * NUM_CORNER_ORIENTATION as usize;
+
+/// Number of twists in a turn metric.
+fn num_twists(turn_metric: MetricKind) -> usize {
+    match turn_metric {
+        MetricKind::HalfTurnMetric => TURNS.len(),
+        MetricKind::QuarterTurnMetric => 6,
+    }
+}
+
+/// Position of a moving cubie among the moving corners.
+fn moving_index(cubie: u8) -> u8 {
+    if cubie > FIXED_CORNER {
+        cubie - 1
+    } else {
+        cubie
+    }
+}
+
+/// A 2x2x2 cube, turned by its R, F and U faces.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::pocket::PocketCube;
+/// use rubikscube::{MetricKind, Turn};
+///
+/// let mut cube = PocketCube::new(MetricKind::HalfTurnMetric);
+/// cube.turn(Turn::R).unwrap();
+/// cube.turn(Turn::U).unwrap();
+///
+/// assert_eq!(cube.distance(), 2);
+/// assert_eq!(cube.solve(), vec![Turn::U_, Turn::R_]);
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PocketCube {
+    corner_orientation: CornerOrientation,
+    corner_permutation: CornerPermutation,
+    turn_metric: MetricKind,
+}
+
+impl PocketCube {
+    /// Returns a solved pocket cube turned in `turn_metric`.
+    pub fn new(turn_metric: MetricKind) -> PocketCube {
+        PocketCube {
+            corner_orientation: Orientation::corner(),
+            corner_permutation: Permutation::corner(),
+            turn_metric,
+        }
+    }
+
+    /// Returns the turn metric of the cube.
+    pub fn turn_metric(&self) -> MetricKind {
+        self.turn_metric
+    }
+
+    /// Performs the specified turn on the cube.
+    ///
+    /// Returns `CubeError::UnsupportedTurn` if `turn` is not one of `TURNS`, such as a turn of
+    /// the L, B or D faces, or is a half turn in the quarter turn metric.
+    pub fn turn(&mut self, turn: Turn) -> Result<(), CubeError> {
+        let twist = TURNS[..num_twists(self.turn_metric)]
+            .iter()
+            .position(|&t| t == turn)
+            .ok_or(CubeError::UnsupportedTurn(turn))?;
+        self.twist(twist);
+        Ok(())
+    }
+
+    /// Performs the turn `TURNS[twist]`.
+    fn twist(&mut self, twist: usize) {
+        let CubieTurn { corners, .. } = &TURN_TABLE[TURNS[twist] as usize];
+        self.corner_orientation
+            .cycle(&corners.targets, &corners.sources, &corners.twists);
+        self.corner_permutation
+            .cycle(&corners.targets, &corners.sources);
+    }
+
+    /// Scrambles the cube with `num_turns` turns sampled from `rng` and returns them.
+    pub fn scramble_with_rng<R: Rng + ?Sized>(&mut self, num_turns: u32, rng: &mut R) -> Vec<Turn> {
+        let between = Uniform::from(0..num_twists(self.turn_metric));
+        (0..num_turns)
+            .map(|_| {
+                let twist = between.sample(rng);
+                self.twist(twist);
+                TURNS[twist]
+            })
+            .collect()
+    }
+
+    /// Scrambles the cube with `num_turns` random turns and returns them.
+    pub fn scramble(&mut self, num_turns: u32) -> Vec<Turn> {
+        self.scramble_with_rng(num_turns, &mut rand::thread_rng())
+    }
+
+    /// Returns true if the cube is solved.
+    pub fn solved(&self) -> bool {
+        self.coordinate() == 0
+    }
+
+    /// Returns the coordinate of the state, below `NUM_STATES`, 0 for the solved cube.
+    ///
+    /// The coordinate is the rank of the permutation of the moving corners, followed by the
+    /// orientations of the last six of them as base 3 digits.
+    pub fn coordinate(&self) -> u32 {
+        let permutation = self.corner_permutation.as_slice();
+        let orientation = self.corner_orientation.as_slice();
+        let cubies = MOVING_CORNERS.map(|cubicle| moving_index(permutation[cubicle as usize]));
+        let twists = MOVING_CORNERS.map(|cubicle| orientation[cubicle as usize]);
+        let permutation_rank = rank_permutation(&cubies) as u32;
+        let orientation_rank = rank_orientation::<NUM_CORNER_ORIENTATION>(&twists[1..]) as u32;
+        permutation_rank * NUM_ORIENTATIONS + orientation_rank
+    }
+
+    /// Returns the cube with the state of `coordinate`, the inverse of `coordinate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `coordinate` is not below `NUM_STATES`.
+    pub fn from_coordinate(coordinate: u32, turn_metric: MetricKind) -> PocketCube {
+        assert!(
+            coordinate < NUM_STATES,
+            "coordinate {} out of range",
+            coordinate
+        );
+        let mut cubies = [0; NUM_MOVING as usize];
+        let mut twists = [0; NUM_MOVING as usize];
+        unrank_permutation((coordinate / NUM_ORIENTATIONS) as u64, &mut cubies);
+        unrank_orientation::<NUM_CORNER_ORIENTATION>(
+            (coordinate % NUM_ORIENTATIONS) as u64,
+            &mut twists[1..],
+        );
+        // the twists of all corners add up to a multiple of 3
+        let twist = twists[1..].iter().sum::<u8>() % NUM_CORNER_ORIENTATION;
+        twists[0] = (NUM_CORNER_ORIENTATION - twist) % NUM_CORNER_ORIENTATION;
+
+        let mut permutation = [FIXED_CORNER; NUM_CORNERS as usize];
+        let mut orientation = [0; NUM_CORNERS as usize];
+        for (i, &cubicle) in MOVING_CORNERS.iter().enumerate() {
+            permutation[cubicle as usize] = MOVING_CORNERS[cubies[i] as usize];
+            orientation[cubicle as usize] = twists[i];
+        }
+        PocketCube {
+            corner_orientation: Orientation::new_with_orientation(&orientation),
+            corner_permutation: Permutation::new_with_permutation(&permutation),
+            turn_metric,
+        }
+    }
+
+    /// Returns the representation of the cube as a one-hot array.
+    ///
+    /// Each of the 7 moving corners has a block of 21 values, one for each cubicle it can be
+    /// in and each of its 3 orientations there. The fixed corner is left out.
+    pub fn representation(&self) -> [bool; POCKET_REPRESENTATION_SIZE] {
+        let mut representation = [false; POCKET_REPRESENTATION_SIZE];
+        let permutation = self.corner_permutation.as_slice();
+        let orientation = self.corner_orientation.as_slice();
+        for (i, &cubicle) in MOVING_CORNERS.iter().enumerate() {
+            let cubie = moving_index(permutation[cubicle as usize]) as usize;
+            let state =
+                i * NUM_CORNER_ORIENTATION as usize + orientation[cubicle as usize] as usize;
+            representation[cubie * NUM_CUBIE_STATES + state] = true;
+        }
+        representation
+    }
+
+    /// Recovers a cube from its `representation`.
+    ///
+    /// Every block of 21 values must have exactly one bit set, otherwise
+    /// `CubeError::InvalidOneHot` is returned with the moving corner. Two corners in the same
+    /// cubicle give `CubeError::DuplicateCubicle` and twists that do not add up to a multiple
+    /// of 3 give `CubeError::InvalidState`.
+    pub fn from_representation(
+        representation: &[bool; POCKET_REPRESENTATION_SIZE],
+        turn_metric: MetricKind,
+    ) -> Result<PocketCube, CubeError> {
+        let mut permutation = [None; NUM_CORNERS as usize];
+        let mut orientation = [0; NUM_CORNERS as usize];
+        permutation[FIXED_CORNER as usize] = Some(FIXED_CORNER);
+        for (cubie, block) in representation.chunks_exact(NUM_CUBIE_STATES).enumerate() {
+            let set: Vec<usize> = (0..block.len()).filter(|&idx| block[idx]).collect();
+            if set.len() != 1 {
+                return Err(CubeError::InvalidOneHot(cubie, set.len()));
+            }
+            let cubicle = MOVING_CORNERS[set[0] / NUM_CORNER_ORIENTATION as usize];
+            if permutation[cubicle as usize]
+                .replace(MOVING_CORNERS[cubie])
+                .is_some()
+            {
+                return Err(CubeError::DuplicateCubicle(cubicle));
+            }
+            orientation[cubicle as usize] = (set[0] % NUM_CORNER_ORIENTATION as usize) as u8;
+        }
+        if orientation.iter().sum::<u8>() % NUM_CORNER_ORIENTATION != 0 {
+            return Err(CubeError::InvalidState);
+        }
+
+        Ok(PocketCube {
+            corner_orientation: Orientation::new_with_orientation(&orientation),
+            corner_permutation: Permutation::new_with_permutation(&permutation.map(Option::unwrap)),
+            turn_metric,
+        })
+    }
+
+    /// Returns the number of turns of an optimal solution, in the cube's turn metric.
+    pub fn distance(&self) -> u8 {
+        distances(self.turn_metric)[self.coordinate() as usize]
+    }
+
+    /// Returns an optimal solution, in the cube's turn metric.
+    ///
+    /// Each turn is one that brings the cube a turn closer to solved, so the solution is
+    /// read from the distance table without any search.
+    pub fn solve(&self) -> Vec<Turn> {
+        let tables = move_tables();
+        let distances = distances(self.turn_metric);
+        let mut coordinate = self.coordinate();
+        let mut solution = Vec::with_capacity(distances[coordinate as usize] as usize);
+        while coordinate != 0 {
+            let distance = distances[coordinate as usize];
+            let (twist, next) = (0..num_twists(self.turn_metric))
+                .map(|twist| (twist, tables.turn(coordinate, twist)))
+                .find(|&(_, next)| distances[next as usize] < distance)
+                .unwrap();
+            solution.push(TURNS[twist]);
+            coordinate = next;
+        }
+        solution
+    }
+}
+
+/// Coordinates of the permutations and orientations reached by every twist.
+struct MoveTables {
+    permutation: Vec<[u16; TURNS.len()]>,
+    orientation: Vec<[u16; TURNS.len()]>,
+}
+
+impl MoveTables {
+    /// Coordinate of the cube at `coordinate` after `TURNS[twist]`.
+    fn turn(&self, coordinate: u32, twist: usize) -> u32 {
+        let permutation = self.permutation[(coordinate / NUM_ORIENTATIONS) as usize][twist];
+        let orientation = self.orientation[(coordinate % NUM_ORIENTATIONS) as usize][twist];
+        permutation as u32 * NUM_ORIENTATIONS + orientation as u32
+    }
+}
+
+fn move_tables() -> &'static MoveTables {
+    static TABLES: OnceLock<MoveTables> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        // permutations and orientations turn independently, so each table turns the one
+        // component with the other solved
+        let table = |size: u32, coordinate: &dyn Fn(u32) -> u32| {
+            (0..size)
+                .map(|rank| {
+                    let cube =
+                        PocketCube::from_coordinate(coordinate(rank), MetricKind::HalfTurnMetric);
+                    std::array::from_fn(|twist| {
+                        let mut turned = cube;
+                        turned.twist(twist);
+                        turned.coordinate()
+                    })
+                })
+                .collect::<Vec<[u32; TURNS.len()]>>()
+        };
+        let permutation = table(factorial(NUM_MOVING) as u32, &|rank| {
+            rank * NUM_ORIENTATIONS
+        });
+        let orientation = table(NUM_ORIENTATIONS, &|rank| rank);
+        MoveTables {
+            permutation: permutation
+                .into_iter()
+                .map(|row| row.map(|coordinate| (coordinate / NUM_ORIENTATIONS) as u16))
+                .collect(),
+            orientation: orientation
+                .into_iter()
+                .map(|row| row.map(|coordinate| (coordinate % NUM_ORIENTATIONS) as u16))
+                .collect(),
+        }
+    })
+}
+
+/// Returns the distance from solved of every state, indexed by coordinate, in `turn_metric`.
+///
+/// The table is built by a breadth-first search from the solved state the first time it is
+/// needed for a turn metric.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::pocket::{distances, NUM_STATES};
+/// use rubikscube::MetricKind;
+///
+/// let table = distances(MetricKind::HalfTurnMetric);
+/// assert_eq!(table.len(), NUM_STATES as usize);
+/// assert_eq!(table.iter().max(), Some(&11));
+/// ```
+pub fn distances(turn_metric: MetricKind) -> &'static [u8] {
+    static HALF_TURN: OnceLock<Vec<u8>> = OnceLock::new();
+    static QUARTER_TURN: OnceLock<Vec<u8>> = OnceLock::new();
+    let table = match turn_metric {
+        MetricKind::HalfTurnMetric => &HALF_TURN,
+        MetricKind::QuarterTurnMetric => &QUARTER_TURN,
+    };
+    table.get_or_init(|| {
+        let tables = move_tables();
+        distance_table(
+            NUM_STATES as usize,
+            [0],
+            num_twists(turn_metric),
+            |coordinate, twist| tables.turn(coordinate as u32, twist) as usize,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distances, PocketCube, NUM_STATES, POCKET_REPRESENTATION_SIZE};
+    use crate::{Cube, CubeError, MetricKind, Turn};
+
+    #[test]
+    fn distances_test() {
+        let count = |metric| {
+            let mut counts = Vec::new();
+            for &distance in distances(metric) {
+                if counts.len() <= distance as usize {
+                    counts.resize(distance as usize + 1, 0);
+                }
+                counts[distance as usize] += 1;
+            }
+            counts
+        };
+        assert_eq!(
+            count(MetricKind::HalfTurnMetric),
+            [1, 9, 54, 321, 1847, 9992, 50136, 227536, 870072, 1887748, 623800, 2644]
+        );
+        assert_eq!(
+            count(MetricKind::QuarterTurnMetric),
+            [
+                1, 6, 27, 120, 534, 2256, 8969, 33058, 114149, 360508, 930588, 1350852, 782536,
+                90280, 276
+            ]
+        );
+    }
+
+    #[test]
+    fn solve_test() {
+        for metric in [MetricKind::HalfTurnMetric, MetricKind::QuarterTurnMetric] {
+            for _ in 0..20 {
+                let mut cube = PocketCube::new(metric);
+                let scramble = cube.scramble(30);
+
+                let solution = cube.solve();
+                assert_eq!(solution.len(), cube.distance() as usize);
+                assert!(solution.len() <= scramble.len());
+                for turn in solution {
+                    cube.turn(turn).unwrap();
+                }
+                assert!(cube.solved());
+            }
+        }
+    }
+
+    #[test]
+    fn matches_cube_test() {
+        // the corners of a 3x3x3 cube turned the same way
+        let mut pocket = PocketCube::new(MetricKind::HalfTurnMetric);
+        let mut cube = Cube::cube_htm();
+        for turn in pocket.scramble(20) {
+            cube.turn(turn as u8).unwrap();
+        }
+        let [_, co, _, cp] = cube.cubicles();
+        assert_eq!(pocket.corner_orientation.as_slice(), co);
+        assert_eq!(pocket.corner_permutation.as_slice(), cp);
+
+        assert_eq!(pocket.turn(Turn::U2), Ok(()));
+        assert_eq!(
+            pocket.turn(Turn::L),
+            Err(CubeError::UnsupportedTurn(Turn::L))
+        );
+        assert_eq!(
+            pocket.turn(Turn::D_),
+            Err(CubeError::UnsupportedTurn(Turn::D_))
+        );
+        let mut quarter = PocketCube::new(MetricKind::QuarterTurnMetric);
+        assert_eq!(quarter.turn(Turn::R_), Ok(()));
+        assert_eq!(
+            quarter.turn(Turn::R2),
+            Err(CubeError::UnsupportedTurn(Turn::R2))
+        );
+        assert_eq!(
+            CubeError::UnsupportedTurn(Turn::R2).to_string(),
+            "Unsupported turn, R2 is not a turn of the puzzle in its turn metric"
+        );
+    }
+
+    #[test]
+    fn coordinate_test() {
+        for coordinate in (0..NUM_STATES).step_by(997) {
+            let cube = PocketCube::from_coordinate(coordinate, MetricKind::HalfTurnMetric);
+            assert_eq!(cube.coordinate(), coordinate);
+        }
+        assert!(PocketCube::new(MetricKind::HalfTurnMetric).solved());
+    }
+
+    #[test]
+    fn representation_test() {
+        let mut cube = PocketCube::new(MetricKind::QuarterTurnMetric);
+        cube.scramble(20);
+        let mut representation = cube.representation();
+        assert_eq!(representation.iter().filter(|&&bit| bit).count(), 7);
+        assert_eq!(
+            PocketCube::from_representation(&representation, MetricKind::QuarterTurnMetric),
+            Ok(cube)
+        );
+
+        representation[..21].fill(false);
+        assert_eq!(
+            PocketCube::from_representation(&representation, MetricKind::QuarterTurnMetric),
+            Err(CubeError::InvalidOneHot(0, 0))
+        );
+
+        // the first corner twisted in place
+        let mut twisted = [false; POCKET_REPRESENTATION_SIZE];
+        for cubie in 0..7 {
+            twisted[cubie * 21 + cubie * 3 + usize::from(cubie == 0)] = true;
+        }
+        assert_eq!(
+            PocketCube::from_representation(&twisted, MetricKind::HalfTurnMetric),
+            Err(CubeError::InvalidState)
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Python bindings, built with the `python` feature.
+//!
+//! The extension module is named `rubikscube` and is built with [maturin] from the
+//! `pyproject.toml` at the root of the repository:
+//!
+//! ```text
+//! maturin build --release
+//! pip install target/wheels/rubikscube-*.whl
+//! ```
+//!
+//! [maturin]: https://www.maturin.rs
+
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2, PyArrayMethods, PyReadonlyArray1};
+use pyo3::exceptions::{PyIndexError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::{Cube, CubeBatch, CubeError, MetricKind, Turn, REPRESENTATION_SIZE};
+
+impl From<CubeError> for PyErr {
+    fn from(err: CubeError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// A turn given either as a `Turn` or as the index of its variant.
+#[derive(FromPyObject)]
+enum Twist {
+    Turn(Turn),
+    Index(u8),
+}
+
+impl From<Twist> for u8 {
+    fn from(twist: Twist) -> u8 {
+        match twist {
+            Twist::Turn(turn) => turn as u8,
+            Twist::Index(idx) => idx,
+        }
+    }
+}
+
+/// Python wrapper around [`Cube`].
+#[pyclass(name = "Cube", module = "rubikscube", eq)]
+#[derive(Clone, PartialEq)]
+struct PyCube {
+    cube: Cube,
+}
+
+#[pymethods]
+impl PyCube {
+    #[new]
+    #[pyo3(signature = (turn_metric = MetricKind::HalfTurnMetric))]
+    fn new(turn_metric: MetricKind) -> PyCube {
+        PyCube {
+            cube: Cube::new(turn_metric),
+        }
+    }
+
+    /// Creates a cube from a 6 x 3 x 3 nested list of facelet colors.
+    #[staticmethod]
+    #[pyo3(signature = (faces, turn_metric = MetricKind::HalfTurnMetric))]
+    fn from_array(faces: [[[String; 3]; 3]; 6], turn_metric: MetricKind) -> PyResult<PyCube> {
+        let faces = faces.each_ref().map(|face| {
+            face.each_ref()
+                .map(|row| row.each_ref().map(String::as_str))
+        });
+        Ok(PyCube {
+            cube: Cube::cube_from_array(&faces, turn_metric)?,
+        })
+    }
+
+    #[getter]
+    fn turn_metric(&self) -> MetricKind {
+        self.cube.turn_metric()
+    }
+
+    fn turn(&mut self, twist: Twist) -> PyResult<()> {
+        Ok(self.cube.turn(twist.into())?)
+    }
+
+    fn scramble(&mut self, num_turns: u32) {
+        self.cube.scramble(num_turns);
+    }
+
+    fn solved(&self) -> bool {
+        self.cube.solved()
+    }
+
+    fn is_solvable(&self) -> bool {
+        self.cube.is_solvable()
+    }
+
+    /// Returns the one-hot representation as a NumPy array of 480 booleans.
+    ///
+    /// The array is allocated by NumPy and the representation is written straight into its
+    /// buffer, without an intermediate copy.
+    fn representation<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray1<bool>>> {
+        let array = PyArray1::<bool>::zeros(py, REPRESENTATION_SIZE, false);
+        {
+            let mut buffer = array.readwrite();
+            self.cube.write_representation(buffer.as_slice_mut()?)?;
+        }
+        Ok(array)
+    }
+
+    /// Returns the edge orientation, corner orientation, edge permutation and corner
+    /// permutation, each indexed by cubie.
+    fn get_state(&self) -> [Vec<u8>; 4] {
+        self.cube.get_state()
+    }
+
+    fn set_state(
+        &mut self,
+        edge_orientation: Vec<u8>,
+        corner_orientation: Vec<u8>,
+        edge_permutation: Vec<u8>,
+        corner_permutation: Vec<u8>,
+    ) -> PyResult<()> {
+        Ok(self.cube.set_state(
+            edge_orientation,
+            corner_orientation,
+            edge_permutation,
+            corner_permutation,
+        )?)
+    }
+
+    fn copy(&self) -> PyCube {
+        self.clone()
+    }
+
+    fn __copy__(&self) -> PyCube {
+        self.clone()
+    }
+
+    fn __str__(&self) -> String {
+        self.cube.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Cube(turn_metric={})", self.cube.turn_metric())
+    }
+}
+
+/// Representations and solved flags returned by `CubeBatch.step`.
+type StepResult<'py> = (Bound<'py, PyArray2<f32>>, Bound<'py, PyArray1<bool>>);
+
+/// Python wrapper around [`CubeBatch`].
+///
+/// The batched methods release the GIL while turning and encoding the cubes.
+#[pyclass(name = "CubeBatch", module = "rubikscube")]
+struct PyCubeBatch {
+    batch: CubeBatch,
+}
+
+impl PyCubeBatch {
+    fn representation_array(&self) -> Array2<f32> {
+        let mut buffer = vec![0f32; self.batch.len() * REPRESENTATION_SIZE];
+        self.batch
+            .write_representation(&mut buffer)
+            .expect("buffer is sized for the batch");
+        Array2::from_shape_vec((self.batch.len(), REPRESENTATION_SIZE), buffer)
+            .expect("buffer is sized for the batch")
+    }
+
+    fn check_index(&self, idx: usize) -> PyResult<()> {
+        if idx < self.batch.len() {
+            Ok(())
+        } else {
+            Err(PyIndexError::new_err(format!(
+                "Invalid index, Expected index below {} got {}",
+                self.batch.len(),
+                idx
+            )))
+        }
+    }
+}
+
+#[pymethods]
+impl PyCubeBatch {
+    #[new]
+    #[pyo3(signature = (size, turn_metric = MetricKind::HalfTurnMetric))]
+    fn new(size: usize, turn_metric: MetricKind) -> PyCubeBatch {
+        PyCubeBatch {
+            batch: CubeBatch::new(size, turn_metric),
+        }
+    }
+
+    #[staticmethod]
+    fn from_cubes(cubes: Vec<PyCube>) -> PyResult<PyCubeBatch> {
+        let cubes: Vec<Cube> = cubes.into_iter().map(|cube| cube.cube).collect();
+        Ok(PyCubeBatch {
+            batch: CubeBatch::from_cubes(&cubes)?,
+        })
+    }
+
+    #[getter]
+    fn turn_metric(&self) -> MetricKind {
+        self.batch.turn_metric()
+    }
+
+    fn __len__(&self) -> usize {
+        self.batch.len()
+    }
+
+    fn get(&self, idx: usize) -> PyResult<PyCube> {
+        self.check_index(idx)?;
+        Ok(PyCube {
+            cube: self.batch.get(idx),
+        })
+    }
+
+    fn set(&mut self, idx: usize, cube: PyRef<'_, PyCube>) -> PyResult<()> {
+        self.check_index(idx)?;
+        self.batch.set(idx, &cube.cube);
+        Ok(())
+    }
+
+    /// Performs `twists[i]` on the `i`th cube.
+    fn turn(&mut self, py: Python<'_>, twists: PyReadonlyArray1<'_, u8>) -> PyResult<()> {
+        let twists = twists.as_array().to_vec();
+        let batch = &mut self.batch;
+        Ok(py.detach(|| batch.turn(&twists))?)
+    }
+
+    /// Performs `twists[i]` on the `i`th cube and returns the new `(len, 480)` float32
+    /// representations along with which cubes are solved.
+    fn step<'py>(
+        &mut self,
+        py: Python<'py>,
+        twists: PyReadonlyArray1<'_, u8>,
+    ) -> PyResult<StepResult<'py>> {
+        let twists = twists.as_array().to_vec();
+        let (representation, solved) = py.detach(|| -> Result<_, CubeError> {
+            self.batch.turn(&twists)?;
+            Ok((self.representation_array(), self.batch.solved()))
+        })?;
+        Ok((representation.into_pyarray(py), solved.into_pyarray(py)))
+    }
+
+    fn scramble(&mut self, py: Python<'_>, num_turns: u32) {
+        let batch = &mut self.batch;
+        py.detach(|| batch.scramble(num_turns));
+    }
+
+    /// Rescrambles every solved cube and returns how many were reset.
+    fn reset_solved(&mut self, py: Python<'_>, num_turns: u32) -> usize {
+        let batch = &mut self.batch;
+        py.detach(|| batch.reset_solved(num_turns))
+    }
+
+    fn solved<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<bool>> {
+        self.batch.solved().into_pyarray(py)
+    }
+
+    /// Returns the `(len, 480)` float32 one-hot representations of the cubes.
+    fn representation<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray2<f32>> {
+        py.detach(|| self.representation_array()).into_pyarray(py)
+    }
+}
+
+#[pyfunction]
+fn scramble(mut cube: PyRefMut<'_, PyCube>, num_turns: u32) {
+    cube.scramble(num_turns);
+}
+
+#[pyfunction]
+fn turn(mut cube: PyRefMut<'_, PyCube>, twist: Twist) -> PyResult<()> {
+    cube.turn(twist)
+}
+
+#[pyfunction]
+fn solved(cube: PyRef<'_, PyCube>) -> bool {
+    cube.solved()
+}
+
+#[pyfunction]
+fn is_solvable(cube: PyRef<'_, PyCube>) -> bool {
+    cube.is_solvable()
+}
+
+#[pymodule]
+fn rubikscube(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCube>()?;
+    m.add_class::<PyCubeBatch>()?;
+    m.add_class::<Turn>()?;
+    m.add_class::<MetricKind>()?;
+    m.add_function(wrap_pyfunction!(scramble, m)?)?;
+    m.add_function(wrap_pyfunction!(turn, m)?)?;
+    m.add_function(wrap_pyfunction!(solved, m)?)?;
+    m.add_function(wrap_pyfunction!(is_solvable, m)?)?;
+    m.add("REPRESENTATION_SIZE", REPRESENTATION_SIZE)?;
+    Ok(())
+}
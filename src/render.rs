@@ -0,0 +1,149 @@
+//! Renders a cube as an unfolded net, in the same layout as the `Display` implementation of
+//! [`Cube`]: the yellow face on top, the red, green, orange and blue faces in the middle row
+//! and the white face at the bottom.
+
+use crate::{cubies::Faces, Cube};
+
+/// Position of each face in the net, as (row, column) in units of faces.
+const NET_POSITIONS: [(usize, usize); 6] = [
+    (2, 1), // W
+    (0, 1), // Y
+    (1, 1), // G
+    (1, 3), // B
+    (1, 0), // R
+    (1, 2), // O
+];
+
+const NET_ROWS: usize = 9;
+const NET_COLUMNS: usize = 12;
+
+/// Size in pixels of a facelet in the SVG rendering.
+const FACELET_SIZE: usize = 30;
+
+/// Lays the facelets of the cube out on a 9 x 12 grid, `None` marking empty cells.
+fn net(cube: &Cube) -> [[Option<Faces>; NET_COLUMNS]; NET_ROWS] {
+    let mut grid = [[None; NET_COLUMNS]; NET_ROWS];
+    for (face, &(row, column)) in cube.faces().iter().zip(NET_POSITIONS.iter()) {
+        for (i, facelets) in face.iter().enumerate() {
+            for (j, &facelet) in facelets.iter().enumerate() {
+                grid[3 * row + i][3 * column + j] = Some(facelet);
+            }
+        }
+    }
+    grid
+}
+
+fn ansi_background(face: Faces) -> &'static str {
+    match face {
+        Faces::White => "\x1b[48;5;231m",
+        Faces::Yellow => "\x1b[48;5;226m",
+        Faces::Green => "\x1b[48;5;28m",
+        Faces::Blue => "\x1b[48;5;21m",
+        Faces::Red => "\x1b[48;5;160m",
+        Faces::Orange => "\x1b[48;5;208m",
+    }
+}
+
+fn svg_fill(face: Faces) -> &'static str {
+    match face {
+        Faces::White => "#ffffff",
+        Faces::Yellow => "#ffd500",
+        Faces::Green => "#009b48",
+        Faces::Blue => "#0046ad",
+        Faces::Red => "#b71234",
+        Faces::Orange => "#ff5800",
+    }
+}
+
+/// Renders the cube with ANSI background colors, two terminal cells per facelet.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{render, Cube};
+///
+/// let rendered = render::ansi(&Cube::cube_htm());
+/// assert_eq!(rendered.lines().count(), 9);
+/// ```
+pub fn ansi(cube: &Cube) -> String {
+    let mut rendered = String::new();
+    for row in net(cube).iter() {
+        let last = row.iter().rposition(Option::is_some).unwrap_or(0);
+        for cell in &row[..=last] {
+            match cell {
+                Some(face) => {
+                    rendered.push_str(ansi_background(*face));
+                    rendered.push_str("  ");
+                    rendered.push_str("\x1b[0m");
+                }
+                None => rendered.push_str("  "),
+            }
+        }
+        rendered.push('\n');
+    }
+    rendered
+}
+
+/// Renders the cube as a standalone SVG image.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{render, Cube};
+///
+/// let rendered = render::svg(&Cube::cube_htm());
+/// assert!(rendered.starts_with("<svg"));
+/// assert_eq!(rendered.matches("<rect").count(), 54);
+/// ```
+pub fn svg(cube: &Cube) -> String {
+    let mut rendered = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        NET_COLUMNS * FACELET_SIZE,
+        NET_ROWS * FACELET_SIZE,
+        NET_COLUMNS * FACELET_SIZE,
+        NET_ROWS * FACELET_SIZE
+    );
+    for (i, row) in net(cube).iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            if let Some(face) = cell {
+                rendered.push_str(&format!(
+                    "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"#000000\"/>\n",
+                    j * FACELET_SIZE,
+                    i * FACELET_SIZE,
+                    FACELET_SIZE,
+                    FACELET_SIZE,
+                    svg_fill(*face)
+                ));
+            }
+        }
+    }
+    rendered.push_str("</svg>\n");
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::net;
+    use crate::{cubies::Faces, Cube, Turn};
+
+    #[test]
+    fn net_matches_display_test() {
+        let mut cube = Cube::cube_htm();
+        cube.turn(Turn::R as u8).unwrap();
+        cube.turn(Turn::U as u8).unwrap();
+
+        let from_net: Vec<String> = net(&cube)
+            .iter()
+            .flatten()
+            .flatten()
+            .map(Faces::to_string)
+            .collect();
+        let from_display: Vec<String> = cube
+            .to_string()
+            .split_whitespace()
+            .map(str::to_string)
+            .collect();
+
+        assert_eq!(from_net, from_display);
+    }
+}
@@ -0,0 +1,215 @@
+//! Finds shortest solutions by iterative deepening search.
+//!
+//! The search is exhaustive, so it is only practical for cubes a few turns away from solved.
+
+use crate::{Cube, CubeError, Goal, Turn};
+
+/// Number of cubies moved by a single turn, of each kind.
+const CUBIES_PER_TURN: usize = 4;
+
+/// Lower bound on the number of turns needed to reach `goal`.
+///
+/// Every turn moves 4 corners and 4 edges, so at least a quarter of the unsolved cubies of
+/// each kind are fixed per turn.
+fn lower_bound(cube: &Cube, goal: &Goal) -> usize {
+    let [eo, co, ep, cp] = cube.cubicles();
+    let (edges, corners) = goal.unsolved(eo, co, ep, cp);
+
+    corners
+        .div_ceil(CUBIES_PER_TURN)
+        .max(edges.div_ceil(CUBIES_PER_TURN))
+}
+
+/// Face turned by a twist, the faces in the order L, R, F, B, U, D.
+fn face(twist: u8) -> u8 {
+    twist % 6
+}
+
+/// Number of clockwise quarter turns made by a twist.
+fn quarter_turns(twist: u8) -> u8 {
+    [1, 3, 2][twist as usize / 6]
+}
+
+/// Returns false if `twist` following `previous` can not be part of a shortest sequence
+/// made of `twists`, or is the same as another ordering of the same turns.
+fn allowed(twists: &[u8], previous: Option<u8>, twist: u8) -> bool {
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return true,
+    };
+    let (previous_face, twist_face) = (face(previous), face(twist));
+
+    // opposite faces commute, only search them in one order
+    if previous_face / 2 == twist_face / 2 && twist_face < previous_face {
+        return false;
+    }
+    if previous_face == twist_face {
+        // consecutive turns of a face are wasted if they cancel or merge into a single
+        // allowed turn, such as any two in the half turn metric or R R' in the quarter turn
+        // metric
+        return match (quarter_turns(previous) + quarter_turns(twist)) % 4 {
+            0 => false,
+            1 => !twists.contains(&twist_face),
+            2 => !twists.contains(&(twist_face + 12)),
+            _ => !twists.contains(&(twist_face + 6)),
+        };
+    }
+    true
+}
+
+fn search(cube: &Cube, goal: &Goal, twists: &[u8], depth: usize, path: &mut Vec<u8>) -> bool {
+    if depth == 0 {
+        return cube.matches(goal);
+    }
+    if lower_bound(cube, goal) > depth {
+        return false;
+    }
+
+    for &twist in twists {
+        if !allowed(twists, path.last().copied(), twist) {
+            continue;
+        }
+        let mut next = *cube;
+        next.turn(twist).unwrap();
+        path.push(twist);
+        if search(&next, goal, twists, depth - 1, path) {
+            return true;
+        }
+        path.pop();
+    }
+    false
+}
+
+/// Returns a shortest sequence of turns solving the cube, in the cube's turn metric, or
+/// `None` if it takes more than `max_depth` turns.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{solver, Cube, Turn};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.turn(Turn::R as u8).unwrap();
+/// cube.turn(Turn::U2 as u8).unwrap();
+///
+/// assert_eq!(solver::solve(&cube, 5), Some(vec![Turn::U2, Turn::R_]));
+/// assert_eq!(solver::solve(&cube, 1), None);
+/// ```
+pub fn solve(cube: &Cube, max_depth: usize) -> Option<Vec<Turn>> {
+    let turns: Vec<Turn> = (0..cube.turn_metric() as u8)
+        .map(|twist| Turn::from_u8(twist).unwrap())
+        .collect();
+    solve_goal(cube, &Goal::solved(), &turns, max_depth).unwrap()
+}
+
+/// Returns a shortest sequence of `turns` bringing the cube to `goal`, or `None` if it takes
+/// more than `max_depth` turns.
+///
+/// Searches by iterative deepening A*, bounded by the number of cubies the goal still needs.
+/// Returns `CubeError::InvalidTurn` if one of `turns` is not in the cube's turn metric.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{solver, Cube, Goal, Turn};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.turn(Turn::R as u8).unwrap();
+///
+/// // a second R flips the edges back, without restoring the corners
+/// let solution = solver::solve_goal(&cube, &Goal::edges_oriented(), &[Turn::U, Turn::R], 3);
+/// assert_eq!(solution, Ok(Some(vec![Turn::R])));
+/// ```
+pub fn solve_goal(
+    cube: &Cube,
+    goal: &Goal,
+    turns: &[Turn],
+    max_depth: usize,
+) -> Result<Option<Vec<Turn>>, CubeError> {
+    let twists: Vec<u8> = turns.iter().map(|&turn| turn as u8).collect();
+    if let Some(&twist) = twists
+        .iter()
+        .find(|&&twist| twist >= cube.turn_metric() as u8)
+    {
+        return Err(CubeError::InvalidTurn(twist, cube.turn_metric() as u8));
+    }
+
+    let mut path = Vec::with_capacity(max_depth);
+    Ok((0..=max_depth)
+        .find(|&depth| search(cube, goal, &twists, depth, &mut path))
+        .map(|_| {
+            path.iter()
+                .map(|&twist| Turn::from_u8(twist).unwrap())
+                .collect()
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, solve_goal};
+    use crate::{Cube, CubeError, Goal, MetricKind, Turn};
+
+    #[test]
+    fn solve_test() {
+        for metric in [MetricKind::HalfTurnMetric, MetricKind::QuarterTurnMetric] {
+            for _ in 0..20 {
+                let mut cube = Cube::new(metric);
+                cube.scramble(4);
+
+                let solution = solve(&cube, 4).unwrap();
+                assert!(solution.len() <= 4);
+                for turn in solution {
+                    cube.turn(turn as u8).unwrap();
+                }
+                assert!(cube.solved());
+            }
+        }
+    }
+
+    #[test]
+    fn quarter_turn_metric_test() {
+        let mut cube = Cube::cube_qtm();
+        cube.turn(Turn::F as u8).unwrap();
+        cube.turn(Turn::F as u8).unwrap();
+
+        assert_eq!(solve(&cube, 2).unwrap().len(), 2);
+        assert_eq!(solve(&Cube::cube_qtm(), 2), Some(vec![]));
+    }
+
+    #[test]
+    fn goal_test() {
+        let turns: Vec<Turn> = (0..18).map(|twist| Turn::from_u8(twist).unwrap()).collect();
+        for _ in 0..20 {
+            let mut cube = Cube::cube_htm();
+            cube.scramble(3);
+
+            let solution = solve_goal(&cube, &Goal::cross(), &turns, 3)
+                .unwrap()
+                .unwrap();
+            for turn in solution {
+                cube.turn(turn as u8).unwrap();
+            }
+            assert!(cube.matches(&Goal::cross()));
+        }
+    }
+
+    #[test]
+    fn restricted_turns_test() {
+        let mut cube = Cube::cube_htm();
+        cube.turn(Turn::R2 as u8).unwrap();
+
+        // without R2, two consecutive R turns are not wasted
+        assert_eq!(
+            solve_goal(&cube, &Goal::solved(), &[Turn::U, Turn::R], 2),
+            Ok(Some(vec![Turn::R, Turn::R]))
+        );
+        assert_eq!(
+            solve_goal(&cube, &Goal::solved(), &[Turn::U, Turn::F], 4),
+            Ok(None)
+        );
+        assert_eq!(
+            solve_goal(&Cube::cube_qtm(), &Goal::cross(), &[Turn::U2], 1),
+            Err(CubeError::InvalidTurn(Turn::U2 as u8, 12))
+        );
+    }
+}
@@ -0,0 +1,576 @@
+//! Membership testing in subgroups of the cube group with the Schreier–Sims algorithm.
+//!
+//! A [`Subgroup`] is generated by a set of move sequences, for example `[R]` and `[U]` for
+//! the `<R, U>` group. Cube states are handled as permutations of the 48 stickers of the
+//! corners and edges, and the algorithm builds a stabiliser chain of the generated group,
+//! from which membership, the order of the group and a factorisation of a member back into
+//! generators follow.
+//!
+//! Slice turns such as `M` are not turns of this crate, since its centers never move, so
+//! generators are sequences of face turns.
+
+use std::rc::Rc;
+
+use crate::{
+    cubies::{NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES, NUM_EDGE_ORIENTATION},
+    moves::Turn,
+    Cube,
+};
+
+/// Number of stickers of the corners and edges.
+const NUM_STICKERS: usize = 48;
+/// Index of the first edge sticker.
+const EDGE_STICKERS: usize = (NUM_CORNERS * NUM_CORNER_ORIENTATION) as usize;
+
+/// Longest factorisation expanded by [`Subgroup::factorise`], before simplification.
+pub const MAX_WORD_LENGTH: u64 = 1 << 20;
+
+type Permutation = [u8; NUM_STICKERS];
+
+const IDENTITY: Permutation = {
+    let mut identity = [0; NUM_STICKERS];
+    let mut sticker = 0;
+    while sticker < NUM_STICKERS {
+        identity[sticker] = sticker as u8;
+        sticker += 1;
+    }
+    identity
+};
+
+/// Returns the permutation applying `first` then `second`.
+fn compose(first: &Permutation, second: &Permutation) -> Permutation {
+    first.map(|sticker| second[sticker as usize])
+}
+
+fn invert(permutation: &Permutation) -> Permutation {
+    let mut inverse = [0; NUM_STICKERS];
+    for (sticker, &position) in permutation.iter().enumerate() {
+        inverse[position as usize] = sticker as u8;
+    }
+    inverse
+}
+
+/// Returns the position of every sticker of `cube`, indexed by the sticker's position in the
+/// solved cube.
+///
+/// Sticker `k` of corner cubie `c` is numbered `3 * c + k` and sticker `k` of edge cubie `e`
+/// is numbered `24 + 2 * e + k`, with positions numbered the same way by cubicle.
+fn sticker_permutation(cube: &Cube) -> Permutation {
+    let [eo, co, ep, cp] = cube.cubicles();
+    let mut permutation = [0; NUM_STICKERS];
+    let corners = NUM_CORNER_ORIENTATION as usize;
+    for (cubicle, (&cubie, &orientation)) in cp.iter().zip(co).enumerate() {
+        for sticker in 0..corners {
+            // the sticker shown at slot `j` of a cubicle is sticker `j + orientation`
+            let slot = (sticker + corners - orientation as usize) % corners;
+            permutation[corners * cubie as usize + sticker] = (corners * cubicle + slot) as u8;
+        }
+    }
+    let edges = NUM_EDGE_ORIENTATION as usize;
+    for (cubicle, (&cubie, &orientation)) in ep.iter().zip(eo).enumerate() {
+        for sticker in 0..edges {
+            let slot = (sticker + edges - orientation as usize) % edges;
+            permutation[EDGE_STICKERS + edges * cubie as usize + sticker] =
+                (EDGE_STICKERS + edges * cubicle + slot) as u8;
+        }
+    }
+    debug_assert_eq!(NUM_STICKERS, EDGE_STICKERS + edges * NUM_EDGES as usize);
+    permutation
+}
+
+/// A product of generators, shared between the elements built from it.
+#[derive(Debug)]
+enum Node {
+    Identity,
+    Generator(usize),
+    Inverse(Rc<Word>),
+    Product(Rc<Word>, Rc<Word>),
+}
+
+#[derive(Debug)]
+struct Word {
+    node: Node,
+    /// number of turns of the expanded word, saturating
+    length: u64,
+}
+
+impl Word {
+    fn new(node: Node, generators: &[Vec<Turn>]) -> Rc<Word> {
+        let length = match &node {
+            Node::Identity => 0,
+            Node::Generator(idx) => generators[*idx].len() as u64,
+            Node::Inverse(word) => word.length,
+            Node::Product(first, second) => first.length.saturating_add(second.length),
+        };
+        Rc::new(Word { node, length })
+    }
+
+    /// Appends the turns of the word, or of its inverse if `inverse` is set, to `turns`.
+    fn expand(word: &Rc<Word>, inverse: bool, generators: &[Vec<Turn>], turns: &mut Vec<Turn>) {
+        let mut stack = vec![(Rc::clone(word), inverse)];
+        while let Some((word, inverse)) = stack.pop() {
+            match &word.node {
+                Node::Identity => {}
+                Node::Generator(idx) if inverse => {
+                    turns.extend(generators[*idx].iter().rev().map(|turn| turn.inverse()))
+                }
+                Node::Generator(idx) => turns.extend(&generators[*idx]),
+                Node::Inverse(word) => stack.push((Rc::clone(word), !inverse)),
+                // the stack is last in first out, so the part expanded first is pushed last
+                Node::Product(first, second) if inverse => {
+                    stack.push((Rc::clone(first), true));
+                    stack.push((Rc::clone(second), true));
+                }
+                Node::Product(first, second) => {
+                    stack.push((Rc::clone(second), false));
+                    stack.push((Rc::clone(first), false));
+                }
+            }
+        }
+    }
+}
+
+/// A group element with the word of generators it was built from.
+#[derive(Debug, Clone)]
+struct Element {
+    permutation: Permutation,
+    word: Rc<Word>,
+}
+
+/// A level of the stabiliser chain.
+#[derive(Debug)]
+struct Level {
+    /// sticker fixed by the elements of the following levels
+    base: u8,
+    /// element mapping the base to each sticker of its orbit
+    transversal: Vec<Option<Element>>,
+}
+
+/// A subgroup of the cube group given by generating move sequences.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::subgroup::Subgroup;
+/// use rubikscube::{Cube, Turn};
+///
+/// let group = Subgroup::new(&[vec![Turn::R], vec![Turn::U]]);
+/// assert_eq!(group.order(), 73_483_200);
+///
+/// let mut cube = Cube::cube_htm();
+/// for turn in [Turn::R, Turn::U, Turn::R_, Turn::U_] {
+///     cube.turn(turn as u8).unwrap();
+/// }
+/// assert!(group.contains(&cube));
+///
+/// cube.turn(Turn::F as u8).unwrap();
+/// assert!(!group.contains(&cube));
+/// ```
+#[derive(Debug)]
+pub struct Subgroup {
+    generators: Vec<Vec<Turn>>,
+    /// strong generating set, the generators of level `i` are those fixing the bases of the
+    /// levels before it
+    strong: Vec<Element>,
+    levels: Vec<Level>,
+}
+
+impl Subgroup {
+    /// Builds the stabiliser chain of the group generated by the move sequences.
+    pub fn new(generators: &[Vec<Turn>]) -> Subgroup {
+        let mut group = Subgroup {
+            generators: generators.to_vec(),
+            strong: Vec::new(),
+            levels: Vec::new(),
+        };
+        for (idx, turns) in generators.iter().enumerate() {
+            let mut cube = Cube::cube_htm();
+            for &turn in turns {
+                cube.turn(turn as u8).unwrap();
+            }
+            let element = Element {
+                permutation: sticker_permutation(&cube),
+                word: group.word(Node::Generator(idx)),
+            };
+            if element.permutation != IDENTITY {
+                group.add_strong(element, 0);
+            }
+        }
+        group.complete();
+        group
+    }
+
+    fn word(&self, node: Node) -> Rc<Word> {
+        Word::new(node, &self.generators)
+    }
+
+    fn identity(&self) -> Element {
+        Element {
+            permutation: IDENTITY,
+            word: self.word(Node::Identity),
+        }
+    }
+
+    fn product(&self, first: &Element, second: &Element) -> Element {
+        Element {
+            permutation: compose(&first.permutation, &second.permutation),
+            word: self.word(Node::Product(
+                Rc::clone(&first.word),
+                Rc::clone(&second.word),
+            )),
+        }
+    }
+
+    fn inverse(&self, element: &Element) -> Element {
+        Element {
+            permutation: invert(&element.permutation),
+            word: self.word(Node::Inverse(Rc::clone(&element.word))),
+        }
+    }
+
+    /// Adds a strong generator fixing the bases of the levels before `level`, adding a base
+    /// if it fixes every base, and recomputes the orbits it belongs to.
+    fn add_strong(&mut self, element: Element, level: usize) {
+        if self
+            .levels
+            .iter()
+            .all(|level| element.permutation[level.base as usize] == level.base)
+        {
+            let base = (0..NUM_STICKERS)
+                .find(|&sticker| element.permutation[sticker] as usize != sticker)
+                .unwrap();
+            self.levels.push(Level {
+                base: base as u8,
+                transversal: Vec::new(),
+            });
+        }
+        self.strong.push(element);
+        for idx in level..self.levels.len() {
+            self.compute_orbit(idx);
+        }
+    }
+
+    /// Strong generators fixing the bases of the levels before `level`.
+    fn level_generators(&self, level: usize) -> Vec<Element> {
+        self.strong
+            .iter()
+            .filter(|element| {
+                self.levels[..level]
+                    .iter()
+                    .all(|level| element.permutation[level.base as usize] == level.base)
+            })
+            .cloned()
+            .collect()
+    }
+
+    fn compute_orbit(&mut self, level: usize) {
+        let generators = self.level_generators(level);
+        let base = self.levels[level].base as usize;
+        let mut transversal = vec![None; NUM_STICKERS];
+        transversal[base] = Some(self.identity());
+        let mut queue = vec![base];
+        while let Some(sticker) = queue.pop() {
+            for generator in &generators {
+                let image = generator.permutation[sticker] as usize;
+                if transversal[image].is_none() {
+                    let element = transversal[sticker].as_ref().unwrap();
+                    transversal[image] = Some(self.product(element, generator));
+                    queue.push(image);
+                }
+            }
+        }
+        self.levels[level].transversal = transversal;
+    }
+
+    /// Divides `element` by transversal elements from `level` on, until a level's orbit does
+    /// not contain the image of its base. Returns that level, or the number of levels if
+    /// every level was passed, with what is left of the element.
+    fn strip(&self, mut element: Element, level: usize) -> (usize, Element) {
+        for (idx, level) in self.levels.iter().enumerate().skip(level) {
+            let image = element.permutation[level.base as usize] as usize;
+            match &level.transversal[image] {
+                Some(transversal) => element = self.product(&element, &self.inverse(transversal)),
+                None => return (idx, element),
+            }
+        }
+        (self.levels.len(), element)
+    }
+
+    /// Adds strong generators until every Schreier generator strips to the identity, which
+    /// makes the levels a stabiliser chain of the group.
+    fn complete(&mut self) {
+        let mut level = self.levels.len();
+        while level > 0 {
+            let idx = level - 1;
+            match self.failing_schreier_generator(idx) {
+                Some((failed_level, residue)) => {
+                    self.add_strong(residue, idx + 1);
+                    // the levels below `idx` changed, so check them again from the deepest
+                    // level the residue reached
+                    level = (failed_level + 1).min(self.levels.len());
+                }
+                None => level -= 1,
+            }
+        }
+    }
+
+    /// Returns the first Schreier generator of `level` that does not strip to the identity
+    /// through the following levels, with the level it stopped at and its residue.
+    fn failing_schreier_generator(&self, level: usize) -> Option<(usize, Element)> {
+        let transversal = &self.levels[level].transversal;
+        for generator in self.level_generators(level) {
+            for (sticker, element) in transversal.iter().enumerate() {
+                let element = match element {
+                    Some(element) => element,
+                    None => continue,
+                };
+                let image = generator.permutation[sticker] as usize;
+                let back = self.inverse(transversal[image].as_ref().unwrap());
+                let schreier = self.product(&self.product(element, &generator), &back);
+
+                let (stopped, residue) = self.strip(schreier, level + 1);
+                if stopped < self.levels.len() || residue.permutation != IDENTITY {
+                    return Some((stopped, residue));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns true if the state of `cube` can be reached with the generators.
+    pub fn contains(&self, cube: &Cube) -> bool {
+        let element = Element {
+            permutation: sticker_permutation(cube),
+            word: self.word(Node::Identity),
+        };
+        let (stopped, residue) = self.strip(element, 0);
+        stopped == self.levels.len() && residue.permutation == IDENTITY
+    }
+
+    /// Returns the number of elements of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::subgroup::Subgroup;
+    /// use rubikscube::Turn;
+    ///
+    /// let faces = [Turn::L, Turn::R, Turn::F, Turn::B, Turn::U, Turn::D];
+    /// let generators: Vec<Vec<Turn>> = faces.iter().map(|&turn| vec![turn]).collect();
+    ///
+    /// assert_eq!(Subgroup::new(&generators).order(), 43_252_003_274_489_856_000);
+    /// ```
+    pub fn order(&self) -> u128 {
+        self.levels
+            .iter()
+            .map(|level| level.transversal.iter().flatten().count() as u128)
+            .product()
+    }
+
+    /// Returns a sequence of turns made of the generators leading from solved to `cube`, or
+    /// `None` if the cube is not in the group or the sequence has more than
+    /// `MAX_WORD_LENGTH` turns.
+    ///
+    /// Consecutive turns of the same face are merged, but the sequence is far from shortest.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::subgroup::Subgroup;
+    /// use rubikscube::{Cube, Turn};
+    ///
+    /// let group = Subgroup::new(&[vec![Turn::R2], vec![Turn::U2]]);
+    /// let mut cube = Cube::cube_htm();
+    /// cube.turn(Turn::R2 as u8).unwrap();
+    /// cube.turn(Turn::U2 as u8).unwrap();
+    ///
+    /// let turns = group.factorise(&cube).unwrap();
+    /// let mut other = Cube::cube_htm();
+    /// for turn in turns {
+    ///     other.turn(turn as u8).unwrap();
+    /// }
+    /// assert_eq!(other, cube);
+    /// ```
+    pub fn factorise(&self, cube: &Cube) -> Option<Vec<Turn>> {
+        let mut element = Element {
+            permutation: sticker_permutation(cube),
+            word: self.word(Node::Identity),
+        };
+        // the cube is the product of the transversal elements stripped, last one first
+        let mut factors = Vec::new();
+        for level in &self.levels {
+            let image = element.permutation[level.base as usize] as usize;
+            let transversal = level.transversal[image].as_ref()?;
+            element.permutation = compose(&element.permutation, &invert(&transversal.permutation));
+            factors.push(Rc::clone(&transversal.word));
+        }
+        if element.permutation != IDENTITY {
+            return None;
+        }
+
+        let length = factors
+            .iter()
+            .fold(0u64, |length, word| length.saturating_add(word.length));
+        if length > MAX_WORD_LENGTH {
+            return None;
+        }
+        let mut turns = Vec::with_capacity(length as usize);
+        for word in factors.iter().rev() {
+            Word::expand(word, false, &self.generators, &mut turns);
+        }
+        Some(merge_turns(&turns))
+    }
+}
+
+/// Merges consecutive turns of the same face, dropping those that cancel out.
+fn merge_turns(turns: &[Turn]) -> Vec<Turn> {
+    // quarter turns of each turn, clockwise
+    let quarters = |turn: Turn| [1, 3, 2][turn as usize / 6];
+    let mut merged: Vec<Turn> = Vec::with_capacity(turns.len());
+    for &turn in turns {
+        let face = turn as u8 % 6;
+        match merged.last() {
+            Some(&last) if last as u8 % 6 == face => {
+                merged.pop();
+                let kind = match (quarters(last) + quarters(turn)) % 4 {
+                    0 => continue,
+                    1 => 0,
+                    3 => 1,
+                    _ => 2,
+                };
+                merged.push(Turn::from_u8(face + 6 * kind).unwrap());
+            }
+            _ => merged.push(turn),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{merge_turns, Subgroup};
+    use crate::{Cube, Turn};
+
+    fn apply(cube: &mut Cube, turns: &[Turn]) {
+        for &turn in turns {
+            cube.turn(turn as u8).unwrap();
+        }
+    }
+
+    #[test]
+    fn order_test() {
+        let group = |generators: &[&[Turn]]| {
+            let generators: Vec<Vec<Turn>> = generators.iter().map(|g| g.to_vec()).collect();
+            Subgroup::new(&generators).order()
+        };
+        assert_eq!(group(&[]), 1);
+        assert_eq!(group(&[&[Turn::U]]), 4);
+        assert_eq!(group(&[&[Turn::R2], &[Turn::U2]]), 12);
+        assert_eq!(group(&[&[Turn::R, Turn::U]]), 105);
+        // the square group
+        assert_eq!(
+            group(&[
+                &[Turn::L2],
+                &[Turn::R2],
+                &[Turn::F2],
+                &[Turn::B2],
+                &[Turn::U2],
+                &[Turn::D2]
+            ]),
+            663_552
+        );
+    }
+
+    #[test]
+    fn contains_test() {
+        let group = Subgroup::new(&[vec![Turn::R], vec![Turn::U]]);
+        let generators = [Turn::R, Turn::R_, Turn::R2, Turn::U, Turn::U_, Turn::U2];
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let mut cube = Cube::cube_htm();
+            let turns: Vec<Turn> = (0..30)
+                .map(|_| generators[rng.gen_range(0..generators.len())])
+                .collect();
+            apply(&mut cube, &turns);
+            assert!(group.contains(&cube));
+        }
+
+        let mut scrambled = Cube::cube_htm();
+        scrambled.scramble(30);
+        let full = Subgroup::new(&[
+            vec![Turn::L],
+            vec![Turn::R],
+            vec![Turn::F],
+            vec![Turn::B],
+            vec![Turn::U],
+            vec![Turn::D],
+        ]);
+        assert!(full.contains(&scrambled));
+
+        // a flipped edge is not reachable with face turns
+        let [mut eo, co, ep, cp] = Cube::cube_htm().get_state();
+        eo[0] = 1;
+        let mut flipped = Cube::cube_htm();
+        flipped.set_state(eo, co, ep, cp).unwrap();
+        assert!(!full.contains(&flipped));
+    }
+
+    #[test]
+    fn factorise_test() {
+        let group = Subgroup::new(&[vec![Turn::R, Turn::U, Turn::R_, Turn::U_], vec![Turn::F2]]);
+        let mut cube = Cube::cube_htm();
+        apply(
+            &mut cube,
+            &[Turn::F2, Turn::R, Turn::U, Turn::R_, Turn::U_, Turn::F2],
+        );
+        apply(&mut cube, &[Turn::U, Turn::R, Turn::U_, Turn::R_]);
+
+        let turns = group.factorise(&cube).unwrap();
+        let mut other = Cube::cube_htm();
+        apply(&mut other, &turns);
+        assert_eq!(other, cube);
+
+        cube.turn(Turn::D as u8).unwrap();
+        assert_eq!(group.factorise(&cube), None);
+
+        let group = Subgroup::new(&[vec![Turn::R], vec![Turn::U]]);
+        let mut cube = Cube::cube_htm();
+        apply(
+            &mut cube,
+            &[
+                Turn::R,
+                Turn::U2,
+                Turn::R_,
+                Turn::U_,
+                Turn::R,
+                Turn::U_,
+                Turn::R2,
+            ],
+        );
+        let turns = group.factorise(&cube).unwrap();
+        assert!(turns
+            .iter()
+            .all(|&turn| turn as u8 % 6 == Turn::R as u8 || turn as u8 % 6 == Turn::U as u8));
+        let mut other = Cube::cube_htm();
+        apply(&mut other, &turns);
+        assert_eq!(other, cube);
+    }
+
+    #[test]
+    fn merge_turns_test() {
+        assert_eq!(
+            merge_turns(&[
+                Turn::R,
+                Turn::R,
+                Turn::U,
+                Turn::U_,
+                Turn::R,
+                Turn::F,
+                Turn::F2
+            ]),
+            vec![Turn::R_, Turn::F_]
+        );
+    }
+}
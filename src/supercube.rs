@@ -0,0 +1,266 @@
+//! Supercubes and picture cubes, whose centers show which way they are turned.
+//!
+//! A [`SuperCube`] is a [`Cube`] that also counts the quarter turns of each center. Every
+//! quarter turn twists one center by a quarter and is an odd permutation of the corners, so
+//! a solvable supercube has centers twisted by an odd number of quarter turns in total exactly
+//! when its corner permutation is odd. A cube whose cubies are solved can still have two
+//! centers twisted a quarter each, or one center twisted by half a turn.
+
+use std::convert::TryInto;
+
+use rand::Rng;
+
+use crate::{
+    cubies::{NUM_CORNERS, NUM_EDGES, REPRESENTATION_SIZE},
+    errors::CubeError,
+    moves::{MetricKind, Turn},
+    permutation::CornerPermutation,
+    symmetry::TURN_FACES,
+    Cube,
+};
+
+/// Number of centers, one on each face.
+const NUM_CENTERS: usize = 6;
+
+/// Number of orientations of a center, in quarter turns.
+const NUM_CENTER_ORIENTATION: u8 = 4;
+
+/// Size of the one-hot representation, see [`SuperCube::representation`].
+pub const SUPERCUBE_REPRESENTATION_SIZE: usize =
+    REPRESENTATION_SIZE + NUM_CENTERS * NUM_CENTER_ORIENTATION as usize;
+
+/// A 3x3x3 cube tracking the orientation of its centers.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::supercube::SuperCube;
+/// use rubikscube::{Faces, MetricKind, Turn};
+///
+/// // turns the up center by half a turn and leaves every cubie in place
+/// let mut cube = SuperCube::new(MetricKind::HalfTurnMetric);
+/// for turn in Turn::parse_sequence("U R L U2 R' L' U R L U2 R' L'").unwrap() {
+///     cube.turn(turn as u8).unwrap();
+/// }
+///
+/// assert!(cube.cube().solved());
+/// assert!(!cube.solved());
+/// assert_eq!(cube.center_twists()[Faces::Yellow as usize], 2);
+/// assert!(cube.is_solvable());
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SuperCube {
+    cube: Cube,
+    /// clockwise quarter turns of each center, indexed by [`Faces`](crate::Faces)
+    centers: [u8; NUM_CENTERS],
+}
+
+impl SuperCube {
+    /// Returns a solved supercube turned in `turn_metric`.
+    pub fn new(turn_metric: MetricKind) -> SuperCube {
+        SuperCube::from(Cube::new(turn_metric))
+    }
+
+    /// Returns the cube without its centers.
+    pub fn cube(&self) -> &Cube {
+        &self.cube
+    }
+
+    /// Returns the clockwise quarter turns of each center from its solved orientation,
+    /// indexed by [`Faces`](crate::Faces).
+    pub fn center_twists(&self) -> [u8; NUM_CENTERS] {
+        self.centers
+    }
+
+    /// Sets the clockwise quarter turns of each center, indexed by [`Faces`](crate::Faces).
+    ///
+    /// Returns `CubeError::InvalidOrientation` if a center is twisted by 4 or more quarter
+    /// turns. On error the cube is left unchanged.
+    pub fn set_center_twists(&mut self, centers: [u8; NUM_CENTERS]) -> Result<(), CubeError> {
+        if let Some(&twist) = centers
+            .iter()
+            .find(|&&twist| twist >= NUM_CENTER_ORIENTATION)
+        {
+            return Err(CubeError::InvalidOrientation(twist, NUM_CENTER_ORIENTATION));
+        }
+        self.centers = centers;
+        Ok(())
+    }
+
+    /// Performs the specified turn on the cube and twists the center of the turned face.
+    ///
+    /// Returns `CubeError::InvalidTurn` as `Cube::turn` does.
+    pub fn turn(&mut self, twist: u8) -> Result<(), CubeError> {
+        self.cube.turn(twist)?;
+        self.twist_center(twist);
+        Ok(())
+    }
+
+    fn twist_center(&mut self, twist: u8) {
+        let face = TURN_FACES[twist as usize % NUM_CENTERS] as usize;
+        // quarter, anti-clockwise and half turns
+        let quarter_turns = [1, 3, 2][twist as usize / NUM_CENTERS];
+        self.centers[face] = (self.centers[face] + quarter_turns) % NUM_CENTER_ORIENTATION;
+    }
+
+    /// Scrambles the cube with turns sampled from `rng` and returns the turns performed.
+    pub fn scramble_with_rng<R: Rng + ?Sized>(&mut self, num_turns: u32, rng: &mut R) -> Vec<Turn> {
+        let turns = self.cube.scramble_with_rng(num_turns, rng);
+        for &turn in &turns {
+            self.twist_center(turn as u8);
+        }
+        turns
+    }
+
+    /// Scrambles the cube with `num_turns` random turns and returns them.
+    pub fn scramble(&mut self, num_turns: u32) -> Vec<Turn> {
+        self.scramble_with_rng(num_turns, &mut rand::thread_rng())
+    }
+
+    /// Returns true if every cubie is solved and every center is in its solved orientation.
+    pub fn solved(&self) -> bool {
+        self.cube.solved() && self.centers.iter().all(|&twist| twist == 0)
+    }
+
+    /// Returns true if the centers are twisted by an odd number of quarter turns in total
+    /// exactly when the corner permutation is odd.
+    pub fn center_parity_matches(&self) -> bool {
+        let [_, _, _, corner_permutation] = self.cube.cubicles();
+        let even = CornerPermutation::new_with_permutation(corner_permutation).parity();
+        let quarter_turns: u8 = self.centers.iter().sum();
+        (quarter_turns % 2 == 1) != even
+    }
+
+    /// Returns true if the cubies are solvable, see `Cube::is_solvable`, and the center
+    /// twists match the corner permutation, see `center_parity_matches`.
+    pub fn is_solvable(&self) -> bool {
+        self.cube.is_solvable() && self.center_parity_matches()
+    }
+
+    /// Returns the representation of the cube as a one-hot array of size 504.
+    ///
+    /// The 480 values of `Cube::representation` are followed by a block of 4 values for each
+    /// center, in the order of [`Faces`](crate::Faces), one for each number of quarter turns
+    /// it is twisted.
+    pub fn representation(&self) -> [bool; SUPERCUBE_REPRESENTATION_SIZE] {
+        let mut representation = [false; SUPERCUBE_REPRESENTATION_SIZE];
+        representation[..REPRESENTATION_SIZE].copy_from_slice(&self.cube.representation());
+        for (face, &twist) in self.centers.iter().enumerate() {
+            let index = face * NUM_CENTER_ORIENTATION as usize + twist as usize;
+            representation[REPRESENTATION_SIZE + index] = true;
+        }
+        representation
+    }
+
+    /// Recovers a supercube from its `representation`.
+    ///
+    /// The cubies are read as by `Cube::from_representation`. A center block without exactly
+    /// one bit set gives `CubeError::InvalidOneHot` with the block, the centers numbered 20
+    /// to 25 after the cubies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::supercube::SuperCube;
+    /// use rubikscube::{CubeError, MetricKind};
+    ///
+    /// let mut cube = SuperCube::new(MetricKind::QuarterTurnMetric);
+    /// cube.scramble(20);
+    ///
+    /// let mut repr = cube.representation();
+    /// assert_eq!(SuperCube::from_representation(&repr, MetricKind::QuarterTurnMetric), Ok(cube));
+    ///
+    /// repr[480..484].fill(true);
+    /// assert_eq!(
+    ///     SuperCube::from_representation(&repr, MetricKind::QuarterTurnMetric),
+    ///     Err(CubeError::InvalidOneHot(20, 4))
+    /// );
+    /// ```
+    pub fn from_representation(
+        representation: &[bool; SUPERCUBE_REPRESENTATION_SIZE],
+        turn_metric: MetricKind,
+    ) -> Result<SuperCube, CubeError> {
+        let (cubies, centers) = representation.split_at(REPRESENTATION_SIZE);
+        let cube = Cube::from_representation(cubies.try_into().unwrap(), turn_metric)?;
+        let num_cubies = (NUM_CORNERS + NUM_EDGES) as usize;
+
+        let mut twists = [0; NUM_CENTERS];
+        for (face, block) in centers
+            .chunks_exact(NUM_CENTER_ORIENTATION as usize)
+            .enumerate()
+        {
+            let set: Vec<usize> = (0..block.len()).filter(|&idx| block[idx]).collect();
+            if set.len() != 1 {
+                return Err(CubeError::InvalidOneHot(num_cubies + face, set.len()));
+            }
+            twists[face] = set[0] as u8;
+        }
+        Ok(SuperCube {
+            cube,
+            centers: twists,
+        })
+    }
+}
+
+impl From<Cube> for SuperCube {
+    /// Returns the supercube of `cube` with every center in its solved orientation.
+    fn from(cube: Cube) -> SuperCube {
+        SuperCube {
+            cube,
+            centers: [0; NUM_CENTERS],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SuperCube;
+    use crate::{CubeError, Faces, MetricKind, Turn};
+
+    fn turned(moves: &str) -> SuperCube {
+        let mut cube = SuperCube::new(MetricKind::HalfTurnMetric);
+        for turn in Turn::parse_sequence(moves).unwrap() {
+            cube.turn(turn as u8).unwrap();
+        }
+        cube
+    }
+
+    #[test]
+    fn center_twist_test() {
+        let cube = turned("R U2 F' R'");
+        assert_eq!(cube.center_twists(), [0, 2, 3, 0, 0, 0]);
+        assert!(cube.is_solvable());
+        assert!(turned("U U U U").solved());
+
+        // R and U each turn a whole number of times
+        let cube = turned(&"R U R' U' ".repeat(6));
+        assert!(cube.solved());
+
+        let mut scrambled = SuperCube::new(MetricKind::QuarterTurnMetric);
+        scrambled.scramble(25);
+        assert!(scrambled.is_solvable());
+    }
+
+    #[test]
+    fn parity_test() {
+        let mut cube = SuperCube::new(MetricKind::HalfTurnMetric);
+        cube.set_center_twists([0, 1, 0, 0, 0, 0]).unwrap();
+        assert!(!cube.center_parity_matches());
+        assert!(!cube.is_solvable());
+
+        cube.set_center_twists([0, 1, 0, 0, 3, 0]).unwrap();
+        assert!(cube.is_solvable());
+        assert!(cube.cube().solved() && !cube.solved());
+
+        // a single quarter turn twists one center and permutes the corners oddly
+        let mut turned = turned("D");
+        assert_eq!(turned.center_twists()[Faces::White as usize], 1);
+        assert!(turned.is_solvable());
+        assert_eq!(
+            turned.set_center_twists([4, 0, 0, 0, 0, 0]),
+            Err(CubeError::InvalidOrientation(4, 4))
+        );
+        turned.set_center_twists([0; 6]).unwrap();
+        assert!(!turned.is_solvable());
+    }
+}
@@ -0,0 +1,490 @@
+//! The 48 symmetries of the cube, for augmenting training data.
+//!
+//! A symmetry is a rotation or reflection of the whole cube. Applying it to a cube
+//! conjugates the cube's state, and colors are relabelled so the solved cube maps to itself.
+//! The turn leading from a cube to the next is remapped to the matching turn of the
+//! symmetric cube, see [`Symmetry::apply_turn`], so every sample `(cube, turn)` yields 48
+//! equivalent samples.
+//!
+//! A rotation can also move the cubies without relabelling their colors, see
+//! [`Symmetry::rotate`], which turns the whole cube in hand while the frame of the centers
+//! stays fixed.
+
+use std::sync::OnceLock;
+
+use crate::{
+    cubies::{
+        Corner, Edge, Faces, CORNER_CUBIES, CORNER_FACELETS, EDGE_CUBIES, EDGE_FACELETS,
+        NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES, NUM_EDGE_ORIENTATION, NUM_FACELETS,
+        REPRESENTATION_SIZE,
+    },
+    moves::Turn,
+    Cube,
+};
+
+/// Number of symmetries of the cube, 24 rotations and 24 reflections.
+pub const NUM_SYMMETRIES: usize = 48;
+
+const NUM_FACES: usize = 6;
+const NUM_TURNS: usize = 18;
+
+pub(crate) type Vector = [i8; 3];
+type Matrix = [[i8; 3]; 3];
+
+/// Outward normal of each face, indexed by [`Faces`]. The x axis points to the orange face,
+/// the y axis to the yellow face and the z axis to the green face.
+pub(crate) const NORMALS: [Vector; NUM_FACES] = [
+    [0, -1, 0], // W
+    [0, 1, 0],  // Y
+    [0, 0, 1],  // G
+    [0, 0, -1], // B
+    [-1, 0, 0], // R
+    [1, 0, 0],  // O
+];
+
+/// Face turned by each of the turns L, R, F, B, U and D.
+pub(crate) const TURN_FACES: [Faces; NUM_FACES] = [
+    Faces::Red,
+    Faces::Orange,
+    Faces::Green,
+    Faces::Blue,
+    Faces::Yellow,
+    Faces::White,
+];
+
+fn transform(matrix: &Matrix, vector: Vector) -> Vector {
+    matrix.map(|row| row[0] * vector[0] + row[1] * vector[1] + row[2] * vector[2])
+}
+
+fn face_with_normal(normal: Vector) -> Faces {
+    let face = NORMALS.iter().position(|&other| other == normal).unwrap();
+    Faces::from_repr(face).unwrap()
+}
+
+/// Returns the position of every facelet, in the order of `Cube::facelets`, as the center of
+/// its cubicle and the normal of its face.
+pub(crate) fn facelet_positions() -> [(Vector, Vector); NUM_FACELETS] {
+    let mut positions = [([0; 3], [0; 3]); NUM_FACELETS];
+    let mut place = |facelets: &[(Faces, u8, u8)]| {
+        let mut center = [0; 3];
+        for &(face, _, _) in facelets {
+            for (axis, value) in NORMALS[face as usize].iter().enumerate() {
+                center[axis] += value;
+            }
+        }
+        for &(face, row, column) in facelets {
+            let index = 9 * face as usize + 3 * row as usize + column as usize;
+            positions[index] = (center, NORMALS[face as usize]);
+        }
+    };
+
+    for face in 0..NUM_FACES {
+        let face = Faces::from_repr(face).unwrap();
+        place(&[(face, 1, 1)]);
+    }
+    for &(a, b, c) in CORNER_FACELETS.iter() {
+        place(&[a, b, c]);
+    }
+    for &(a, b) in EDGE_FACELETS.iter() {
+        place(&[a, b]);
+    }
+    positions
+}
+
+fn facelet_index(&(face, row, column): &(Faces, u8, u8)) -> usize {
+    9 * face as usize + 3 * row as usize + column as usize
+}
+
+/// Where a symmetry takes the corners or the edges, `M` cubies with `N` facelets and
+/// orientations each.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct CubieTable<const N: usize, const M: usize> {
+    /// cubicle each cubicle is moved to, also the cubie each cubie is relabelled to
+    cubicles: [u8; M],
+    /// orientation of the relabelled cubie, indexed by cubicle, cubie and orientation
+    orientations: [[[u8; N]; M]; M],
+}
+
+impl<const N: usize, const M: usize> CubieTable<N, M> {
+    /// Builds the table of a symmetry moving facelets to `facelets` and relabelling colors to
+    /// `colors`, for cubicles made of the facelets `cubicle_facelets`.
+    ///
+    /// `cubie_colors` gives the colors of a cubie with an orientation, read in the order of a
+    /// cubicle's facelets, and `read` recovers the cubie and orientation from them.
+    fn new(
+        facelets: &[usize; NUM_FACELETS],
+        colors: &[Faces; NUM_FACES],
+        cubicle_facelets: [[usize; N]; M],
+        cubie_colors: impl Fn(u8, u8) -> [Faces; N],
+        read: impl Fn([Faces; N]) -> (u8, u8),
+    ) -> CubieTable<N, M> {
+        let targets = cubicle_facelets.map(|cubicle| cubicle.map(|facelet| facelets[facelet]));
+        let cubicles = targets.map(|target| {
+            let cubicle = cubicle_facelets
+                .iter()
+                .position(|cubicle| cubicle.contains(&target[0]))
+                .unwrap();
+            cubicle as u8
+        });
+        let orientations = std::array::from_fn(|cubicle| {
+            let target = &cubicle_facelets[cubicles[cubicle] as usize];
+            std::array::from_fn(|cubie| {
+                std::array::from_fn(|orientation| {
+                    let before = cubie_colors(cubie as u8, orientation as u8);
+                    let after = target.map(|facelet| {
+                        let k = targets[cubicle].iter().position(|&t| t == facelet).unwrap();
+                        colors[before[k] as usize]
+                    });
+                    let (moved, orientation) = read(after);
+                    debug_assert_eq!(moved, cubicles[cubie]);
+                    orientation
+                })
+            })
+        });
+        CubieTable {
+            cubicles,
+            orientations,
+        }
+    }
+
+    /// Returns the permutation and orientation of the symmetric cubies.
+    fn apply(&self, permutation: &[u8], orientation: &[u8]) -> ([u8; M], [u8; M]) {
+        let mut moved_permutation = [0; M];
+        let mut moved_orientation = [0; M];
+        for (cubicle, (&cubie, &twist)) in permutation.iter().zip(orientation).enumerate() {
+            let target = self.cubicles[cubicle] as usize;
+            moved_permutation[target] = self.cubicles[cubie as usize];
+            moved_orientation[target] = self.orientations[cubicle][cubie as usize][twist as usize];
+        }
+        (moved_permutation, moved_orientation)
+    }
+}
+
+/// A rotation or reflection of the cube.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Symmetry {
+    /// signed permutation matrix acting on facelet positions
+    matrix: Matrix,
+    /// index each facelet is moved to
+    facelets: [usize; NUM_FACELETS],
+    /// the symmetry acting on the corners
+    corners: CubieTable<{ NUM_CORNER_ORIENTATION as usize }, { NUM_CORNERS as usize }>,
+    /// the symmetry acting on the edges
+    edges: CubieTable<{ NUM_EDGE_ORIENTATION as usize }, { NUM_EDGES as usize }>,
+    /// color each color is relabelled to, indexed by [`Faces`]
+    colors: [Faces; NUM_FACES],
+    /// turn each turn is mapped to, indexed by the turn's enum variant
+    turns: [Turn; NUM_TURNS],
+}
+
+impl Symmetry {
+    fn new(matrix: Matrix) -> Symmetry {
+        let positions = facelet_positions();
+        let facelets = positions.map(|(center, normal)| {
+            let image = (transform(&matrix, center), transform(&matrix, normal));
+            positions.iter().position(|&other| other == image).unwrap()
+        });
+        let colors = NORMALS.map(|normal| face_with_normal(transform(&matrix, normal)));
+        let corners = CubieTable::new(
+            &facelets,
+            &colors,
+            CORNER_FACELETS.map(|(a, b, c)| [a, b, c].map(|f| facelet_index(&f))),
+            |cubie, orientation| {
+                let corner = CORNER_CUBIES[cubie as usize].orient_corner(orientation);
+                [corner.facelet_a(), corner.facelet_b(), corner.facelet_c()]
+            },
+            |[a, b, c]| Corner::new(a, b, c).cubie_and_orientation().unwrap(),
+        );
+        let edges = CubieTable::new(
+            &facelets,
+            &colors,
+            EDGE_FACELETS.map(|(a, b)| [a, b].map(|f| facelet_index(&f))),
+            |cubie, orientation| {
+                let edge = EDGE_CUBIES[cubie as usize].orient_edge(orientation);
+                [edge.facelet_a(), edge.facelet_b()]
+            },
+            |[a, b]| Edge::new(a, b).cubie_and_orientation().unwrap(),
+        );
+
+        let mut symmetry = Symmetry {
+            matrix,
+            facelets,
+            corners,
+            edges,
+            colors,
+            turns: [Turn::L; NUM_TURNS],
+        };
+        let reflection = symmetry.is_reflection();
+        for (twist, turn) in symmetry.turns.iter_mut().enumerate() {
+            let face = colors[TURN_FACES[twist % NUM_FACES] as usize];
+            let face = TURN_FACES.iter().position(|&other| other == face).unwrap();
+            // reflections reverse the direction of quarter turns
+            let kind = match (twist / NUM_FACES, reflection) {
+                (0, true) => 1,
+                (1, true) => 0,
+                (kind, _) => kind,
+            };
+            *turn = Turn::from_u8((NUM_FACES * kind + face) as u8).unwrap();
+        }
+        symmetry
+    }
+
+    /// Returns the 48 symmetries of the cube, the identity first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::symmetry::Symmetry;
+    ///
+    /// let symmetries = Symmetry::all();
+    /// assert_eq!(symmetries.len(), 48);
+    /// assert_eq!(symmetries.iter().filter(|s| s.is_reflection()).count(), 24);
+    /// ```
+    pub fn all() -> &'static [Symmetry; NUM_SYMMETRIES] {
+        static SYMMETRIES: OnceLock<[Symmetry; NUM_SYMMETRIES]> = OnceLock::new();
+        SYMMETRIES.get_or_init(|| {
+            const AXES: [[usize; 3]; 6] = [
+                [0, 1, 2],
+                [0, 2, 1],
+                [1, 0, 2],
+                [1, 2, 0],
+                [2, 0, 1],
+                [2, 1, 0],
+            ];
+            std::array::from_fn(|idx| {
+                let axes = AXES[idx / 8];
+                let mut matrix = [[0; 3]; 3];
+                for (row, &axis) in axes.iter().enumerate() {
+                    matrix[row][axis] = if idx >> row & 1 == 0 { 1 } else { -1 };
+                }
+                Symmetry::new(matrix)
+            })
+        })
+    }
+
+    /// Returns the 24 rotations of the cube, the identity first.
+    pub fn rotations() -> impl Iterator<Item = &'static Symmetry> {
+        Symmetry::all()
+            .iter()
+            .filter(|symmetry| !symmetry.is_reflection())
+    }
+
+    /// Returns true if the symmetry is a reflection rather than a rotation.
+    pub fn is_reflection(&self) -> bool {
+        let m = &self.matrix;
+        let determinant = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+        determinant < 0
+    }
+
+    /// Returns the symmetric cube, with the same turn metric.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::symmetry::Symmetry;
+    /// use rubikscube::{Cube, Turn};
+    ///
+    /// let mut cube = Cube::cube_htm();
+    /// cube.turn(Turn::R as u8).unwrap();
+    ///
+    /// for symmetry in Symmetry::all() {
+    ///     let mut expected = Cube::cube_htm();
+    ///     expected.turn(symmetry.apply_turn(Turn::R) as u8).unwrap();
+    ///     assert_eq!(symmetry.apply(&cube), expected);
+    /// }
+    /// ```
+    pub fn apply(&self, cube: &Cube) -> Cube {
+        let [eo, co, ep, cp] = cube.cubicles();
+        let (corner_permutation, corner_orientation) = self.corners.apply(cp, co);
+        let (edge_permutation, edge_orientation) = self.edges.apply(ep, eo);
+        Cube::from_cubicles(
+            &edge_orientation,
+            &corner_orientation,
+            &edge_permutation,
+            &corner_permutation,
+            cube.turn_metric(),
+        )
+    }
+
+    /// Returns the cube with every cubie moved to where the rotation takes it, keeping its
+    /// colors, as if the whole cube was turned in hand with the centers left in place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the symmetry is a reflection, which would mirror the cubies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rubikscube::symmetry::Symmetry;
+    /// use rubikscube::Cube;
+    ///
+    /// let rotations: Vec<_> = Symmetry::rotations().collect();
+    /// let cube = Cube::cube_htm();
+    /// assert_eq!(rotations[0].rotate(&cube), cube);
+    /// assert!(rotations[1..].iter().all(|rotation| !rotation.rotate(&cube).solved()));
+    /// ```
+    pub fn rotate(&self, cube: &Cube) -> Cube {
+        assert!(!self.is_reflection(), "reflections can not rotate a cube");
+        let mut faces = self.move_facelets(&cube.faces());
+        for (face, facelets) in faces.iter_mut().enumerate() {
+            facelets[1][1] = Faces::from_repr(face).unwrap();
+        }
+        Cube::cube_from_faces(&faces, cube.turn_metric()).unwrap()
+    }
+
+    /// Returns the facelets, centers included, moved to where the symmetry takes them,
+    /// keeping their colors.
+    pub(crate) fn move_facelets(
+        &self,
+        faces: &[[[Faces; 3]; 3]; NUM_FACES],
+    ) -> [[[Faces; 3]; 3]; NUM_FACES] {
+        let mut moved = [[[Faces::White; 3]; 3]; NUM_FACES];
+        for (index, &color) in faces.iter().flatten().flatten().enumerate() {
+            let target = self.facelets[index];
+            moved[target / 9][target / 3 % 3][target % 3] = color;
+        }
+        moved
+    }
+
+    /// Returns the color `color` is relabelled to on the symmetric cube, which is also the
+    /// face its center moves to.
+    pub fn apply_color(&self, color: Faces) -> Faces {
+        self.colors[color as usize]
+    }
+
+    /// Returns the turn that has the same effect on the symmetric cube as `turn` has on the
+    /// original cube.
+    pub fn apply_turn(&self, turn: Turn) -> Turn {
+        self.turns[turn as usize]
+    }
+}
+
+/// Returns the 48 symmetric versions of `cube`, each with `turn` remapped to it.
+///
+/// Applying the remapped turn to a symmetric cube gives the symmetric version of `cube`
+/// after `turn`. Cubes that are themselves symmetric, such as the solved cube, appear more
+/// than once.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::symmetry::augment;
+/// use rubikscube::{Cube, Turn};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.scramble(20);
+///
+/// let samples = augment(&cube, Turn::U);
+/// assert_eq!(samples.len(), 48);
+/// assert_eq!(samples[0], (cube, Turn::U));
+/// ```
+pub fn augment(cube: &Cube, turn: Turn) -> Vec<(Cube, Turn)> {
+    Symmetry::all()
+        .iter()
+        .map(|symmetry| (symmetry.apply(cube), symmetry.apply_turn(turn)))
+        .collect()
+}
+
+/// Returns the `Cube::representation` of the 48 symmetric versions of `cube`, each with
+/// `turn` remapped to it, see [`augment`].
+pub fn augment_representations(
+    cube: &Cube,
+    turn: Turn,
+) -> Vec<([bool; REPRESENTATION_SIZE], Turn)> {
+    augment(cube, turn)
+        .into_iter()
+        .map(|(cube, turn)| (cube.representation(), turn))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{augment, augment_representations, Symmetry};
+    use crate::{Cube, MetricKind, Turn};
+
+    #[test]
+    fn symmetries_are_distinct_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(30);
+
+        let symmetric: HashSet<Cube> = augment(&cube, Turn::R)
+            .into_iter()
+            .map(|(c, _)| c)
+            .collect();
+        assert_eq!(symmetric.len(), 48);
+        for symmetric in symmetric {
+            assert!(symmetric.is_solvable());
+        }
+        for symmetry in Symmetry::all() {
+            assert!(symmetry.apply(&Cube::cube_htm()).solved());
+        }
+        assert_eq!(Symmetry::all()[0].apply(&cube), cube);
+    }
+
+    #[test]
+    fn apply_matches_facelets_test() {
+        // the symmetric cube read back from its moved and relabelled facelets
+        let apply_facelets = |symmetry: &Symmetry, cube: &Cube| {
+            let mut faces = symmetry.move_facelets(&cube.faces());
+            for facelet in faces.iter_mut().flatten().flatten() {
+                *facelet = symmetry.apply_color(*facelet);
+            }
+            Cube::cube_from_faces(&faces, cube.turn_metric()).unwrap()
+        };
+        for _ in 0..10 {
+            let mut cube = Cube::cube_htm();
+            cube.scramble(30);
+            for symmetry in Symmetry::all() {
+                assert_eq!(symmetry.apply(&cube), apply_facelets(symmetry, &cube));
+            }
+        }
+    }
+
+    #[test]
+    fn turns_commute_with_symmetries_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(30);
+
+        for symmetry in Symmetry::all() {
+            let symmetric = symmetry.apply(&cube);
+            for twist in 0..MetricKind::HalfTurnMetric as u8 {
+                let turn = Turn::from_u8(twist).unwrap();
+                let mut turned = cube;
+                turned.turn(twist).unwrap();
+                let mut symmetric_turned = symmetric;
+                symmetric_turned
+                    .turn(symmetry.apply_turn(turn) as u8)
+                    .unwrap();
+                assert_eq!(symmetry.apply(&turned), symmetric_turned);
+            }
+        }
+    }
+
+    #[test]
+    fn quarter_turns_stay_quarter_turns_test() {
+        let mut cube = Cube::cube_qtm();
+        cube.scramble(30);
+        for (symmetric, turn) in augment(&cube, Turn::F_) {
+            assert_eq!(symmetric.turn_metric(), MetricKind::QuarterTurnMetric);
+            assert!((turn as u8) < MetricKind::QuarterTurnMetric as u8);
+        }
+    }
+
+    #[test]
+    fn augment_representations_test() {
+        let mut cube = Cube::cube_htm();
+        cube.scramble(10);
+        for ((symmetric, turn), (representation, repr_turn)) in augment(&cube, Turn::D2)
+            .into_iter()
+            .zip(augment_representations(&cube, Turn::D2))
+        {
+            assert_eq!(symmetric.representation(), representation);
+            assert_eq!(turn, repr_turn);
+        }
+    }
+}
@@ -0,0 +1,607 @@
+//! Thistlethwaite's four phase algorithm.
+//!
+//! The cube is brought through the nested subgroups
+//!
+//! * G0 = <L, R, F, B, U, D>, every solvable cube,
+//! * G1 = <L2, R2, F, B, U, D>, the cubes with oriented edges,
+//! * G2 = <L2, R2, F2, B2, U, D>, which also have oriented corners and the edges of the slice
+//!   between U and D in that slice,
+//! * G3 = <L2, R2, F2, B2, U2, D2>, the cubes solvable with half turns only,
+//! * {e}, the solved cube.
+//!
+//! Each phase only uses the turns of the group it starts from, and looks up the distance to
+//! the next group in a table indexed by coordinates of the orientations and permutations of
+//! the cubies. The tables cover every coset, so each phase is solved optimally, but the whole
+//! solution is usually far from a shortest one. The tables are built on first use, which takes
+//! a few seconds.
+
+use std::sync::OnceLock;
+
+use crate::{
+    bfs::{
+        distance_table, factorial, rank_orientation, rank_permutation, unrank_orientation,
+        unrank_permutation, UNVISITED,
+    },
+    cubies::{NUM_CORNERS, NUM_CORNER_ORIENTATION, NUM_EDGES, NUM_EDGE_ORIENTATION},
+    errors::CubeError,
+    moves::{in_metric, MetricKind, Turn},
+    turn_table::{CubieTurn, TURN_TABLE},
+    Cube,
+};
+
+const NUM_PHASES: usize = 4;
+
+/// Turns of G0, G1, G2 and G3, those used by each phase.
+const PHASE_TURNS: [&[Turn]; NUM_PHASES] = [
+    &[
+        Turn::L,
+        Turn::R,
+        Turn::F,
+        Turn::B,
+        Turn::U,
+        Turn::D,
+        Turn::L_,
+        Turn::R_,
+        Turn::F_,
+        Turn::B_,
+        Turn::U_,
+        Turn::D_,
+        Turn::L2,
+        Turn::R2,
+        Turn::F2,
+        Turn::B2,
+        Turn::U2,
+        Turn::D2,
+    ],
+    &[
+        Turn::F,
+        Turn::B,
+        Turn::U,
+        Turn::D,
+        Turn::F_,
+        Turn::B_,
+        Turn::U_,
+        Turn::D_,
+        Turn::L2,
+        Turn::R2,
+        Turn::F2,
+        Turn::B2,
+        Turn::U2,
+        Turn::D2,
+    ],
+    &[
+        Turn::U,
+        Turn::D,
+        Turn::U_,
+        Turn::D_,
+        Turn::L2,
+        Turn::R2,
+        Turn::F2,
+        Turn::B2,
+        Turn::U2,
+        Turn::D2,
+    ],
+    &[Turn::L2, Turn::R2, Turn::F2, Turn::B2, Turn::U2, Turn::D2],
+];
+
+/// Groups each phase goes between and what it does.
+const PHASE_NAMES: [&str; NUM_PHASES] = [
+    "G0 to G1, orient edges",
+    "G1 to G2, orient corners and place the U-D slice edges",
+    "G2 to G3, place the other edges in their slices and the corners in their orbits",
+    "G3 to solved, with half turns only",
+];
+
+/// Edge cubicles of the slice between U and D, between L and R and between F and B.
+const SLICES: [[u8; 4]; 3] = [[4, 5, 6, 7], [0, 2, 8, 10], [1, 3, 9, 11]];
+
+/// Edge cubicles outside of the slice between U and D.
+const OUTSIDE_SLICE: [u8; 8] = [0, 1, 2, 3, 8, 9, 10, 11];
+
+/// Number of permutations of the 4 edges of a slice.
+const SLICE_PERMUTATIONS: usize = 24;
+
+/// Subsets of 4 of a number of positions, as bitmasks in increasing order.
+struct Combinations {
+    masks: Vec<u16>,
+    /// rank of every mask, only meaningful for masks of 4 positions
+    ranks: Vec<u16>,
+}
+
+impl Combinations {
+    fn new(positions: usize) -> Combinations {
+        let masks: Vec<u16> = (0..1u16 << positions)
+            .filter(|mask| mask.count_ones() == 4)
+            .collect();
+        let mut ranks = vec![0; 1 << positions];
+        for (rank, &mask) in masks.iter().enumerate() {
+            ranks[mask as usize] = rank as u16;
+        }
+        Combinations { masks, ranks }
+    }
+
+    fn len(&self) -> usize {
+        self.masks.len()
+    }
+}
+
+/// Coordinate of each state after every turn of a phase, `moves[coordinate * turns + turn]`.
+struct MoveTable {
+    size: usize,
+    moves: Vec<u32>,
+}
+
+impl MoveTable {
+    fn new(size: usize, turns: &[Turn], apply: impl Fn(usize, &CubieTurn) -> usize) -> Self {
+        let mut moves = Vec::with_capacity(size * turns.len());
+        for coordinate in 0..size {
+            for &turn in turns {
+                moves.push(apply(coordinate, &TURN_TABLE[turn as usize]) as u32);
+            }
+        }
+        MoveTable { size, moves }
+    }
+}
+
+/// Distance to the next group of every coset of a phase.
+///
+/// A coset is indexed by a pair of coordinates, `first * second.size + second`.
+struct PhaseTable {
+    turns: &'static [Turn],
+    coordinates: [MoveTable; 2],
+    distances: Vec<u8>,
+}
+
+impl PhaseTable {
+    /// Builds the table by breadth-first search from the `goals`, the cosets of the next
+    /// group. The turns of a phase include their inverses, so searching forwards from the
+    /// goals gives the distances to them.
+    fn new(
+        turns: &'static [Turn],
+        coordinates: [MoveTable; 2],
+        goals: impl Iterator<Item = usize>,
+    ) -> PhaseTable {
+        let size = coordinates[0].size * coordinates[1].size;
+        let mut table = PhaseTable {
+            turns,
+            coordinates,
+            distances: Vec::new(),
+        };
+        table.distances = distance_table(size, goals, turns.len(), |index, turn| {
+            table.turn(index, turn)
+        });
+        table
+    }
+
+    fn turn(&self, index: usize, turn: usize) -> usize {
+        let [first, second] = &self.coordinates;
+        let num_turns = self.turns.len();
+        let (a, b) = (index / second.size, index % second.size);
+        first.moves[a * num_turns + turn] as usize * second.size
+            + second.moves[b * num_turns + turn] as usize
+    }
+
+    /// Returns a shortest sequence of turns from the coset `index` to the next group.
+    fn solve(&self, mut index: usize) -> Vec<Turn> {
+        let mut turns = Vec::new();
+        let mut distance = self.distances[index];
+        // the cube was checked to be solvable, so every coset it goes through is reached
+        assert_ne!(distance, UNVISITED);
+        while distance > 0 {
+            let (turn, next) = (0..self.turns.len())
+                .map(|turn| (turn, self.turn(index, turn)))
+                .find(|&(_, next)| self.distances[next] == distance - 1)
+                .unwrap();
+            turns.push(self.turns[turn]);
+            index = next;
+            distance -= 1;
+        }
+        turns
+    }
+}
+
+/// The tables of the four phases and the lookups needed to compute their coordinates.
+struct Tables {
+    phases: [PhaseTable; NUM_PHASES],
+    /// `Combinations` of the U-D slice edges among all 12 edge cubicles
+    slice: Combinations,
+    /// `Combinations` of the L-R slice edges among the `OUTSIDE_SLICE` cubicles
+    outside_slice: Combinations,
+    /// index in `half_turn_corners` of every corner permutation rank
+    half_turn_index: Vec<u8>,
+}
+
+/// Corner permutation ranks reachable with half turns only, in the order found.
+fn half_turn_corners() -> Vec<u32> {
+    let mut seen = vec![false; factorial(NUM_CORNERS) as usize];
+    let identity = rank_permutation(&[0, 1, 2, 3, 4, 5, 6, 7]) as usize;
+    seen[identity] = true;
+    let mut found = vec![identity as u32];
+    let mut next = 0;
+    while next < found.len() {
+        let mut corners = [0; NUM_CORNERS as usize];
+        unrank_permutation(found[next] as u64, &mut corners);
+        for &turn in PHASE_TURNS[3] {
+            let mut turned = corners;
+            TURN_TABLE[turn as usize]
+                .corners
+                .apply::<NUM_CORNER_ORIENTATION>(&mut turned, &mut [0; NUM_CORNERS as usize]);
+            let rank = rank_permutation(&turned) as usize;
+            if !seen[rank] {
+                seen[rank] = true;
+                found.push(rank as u32);
+            }
+        }
+        next += 1;
+    }
+    found
+}
+
+/// Marks the cubicles holding one of `cubies`.
+fn labels(permutation: &[u8], cubies: &[u8]) -> Vec<bool> {
+    permutation
+        .iter()
+        .map(|cubie| cubies.contains(cubie))
+        .collect()
+}
+
+/// Bitmask of the `positions` whose cubicle is labelled.
+fn mask(labels: &[bool], positions: &[u8]) -> usize {
+    positions
+        .iter()
+        .enumerate()
+        .filter(|&(_, &cubicle)| labels[cubicle as usize])
+        .fold(0, |mask, (bit, _)| mask | 1 << bit)
+}
+
+/// Edge permutation with the U-D slice edges in the positions of `mask`, and every other
+/// cubicle holding the L-R slice edges in the `OUTSIDE_SLICE` positions of `outside_mask`.
+/// Edges are only told apart by slice.
+fn slice_edges(mask: u16, outside_mask: u16) -> [u8; NUM_EDGES as usize] {
+    let mut edges = [SLICES[2][0]; NUM_EDGES as usize];
+    for (cubicle, edge) in edges.iter_mut().enumerate() {
+        if mask >> cubicle & 1 == 1 {
+            *edge = SLICES[0][0];
+        }
+    }
+    for (bit, &cubicle) in OUTSIDE_SLICE.iter().enumerate() {
+        if outside_mask >> bit & 1 == 1 {
+            edges[cubicle as usize] = SLICES[1][0];
+        }
+    }
+    edges
+}
+
+impl Tables {
+    fn new() -> Tables {
+        let slice = Combinations::new(NUM_EDGES as usize);
+        let outside_slice = Combinations::new(OUTSIDE_SLICE.len());
+        let half_turn_corners = half_turn_corners();
+        let mut half_turn_index = vec![UNVISITED; factorial(NUM_CORNERS) as usize];
+        for (index, &rank) in half_turn_corners.iter().enumerate() {
+            half_turn_index[rank as usize] = index as u8;
+        }
+
+        let edge_orientation = |turns| {
+            MoveTable::new(
+                1 << NUM_EDGES,
+                turns,
+                |coordinate, CubieTurn { edges, .. }| {
+                    let mut orientations = [0; NUM_EDGES as usize];
+                    unrank_orientation::<NUM_EDGE_ORIENTATION>(
+                        coordinate as u64,
+                        &mut orientations,
+                    );
+                    edges.apply::<NUM_EDGE_ORIENTATION>(
+                        &mut [0; NUM_EDGES as usize],
+                        &mut orientations,
+                    );
+                    rank_orientation::<NUM_EDGE_ORIENTATION>(&orientations) as usize
+                },
+            )
+        };
+        let unit = |turns: &[Turn]| MoveTable {
+            size: 1,
+            moves: vec![0; turns.len()],
+        };
+        let phase1 = PhaseTable::new(
+            PHASE_TURNS[0],
+            [edge_orientation(PHASE_TURNS[0]), unit(PHASE_TURNS[0])],
+            std::iter::once(0),
+        );
+
+        let turns = PHASE_TURNS[1];
+        let corner_orientation = MoveTable::new(
+            (NUM_CORNER_ORIENTATION as usize).pow(NUM_CORNERS as u32),
+            turns,
+            |coordinate, CubieTurn { corners, .. }| {
+                let mut orientations = [0; NUM_CORNERS as usize];
+                unrank_orientation::<NUM_CORNER_ORIENTATION>(coordinate as u64, &mut orientations);
+                corners.apply::<NUM_CORNER_ORIENTATION>(
+                    &mut [0; NUM_CORNERS as usize],
+                    &mut orientations,
+                );
+                rank_orientation::<NUM_CORNER_ORIENTATION>(&orientations) as usize
+            },
+        );
+        let slice_positions =
+            MoveTable::new(slice.len(), turns, |coordinate, CubieTurn { edges, .. }| {
+                let mut permutation = slice_edges(slice.masks[coordinate], 0);
+                edges.apply::<NUM_EDGE_ORIENTATION>(&mut permutation, &mut [0; NUM_EDGES as usize]);
+                slice.ranks[mask(&labels(&permutation, &SLICES[0][..1]), &EDGE_CUBICLES)] as usize
+            });
+        let solved_slice = slice.ranks[mask(&labels(&EDGE_CUBICLES, &SLICES[0]), &EDGE_CUBICLES)];
+        let phase2 = PhaseTable::new(
+            turns,
+            [corner_orientation, slice_positions],
+            std::iter::once(solved_slice as usize),
+        );
+
+        let turns = PHASE_TURNS[2];
+        let corner_permutation = MoveTable::new(
+            factorial(NUM_CORNERS) as usize,
+            turns,
+            |coordinate, CubieTurn { corners, .. }| {
+                let mut permutation = [0; NUM_CORNERS as usize];
+                unrank_permutation(coordinate as u64, &mut permutation);
+                corners.apply::<NUM_CORNER_ORIENTATION>(
+                    &mut permutation,
+                    &mut [0; NUM_CORNERS as usize],
+                );
+                rank_permutation(&permutation) as usize
+            },
+        );
+        let outside_positions = MoveTable::new(
+            outside_slice.len(),
+            turns,
+            |coordinate, CubieTurn { edges, .. }| {
+                let mut permutation = slice_edges(0b1111_0000, outside_slice.masks[coordinate]);
+                edges.apply::<NUM_EDGE_ORIENTATION>(&mut permutation, &mut [0; NUM_EDGES as usize]);
+                outside_slice.ranks[mask(&labels(&permutation, &SLICES[1][..1]), &OUTSIDE_SLICE)]
+                    as usize
+            },
+        );
+        let solved_outside =
+            outside_slice.ranks[mask(&labels(&EDGE_CUBICLES, &SLICES[1]), &OUTSIDE_SLICE)];
+        let phase3 = PhaseTable::new(
+            turns,
+            [corner_permutation, outside_positions],
+            half_turn_corners
+                .iter()
+                .map(|&rank| rank as usize * outside_slice.len() + solved_outside as usize),
+        );
+
+        let turns = PHASE_TURNS[3];
+        let corners = MoveTable::new(
+            half_turn_corners.len(),
+            turns,
+            |coordinate, CubieTurn { corners, .. }| {
+                let mut permutation = [0; NUM_CORNERS as usize];
+                unrank_permutation(half_turn_corners[coordinate] as u64, &mut permutation);
+                corners.apply::<NUM_CORNER_ORIENTATION>(
+                    &mut permutation,
+                    &mut [0; NUM_CORNERS as usize],
+                );
+                half_turn_index[rank_permutation(&permutation) as usize] as usize
+            },
+        );
+        let slice_permutations = MoveTable::new(
+            SLICE_PERMUTATIONS.pow(SLICES.len() as u32),
+            turns,
+            |coordinate, CubieTurn { edges, .. }| {
+                let mut permutation = unrank_slices(coordinate);
+                edges.apply::<NUM_EDGE_ORIENTATION>(&mut permutation, &mut [0; NUM_EDGES as usize]);
+                rank_slices(&permutation)
+            },
+        );
+        let identity = half_turn_index[rank_permutation(&[0, 1, 2, 3, 4, 5, 6, 7]) as usize];
+        let phase4 = PhaseTable::new(
+            turns,
+            [corners, slice_permutations],
+            std::iter::once(identity as usize * SLICE_PERMUTATIONS.pow(SLICES.len() as u32)),
+        );
+
+        Tables {
+            phases: [phase1, phase2, phase3, phase4],
+            slice,
+            outside_slice,
+            half_turn_index,
+        }
+    }
+
+    fn get() -> &'static Tables {
+        static TABLES: OnceLock<Tables> = OnceLock::new();
+        TABLES.get_or_init(Tables::new)
+    }
+
+    /// Index of the coset of `cube` in the table of `phase`.
+    fn coset(&self, phase: usize, cube: &Cube) -> usize {
+        let [eo, co, ep, cp] = cube.cubicles();
+        match phase {
+            0 => rank_orientation::<NUM_EDGE_ORIENTATION>(eo) as usize,
+            1 => {
+                let slice = self.slice.ranks[mask(&labels(ep, &SLICES[0]), &EDGE_CUBICLES)];
+                rank_orientation::<NUM_CORNER_ORIENTATION>(co) as usize * self.slice.len()
+                    + slice as usize
+            }
+            2 => {
+                let outside =
+                    self.outside_slice.ranks[mask(&labels(ep, &SLICES[1]), &OUTSIDE_SLICE)];
+                rank_permutation(cp) as usize * self.outside_slice.len() + outside as usize
+            }
+            _ => {
+                let corners = self.half_turn_index[rank_permutation(cp) as usize] as usize;
+                corners * SLICE_PERMUTATIONS.pow(SLICES.len() as u32) + rank_slices(ep)
+            }
+        }
+    }
+}
+
+/// The solved edge permutation, also every edge cubicle.
+const EDGE_CUBICLES: [u8; NUM_EDGES as usize] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// Rank of the permutation of the edges within each slice, assuming every edge is in its
+/// slice.
+fn rank_slices(permutation: &[u8]) -> usize {
+    SLICES.iter().fold(0, |rank, slice| {
+        let within = slice.map(|cubicle| {
+            let cubie = permutation[cubicle as usize];
+            slice.iter().position(|&other| other == cubie).unwrap() as u8
+        });
+        rank * SLICE_PERMUTATIONS + rank_permutation(&within) as usize
+    })
+}
+
+/// Inverse of `rank_slices`.
+fn unrank_slices(mut rank: usize) -> [u8; NUM_EDGES as usize] {
+    let mut permutation = [0; NUM_EDGES as usize];
+    for slice in SLICES.iter().rev() {
+        let mut within = [0; 4];
+        unrank_permutation((rank % SLICE_PERMUTATIONS) as u64, &mut within);
+        rank /= SLICE_PERMUTATIONS;
+        for (&cubicle, &index) in slice.iter().zip(&within) {
+            permutation[cubicle as usize] = slice[index as usize];
+        }
+    }
+    permutation
+}
+
+/// The turns of one phase.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Phase {
+    /// Groups the phase goes between and what it does.
+    pub name: &'static str,
+    pub turns: Vec<Turn>,
+}
+
+/// A solution, split into its four phases.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Solution {
+    pub phases: Vec<Phase>,
+}
+
+impl Solution {
+    /// Returns the turns of every phase one after the other.
+    pub fn turns(&self) -> Vec<Turn> {
+        self.phases
+            .iter()
+            .flat_map(|phase| phase.turns.iter().copied())
+            .collect()
+    }
+}
+
+/// Solves a cube with Thistlethwaite's algorithm.
+///
+/// Half turns are written as two quarter turns for cubes in the quarter turn metric. Returns
+/// `CubeError::InvalidState` if the cube is not solvable.
+///
+/// # Examples
+///
+/// ```
+/// use rubikscube::{thistlethwaite, Cube};
+///
+/// let mut cube = Cube::cube_htm();
+/// cube.scramble(30);
+///
+/// let solution = thistlethwaite::solve(&cube).unwrap();
+/// assert_eq!(solution.phases.len(), 4);
+/// for turn in solution.turns() {
+///     cube.turn(turn as u8).unwrap();
+/// }
+/// assert!(cube.solved());
+/// ```
+pub fn solve(cube: &Cube) -> Result<Solution, CubeError> {
+    if !cube.is_solvable() {
+        return Err(CubeError::InvalidState);
+    }
+    let tables = Tables::get();
+    let [eo, co, ep, cp] = cube.cubicles();
+    let mut current = Cube::from_cubicles(eo, co, ep, cp, MetricKind::HalfTurnMetric);
+
+    let mut phases = Vec::with_capacity(NUM_PHASES);
+    for (phase, (table, name)) in tables.phases.iter().zip(PHASE_NAMES).enumerate() {
+        let turns = table.solve(tables.coset(phase, &current));
+        for &turn in &turns {
+            current.turn(turn as u8)?;
+        }
+        let turns = in_metric(turns, cube.turn_metric());
+        phases.push(Phase { name, turns });
+    }
+    debug_assert!(current.solved());
+    Ok(Solution { phases })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve, Tables, PHASE_TURNS};
+    use crate::{errors::CubeError, Cube, MetricKind, Turn};
+
+    #[test]
+    fn table_sizes_test() {
+        let tables = Tables::get();
+        // phases 1, 2 and 4 reach one state per coset of the next group, phase 3 tracks the
+        // whole corner permutation and reaches every pair of coordinates
+        let expected = [2048, 1_082_565, 40320 * 70, 663_552];
+        for (table, expected) in tables.phases.iter().zip(expected) {
+            let reached = table.distances.iter().filter(|&&d| d != u8::MAX).count();
+            assert_eq!(reached, expected);
+        }
+        let longest: Vec<u8> = tables
+            .phases
+            .iter()
+            .map(|table| {
+                table
+                    .distances
+                    .iter()
+                    .filter(|&&d| d != u8::MAX)
+                    .max()
+                    .copied()
+                    .unwrap()
+            })
+            .collect();
+        assert_eq!(longest, [7, 10, 13, 15]);
+    }
+
+    #[test]
+    fn solve_test() {
+        for seed in 0..20 {
+            let mut cube = Cube::cube_htm();
+            cube.scramble(seed + 10);
+            let solution = solve(&cube).unwrap();
+            for (phase, turns) in solution.phases.iter().zip(PHASE_TURNS) {
+                assert!(phase.turns.iter().all(|turn| turns.contains(turn)));
+            }
+            for turn in solution.turns() {
+                cube.turn(turn as u8).unwrap();
+            }
+            assert!(cube.solved());
+        }
+        let solution = solve(&Cube::cube_htm()).unwrap();
+        assert!(solution.turns().is_empty());
+    }
+
+    #[test]
+    fn quarter_turn_metric_test() {
+        let mut cube = Cube::cube_qtm();
+        cube.scramble(25);
+        let solution = solve(&cube).unwrap();
+        for turn in solution.turns() {
+            assert!((turn as u8) < MetricKind::QuarterTurnMetric as u8);
+            cube.turn(turn as u8).unwrap();
+        }
+        assert!(cube.solved());
+    }
+
+    #[test]
+    fn unsolvable_test() {
+        let [eo, _, ep, cp] = Cube::cube_htm().cubicles().map(<[u8]>::to_vec);
+        let twisted = [1, 0, 0, 0, 0, 0, 0, 0];
+        let cube = Cube::from_cubicles(&eo, &twisted, &ep, &cp, MetricKind::HalfTurnMetric);
+        assert_eq!(solve(&cube), Err(CubeError::InvalidState));
+        let mut cube = Cube::cube_htm();
+        cube.turn(Turn::R as u8).unwrap();
+        let phases: Vec<_> = solve(&cube).unwrap().phases;
+        let turns: Vec<_> = phases.into_iter().map(|phase| phase.turns).collect();
+        assert_eq!(turns, [vec![Turn::R], vec![], vec![], vec![Turn::R2]]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{
+    cubies::*,
+    moves::{MetricKind, Turn},
+    permutation::Permutation,
+};
+
+/// Number of edge and of corner cubies moved by a face turn.
+pub const NUM_MOVED_CUBIES: usize = 4;
+
+/// Cubicles of one kind of cubie moved by a turn.
+///
+/// The cubie in cubicle `sources[k]` is moved to cubicle `targets[k]` and `twists[k]` is added
+/// to its orientation. Cubicles outside of `targets` are left untouched.
+pub struct CubicleCycle {
+    pub targets: [u8; NUM_MOVED_CUBIES],
+    pub sources: [u8; NUM_MOVED_CUBIES],
+    pub twists: [u8; NUM_MOVED_CUBIES],
+}
+
+impl CubicleCycle {
+    /// Applies the cycle to a permutation and to orientations with `M` possible values, both
+    /// indexed by cubicle.
+    #[inline]
+    pub fn apply<const M: u8>(&self, permutation: &mut [u8], orientations: &mut [u8]) {
+        let cubies = self.sources.map(|cubicle| permutation[cubicle as usize]);
+        let cubie_orientations = self.sources.map(|cubicle| orientations[cubicle as usize]);
+        for k in 0..NUM_MOVED_CUBIES {
+            let cubicle = self.targets[k] as usize;
+            permutation[cubicle] = cubies[k];
+            orientations[cubicle] = (cubie_orientations[k] + self.twists[k]) % M;
+        }
+    }
+}
+
+/// Cubie level transformation performed by a single turn.
+pub struct CubieTurn {
+    pub edges: CubicleCycle,
+    pub corners: CubicleCycle,
+}
+
+/// Transformation of every turn, indexed by the turn's enum variant.
+pub static TURN_TABLE: [CubieTurn; MetricKind::HalfTurnMetric as usize] = [
+    cubie_turn(Turn::L),
+    cubie_turn(Turn::R),
+    cubie_turn(Turn::F),
+    cubie_turn(Turn::B),
+    cubie_turn(Turn::U),
+    cubie_turn(Turn::D),
+    cubie_turn(Turn::L_),
+    cubie_turn(Turn::R_),
+    cubie_turn(Turn::F_),
+    cubie_turn(Turn::B_),
+    cubie_turn(Turn::U_),
+    cubie_turn(Turn::D_),
+    cubie_turn(Turn::L2),
+    cubie_turn(Turn::R2),
+    cubie_turn(Turn::F2),
+    cubie_turn(Turn::B2),
+    cubie_turn(Turn::U2),
+    cubie_turn(Turn::D2),
+];
+
+/// Computes the transformation of a turn from the cubicles of the face it turns.
+const fn cubie_turn(m: Turn) -> CubieTurn {
+    // unpack cubicle indices
+    let ((a, b, c, d), (w, x, y, z)) = match m {
+        Turn::L | Turn::L_ | Turn::L2 => (L_EDGE_CUBICLES, L_CORNER_CUBICLES),
+        Turn::R | Turn::R_ | Turn::R2 => (R_EDGE_CUBICLES, R_CORNER_CUBICLES),
+        Turn::F | Turn::F_ | Turn::F2 => (F_EDGE_CUBICLES, F_CORNER_CUBICLES),
+        Turn::B | Turn::B_ | Turn::B2 => (B_EDGE_CUBICLES, B_CORNER_CUBICLES),
+        Turn::U | Turn::U_ | Turn::U2 => (U_EDGE_CUBICLES, U_CORNER_CUBICLES),
+        Turn::D | Turn::D_ | Turn::D2 => (D_EDGE_CUBICLES, D_CORNER_CUBICLES),
+    };
+
+    let mut edge_twist = [0; NUM_EDGES as usize];
+    let mut corner_twist = [0; NUM_CORNERS as usize];
+
+    // edge and corner cubie orientation changes based on the cubicle before the move
+    match m {
+        Turn::L | Turn::R | Turn::L_ | Turn::R_ => {
+            corner_twist[w as usize] = 1;
+            corner_twist[x as usize] = 2;
+            corner_twist[y as usize] = 1;
+            corner_twist[z as usize] = 2;
+
+            edge_twist[a as usize] = 1;
+            edge_twist[b as usize] = 1;
+            edge_twist[c as usize] = 1;
+            edge_twist[d as usize] = 1;
+        }
+        Turn::F | Turn::B | Turn::F_ | Turn::B_ => {
+            corner_twist[w as usize] = 1;
+            corner_twist[x as usize] = 2;
+            corner_twist[y as usize] = 1;
+            corner_twist[z as usize] = 2;
+        }
+        _ => {}
+    }
+
+    let mut edge_permutation = Permutation::edge();
+    let mut corner_permutation = Permutation::corner();
+
+    // edge and corner cubie permutation based on move
+    match m {
+        Turn::L | Turn::R | Turn::F | Turn::B | Turn::U | Turn::D => {
+            edge_permutation.swap_four_cubies(a, b, c, d);
+            corner_permutation.swap_four_cubies(w, x, y, z);
+        }
+        Turn::L_ | Turn::R_ | Turn::F_ | Turn::B_ | Turn::U_ | Turn::D_ => {
+            edge_permutation.swap_four_cubies(d, c, b, a);
+            corner_permutation.swap_four_cubies(z, y, x, w);
+        }
+        Turn::L2 | Turn::R2 | Turn::F2 | Turn::B2 | Turn::U2 | Turn::D2 => {
+            edge_permutation.swap_two_cubies(a, c);
+            edge_permutation.swap_two_cubies(b, d);
+            corner_permutation.swap_two_cubies(w, y);
+            corner_permutation.swap_two_cubies(x, z);
+        }
+    }
+
+    CubieTurn {
+        edges: moved_cubicles(&edge_permutation, &edge_twist),
+        corners: moved_cubicles(&corner_permutation, &corner_twist),
+    }
+}
+
+/// Collects the cubicles whose cubie is changed by `permutation`.
+///
+/// `twist` is indexed by the cubicle a cubie is in before the turn.
+const fn moved_cubicles<const N: usize>(
+    permutation: &Permutation<N>,
+    twist: &[u8; N],
+) -> CubicleCycle {
+    let mut cycle = CubicleCycle {
+        targets: [0; NUM_MOVED_CUBIES],
+        sources: [0; NUM_MOVED_CUBIES],
+        twists: [0; NUM_MOVED_CUBIES],
+    };
+
+    let mut k = 0;
+    let mut cubicle = 0;
+    while cubicle < N {
+        let source = permutation.cubie_in_cubicle(cubicle as u8);
+        if source as usize != cubicle {
+            cycle.targets[k] = cubicle as u8;
+            cycle.sources[k] = source;
+            cycle.twists[k] = twist[source as usize];
+            k += 1;
+        }
+        cubicle += 1;
+    }
+    assert!(k == NUM_MOVED_CUBIES);
+
+    cycle
+}
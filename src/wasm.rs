@@ -0,0 +1,217 @@
+//! WebAssembly bindings, built with the `wasm` feature.
+//!
+//! Build the package for a bundler or the browser with [wasm-pack]:
+//!
+//! ```text
+//! wasm-pack build --target web -- --features wasm
+//! ```
+//!
+//! and run the tests headlessly under Node with
+//!
+//! ```text
+//! wasm-pack test --node -- --features wasm
+//! ```
+//!
+//! or, without wasm-pack, with the `wasm-bindgen-test-runner` of `wasm-bindgen-cli` at the
+//! version of `wasm-bindgen` in use:
+//!
+//! ```text
+//! CARGO_TARGET_WASM32_UNKNOWN_UNKNOWN_RUNNER=wasm-bindgen-test-runner \
+//!     cargo test --target wasm32-unknown-unknown --features wasm --lib
+//! ```
+//!
+//! [wasm-pack]: https://rustwasm.github.io/wasm-pack/
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Cube, CubeError, MetricKind, Turn};
+
+/// JavaScript wrapper around [`Cube`].
+#[wasm_bindgen(js_name = Cube)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WasmCube {
+    cube: Cube,
+}
+
+#[wasm_bindgen(js_class = Cube)]
+impl WasmCube {
+    #[wasm_bindgen(constructor)]
+    pub fn new(turn_metric: MetricKind) -> WasmCube {
+        WasmCube {
+            cube: Cube::new(turn_metric),
+        }
+    }
+
+    /// Creates a cube from a string of 54 facelet colors, see `Cube::from_facelets`.
+    #[wasm_bindgen(js_name = fromFacelets)]
+    pub fn from_facelets(facelets: &str, turn_metric: MetricKind) -> Result<WasmCube, JsError> {
+        Ok(WasmCube {
+            cube: Cube::from_facelets(facelets, turn_metric)?,
+        })
+    }
+
+    #[wasm_bindgen(getter, js_name = turnMetric)]
+    pub fn turn_metric(&self) -> MetricKind {
+        self.cube.turn_metric()
+    }
+
+    pub fn turn(&mut self, twist: Turn) -> Result<(), JsError> {
+        Ok(self.cube.turn(twist as u8)?)
+    }
+
+    /// Applies a sequence of moves such as `"R U R' U'"`.
+    ///
+    /// The cube is left unchanged if any move is invalid.
+    #[wasm_bindgen(js_name = applyMoves)]
+    pub fn apply_moves(&mut self, moves: &str) -> Result<(), JsError> {
+        Ok(self.try_apply_moves(moves)?)
+    }
+
+    pub fn scramble(&mut self, num_turns: u32) {
+        self.cube.scramble(num_turns);
+    }
+
+    pub fn solved(&self) -> bool {
+        self.cube.solved()
+    }
+
+    #[wasm_bindgen(js_name = isSolvable)]
+    pub fn is_solvable(&self) -> bool {
+        self.cube.is_solvable()
+    }
+
+    /// Returns the 54 facelet colors, in the format read by `fromFacelets`.
+    pub fn facelets(&self) -> String {
+        self.cube.facelets()
+    }
+
+    /// Renders the cube as an unfolded net of facelet colors.
+    pub fn render(&self) -> String {
+        self.cube.to_string()
+    }
+
+    /// Returns the one-hot representation as a `Uint8Array` of 480 zeros and ones.
+    pub fn representation(&self) -> Vec<u8> {
+        self.cube
+            .representation()
+            .iter()
+            .map(|&state| state as u8)
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = clone)]
+    pub fn copy(&self) -> WasmCube {
+        *self
+    }
+
+    pub fn equals(&self, other: &WasmCube) -> bool {
+        self == other
+    }
+}
+
+impl WasmCube {
+    fn try_apply_moves(&mut self, moves: &str) -> Result<(), CubeError> {
+        let mut cube = self.cube;
+        for turn in Turn::parse_sequence(moves)? {
+            cube.turn(turn as u8)?;
+        }
+        self.cube = cube;
+        Ok(())
+    }
+}
+
+/// Parses a sequence of moves such as `"R U R' U'"` into the indices of their `Turn`.
+#[wasm_bindgen(js_name = parseMoves)]
+pub fn parse_moves(moves: &str) -> Result<Vec<u8>, JsError> {
+    Ok(Turn::parse_sequence(moves)?
+        .into_iter()
+        .map(|turn| turn as u8)
+        .collect())
+}
+
+/// Returns true if the cube described by a facelet string can be solved.
+///
+/// Throws if the string does not describe a cube, see `Cube::from_facelets`.
+#[wasm_bindgen(js_name = checkFacelets)]
+pub fn check_facelets(facelets: &str) -> Result<bool, JsError> {
+    Ok(Cube::from_facelets(facelets, MetricKind::HalfTurnMetric)?.is_solvable())
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    use super::{check_facelets, parse_moves, WasmCube};
+    use crate::{MetricKind, Turn};
+
+    const SOLVED: &str = "WWWWWWWWWYYYYYYYYYGGGGGGGGGBBBBBBBBBRRRRRRRRROOOOOOOOO";
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn turn_test() {
+        let mut cube = WasmCube::new(MetricKind::HalfTurnMetric);
+        assert!(cube.solved());
+
+        cube.turn(Turn::F).unwrap();
+        assert!(!cube.solved());
+        assert!(cube.is_solvable());
+
+        cube.turn(Turn::F_).unwrap();
+        assert!(cube.solved());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn apply_moves_test() {
+        let mut cube = WasmCube::new(MetricKind::HalfTurnMetric);
+        cube.apply_moves("R U R' U' U R U' R'").unwrap();
+        assert!(cube.solved());
+
+        cube.apply_moves("L2 D").unwrap();
+        let mut expected = WasmCube::new(MetricKind::HalfTurnMetric);
+        expected.turn(Turn::L2).unwrap();
+        expected.turn(Turn::D).unwrap();
+        assert!(cube.equals(&expected));
+
+        assert_eq!(
+            parse_moves("L2 D").unwrap(),
+            vec![Turn::L2 as u8, Turn::D as u8]
+        );
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn invalid_moves_leave_cube_unchanged_test() {
+        let mut cube = WasmCube::new(MetricKind::QuarterTurnMetric);
+        assert!(cube.try_apply_moves("R U2").is_err());
+        assert!(cube.try_apply_moves("R Q").is_err());
+        assert!(cube.solved());
+    }
+
+    #[wasm_bindgen_test(unsupported = test)]
+    fn facelets_test() {
+        let mut cube = WasmCube::new(MetricKind::HalfTurnMetric);
+        assert_eq!(cube.facelets(), SOLVED);
+        assert!(check_facelets(SOLVED).unwrap());
+
+        cube.scramble(25);
+        let copy = WasmCube::from_facelets(&cube.facelets(), MetricKind::HalfTurnMetric).unwrap();
+        assert!(copy.equals(&cube));
+        assert_eq!(copy.render(), cube.render());
+        assert_eq!(
+            copy.representation().iter().filter(|&&s| s == 1).count(),
+            20
+        );
+    }
+
+    // `JsError` can only be created inside a JavaScript runtime
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen_test]
+    fn errors_test() {
+        let mut cube = WasmCube::new(MetricKind::QuarterTurnMetric);
+        assert!(cube.turn(Turn::U2).is_err());
+        assert!(cube.apply_moves("R X").is_err());
+        assert!(parse_moves("R3").is_err());
+        assert!(check_facelets("WWW").is_err());
+        assert!(
+            WasmCube::from_facelets(&SOLVED.replace('G', "W"), MetricKind::HalfTurnMetric).is_err()
+        );
+    }
+}
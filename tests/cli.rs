@@ -0,0 +1,179 @@
+use std::process::{Command, Output};
+
+const SOLVED: &str = "WWWWWWWWWYYYYYYYYYGGGGGGGGGBBBBBBBBBRRRRRRRRROOOOOOOOO";
+
+fn rubikscube(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rubikscube"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn scramble_is_reproducible_test() {
+    let first = rubikscube(&["scramble", "--seed", "42", "--length", "10"]);
+    let second = rubikscube(&["scramble", "--seed", "42", "--length", "10"]);
+    assert!(first.status.success());
+    assert_eq!(stdout(&first), stdout(&second));
+
+    let output = stdout(&first);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0].split_whitespace().count(), 10);
+
+    let applied = rubikscube(&["apply", lines[0]]);
+    assert_eq!(stdout(&applied).trim(), lines[1]);
+}
+
+#[test]
+fn apply_and_solve_test() {
+    let applied = rubikscube(&["apply", "R U2 F'"]);
+    assert!(applied.status.success());
+    let facelets = stdout(&applied);
+
+    let solved = rubikscube(&["solve", facelets.trim()]);
+    assert!(solved.status.success());
+    assert_eq!(stdout(&solved).trim(), "F U2 R_");
+
+    let undone = rubikscube(&["apply", "F U2 R_", "--from", facelets.trim()]);
+    assert_eq!(stdout(&undone).trim(), SOLVED);
+}
+
+#[test]
+fn solve_methods_test() {
+    let scramble = "R U2 F' L D B2 R' U";
+    let applied = rubikscube(&["apply", scramble]);
+    let facelets = stdout(&applied);
+
+    for (method, num_steps) in [("thistlethwaite", 4), ("beginner", 7)] {
+        let solved = rubikscube(&["solve", "--method", method, facelets.trim()]);
+        assert!(solved.status.success());
+        let output = stdout(&solved);
+        let steps: Vec<&str> = output
+            .lines()
+            .map(|line| line.rsplit_once(':').unwrap().1.trim())
+            .collect();
+        assert_eq!(steps.len(), num_steps);
+
+        let undone = rubikscube(&["apply", &steps.join(" "), "--from", facelets.trim()]);
+        assert_eq!(stdout(&undone).trim(), SOLVED);
+    }
+}
+
+#[test]
+fn analyse_test() {
+    let analysed = rubikscube(&["analyse", "--scramble", "U R U' R'", "R U R' U'"]);
+    assert!(analysed.status.success());
+    let output = stdout(&analysed);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines[0], "cross face: W");
+    assert_eq!(lines[1], "cross: skipped");
+    assert_eq!(lines[5], "F2L 4: R U R_ (3 turns)");
+    assert_eq!(lines[6], "OLL: skipped");
+    assert_eq!(lines[7], "PLL: U_ (1 turn)");
+
+    let unsolved = rubikscube(&["analyse", "--scramble", "U R U' R'", "R U"]);
+    assert_eq!(unsolved.status.code(), Some(1));
+    assert!(stdout(&unsolved).ends_with("unsolved after: R U\n"));
+}
+
+#[test]
+fn render_test() {
+    let svg = rubikscube(&["render", "--format", "svg", "--moves", "R"]);
+    assert!(svg.status.success());
+    assert_eq!(stdout(&svg).matches("<rect").count(), 54);
+
+    let ansi = rubikscube(&["render", SOLVED]);
+    assert_eq!(stdout(&ansi).lines().count(), 9);
+}
+
+#[test]
+fn check_test() {
+    let check = rubikscube(&["check", SOLVED]);
+    assert!(check.status.success());
+    assert!(stdout(&check).ends_with("solvable\n"));
+
+    // swapping two facelets of the same edge flips it
+    let mut flipped: Vec<char> = SOLVED.chars().collect();
+    flipped.swap(10, 28);
+    let flipped: String = flipped.into_iter().collect();
+    let check = rubikscube(&["check", &flipped]);
+    assert_eq!(check.status.code(), Some(1));
+    assert!(stdout(&check).contains("edges flipped"));
+}
+
+#[test]
+fn exit_codes_test() {
+    assert_eq!(rubikscube(&["apply", "R Q"]).status.code(), Some(3));
+    assert_eq!(
+        rubikscube(&["--metric", "qtm", "apply", "R2"])
+            .status
+            .code(),
+        Some(3)
+    );
+    assert_eq!(rubikscube(&["check", "WWW"]).status.code(), Some(4));
+    assert_eq!(rubikscube(&["frobnicate"]).status.code(), Some(2));
+}
+
+#[test]
+fn dataset_is_reproducible_test() {
+    let dir = std::env::temp_dir();
+    let paths: Vec<_> = (0..2)
+        .map(|i| {
+            dir.join(format!(
+                "rubikscube-dataset-{}-{}.npy",
+                std::process::id(),
+                i
+            ))
+        })
+        .collect();
+    for path in &paths {
+        let output = rubikscube(&[
+            "dataset",
+            "--output",
+            path.to_str().unwrap(),
+            "--samples",
+            "50",
+            "--max-depth",
+            "3",
+            "--seed",
+            "9",
+            "--encoding",
+            "packed",
+            "--solver-depth",
+            "3",
+        ]);
+        assert!(output.status.success());
+    }
+
+    let first = std::fs::read(&paths[0]).unwrap();
+    let second = std::fs::read(&paths[1]).unwrap();
+    for path in &paths {
+        std::fs::remove_file(path).unwrap();
+    }
+    assert!(first.starts_with(b"\x93NUMPY"));
+    assert_eq!(first.len(), 128 + 50 * (20 + 4 + 4));
+    assert_eq!(first, second);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn dataset_write_error_test() {
+    // every write to /dev/full fails, the buffered records only when flushed
+    let output = rubikscube(&[
+        "dataset",
+        "--output",
+        "/dev/full",
+        "--samples",
+        "5",
+        "--max-depth",
+        "2",
+        "--encoding",
+        "packed",
+    ]);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("could not write /dev/full"));
+}
@@ -0,0 +1,120 @@
+import copy
+
+import numpy as np
+import pytest
+
+import rubikscube
+from rubikscube import Cube, CubeBatch, MetricKind, Turn
+
+
+def test_new_cube_is_solved():
+    cube = Cube()
+    assert cube.solved()
+    assert cube.is_solvable()
+    assert cube.turn_metric == MetricKind.HalfTurnMetric
+    assert Cube(MetricKind.QuarterTurnMetric).turn_metric == MetricKind.QuarterTurnMetric
+
+
+def test_turn_and_inverse():
+    cube = Cube()
+    cube.turn(Turn.L)
+    cube.turn(Turn.F)
+    assert not cube.solved()
+    cube.turn(Turn.F_)
+    cube.turn(int(Turn.L_))
+    assert cube.solved()
+
+
+def test_invalid_turn_raises():
+    cube = Cube(MetricKind.QuarterTurnMetric)
+    with pytest.raises(ValueError):
+        cube.turn(Turn.L2)
+    with pytest.raises(ValueError):
+        cube.turn(18)
+
+
+def test_module_functions():
+    cube = Cube()
+    rubikscube.turn(cube, Turn.U2)
+    assert not rubikscube.solved(cube)
+    rubikscube.turn(cube, Turn.U2)
+    assert rubikscube.solved(cube)
+
+    rubikscube.scramble(cube, 20)
+    assert rubikscube.is_solvable(cube)
+
+
+def test_copy_and_equality():
+    cube = Cube()
+    cube.scramble(10)
+    other = copy.copy(cube)
+    assert other == cube
+    other.turn(Turn.R)
+    assert other != cube
+
+
+def test_state_round_trip():
+    cube = Cube()
+    cube.scramble(25)
+    other = Cube()
+    other.set_state(*cube.get_state())
+    assert other == cube
+
+    with pytest.raises(ValueError):
+        other.set_state([0] * 12, [0] * 8, [0] * 12, list(range(8)))
+
+
+def test_representation():
+    cube = Cube()
+    repr_ = cube.representation()
+    assert isinstance(repr_, np.ndarray)
+    assert repr_.dtype == np.bool_
+    assert repr_.shape == (rubikscube.REPRESENTATION_SIZE,)
+    assert repr_.sum() == 20
+    assert repr_.flags.owndata
+
+    # the array owns its buffer and is not affected by later turns
+    cube.turn(Turn.R)
+    assert not np.array_equal(repr_, cube.representation())
+
+
+def test_batch_step():
+    batch = CubeBatch(4)
+    assert len(batch) == 4
+    assert batch.solved().all()
+
+    twists = np.array([int(t) for t in (Turn.L, Turn.R, Turn.F, Turn.B)], dtype=np.uint8)
+    obs, solved = batch.step(twists)
+    assert obs.shape == (4, rubikscube.REPRESENTATION_SIZE)
+    assert obs.dtype == np.float32
+    assert (obs.sum(axis=1) == 20).all()
+    assert not solved.any()
+    np.testing.assert_array_equal(obs[0], batch.get(0).representation())
+
+    inverses = np.array([int(t) for t in (Turn.L_, Turn.R_, Turn.F_, Turn.U)], dtype=np.uint8)
+    _, solved = batch.step(inverses)
+    assert solved.tolist() == [True, True, True, False]
+
+
+def test_batch_matches_single_cubes():
+    batch = CubeBatch(3)
+    batch.scramble(20)
+    for idx in range(len(batch)):
+        cube = batch.get(idx)
+        np.testing.assert_array_equal(batch.representation()[idx], cube.representation())
+
+    batch.set(0, Cube())
+    assert batch.solved()[0]
+    assert batch.reset_solved(5) >= 1
+
+
+def test_batch_errors():
+    batch = CubeBatch(2)
+    with pytest.raises(ValueError):
+        batch.step(np.zeros(3, dtype=np.uint8))
+    with pytest.raises(ValueError):
+        batch.turn(np.array([0, 18], dtype=np.uint8))
+    with pytest.raises(IndexError):
+        batch.get(2)
+    with pytest.raises(ValueError):
+        CubeBatch.from_cubes([Cube(), Cube(MetricKind.QuarterTurnMetric)])
@@ -0,0 +1,95 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+const SOLVED: &str = "WWWWWWWWWYYYYYYYYYGGGGGGGGGBBBBBBBBBRRRRRRRRROOOOOOOOO";
+
+/// Runs the REPL on `script` and returns the lines it printed that are not part of a drawing.
+fn run(script: &str) -> Vec<String> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rubikscube-repl"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line
+                    .split_whitespace()
+                    .all(|facelet| facelet.len() == 1 && "WYGBRO".contains(facelet))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn undo_redo_test() {
+    let output = run("R U R' U'\nundo 2\nhistory\nsolved\nredo 5\nhistory\nundo 4\nsolved\n");
+    assert_eq!(output, vec!["R U | R_ U_", "false", "R U R_ U_ |", "true"]);
+}
+
+#[test]
+fn moves_after_undo_replace_redo_test() {
+    let output = run("R U\nundo\nF\nredo\nhistory\n");
+    assert_eq!(output, vec!["R F |"]);
+}
+
+#[test]
+fn set_and_query_test() {
+    let output = run(&format!(
+        "R\nset {}\nhistory\nsolved\nU2\ncycles\nfacelets\n",
+        SOLVED
+    ));
+    assert_eq!(output[0], "|");
+    assert_eq!(output[1], "true");
+    assert_eq!(output[2], "edges    (0 2) (1 3)");
+    assert_eq!(output[3], "corners  (0 2) (1 3)");
+    assert_eq!(output.len(), 5);
+}
+
+#[test]
+fn solvable_test() {
+    let mut flipped: Vec<char> = SOLVED.chars().collect();
+    flipped.swap(10, 28);
+    let flipped: String = flipped.into_iter().collect();
+
+    let output = run(&format!("set {}\nsolvable\n", flipped));
+    assert_eq!(output, vec!["false", "an odd number of edges is flipped"]);
+}
+
+#[test]
+fn errors_and_quit_test() {
+    let output = run("R Q\nset WWW\nhistory\nquit\nsolved\n");
+    assert_eq!(
+        output,
+        vec![
+            "error: Invalid move, could not parse \"Q\"",
+            "error: Invalid facelet string, Expected 54 facelets got 3",
+            "|",
+        ]
+    );
+}
+
+#[test]
+fn invalid_count_test() {
+    let output = run("R U\nundo abc\nredo -1\nhistory\n");
+    assert_eq!(
+        output,
+        vec![
+            "error: Invalid count, Expected a number of moves got \"abc\"",
+            "error: Invalid count, Expected a number of moves got \"-1\"",
+            "R U |",
+        ]
+    );
+}